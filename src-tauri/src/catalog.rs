@@ -0,0 +1,382 @@
+use crate::installer::{self, HostApp, InstallResult};
+use crate::integrity;
+use crate::settings;
+use crate::version;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// A repository index, e.g.
+///
+/// ```json
+/// {
+///   "name": "Studio panels",
+///   "packages": [
+///     { "id": "com.studio.tool", "name": "Tool", "version": "1.4.0",
+///       "url": "tool-1.4.0.zxp", "sha256": "…",
+///       "hosts": [{ "name": "AEFT", "version": "[18.0,99.9]" }] }
+///   ]
+/// }
+/// ```
+///
+/// Relative package URLs are resolved against the index location.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CatalogIndex {
+    #[serde(default)]
+    pub name: String,
+    #[serde(default)]
+    pub packages: Vec<CatalogPackage>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CatalogPackage {
+    pub id: String,
+    #[serde(default)]
+    pub name: String,
+    pub version: String,
+    #[serde(default)]
+    pub description: String,
+    pub url: String,
+    pub sha256: String,
+    #[serde(default)]
+    pub hosts: Vec<HostApp>,
+    #[serde(default)]
+    pub changelog: String,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CatalogStatus {
+    /// Not installed on this machine
+    Installable,
+    /// Installed, catalog has a newer version
+    Updatable,
+    /// Installed at the catalog version
+    Installed,
+    /// Installed version is newer than the catalog's
+    Newer,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CatalogEntry {
+    pub package: CatalogPackage,
+    /// Index location the package was listed in
+    pub source: String,
+    pub status: CatalogStatus,
+    pub installed_version: Option<String>,
+}
+
+/// True for http(s) locations, false for local paths and file shares
+fn is_remote(location: &str) -> bool {
+    location.starts_with("http://") || location.starts_with("https://")
+}
+
+/// Read a local path, `file://` URL or http(s) URL into memory
+pub fn read_location(location: &str) -> Result<Vec<u8>, String> {
+    if is_remote(location) {
+        let response = reqwest::blocking::get(location)
+            .map_err(|e| format!("Cannot reach {}: {}", location, e))?;
+        if !response.status().is_success() {
+            return Err(format!("{} returned HTTP {}", location, response.status()));
+        }
+        response
+            .bytes()
+            .map(|b| b.to_vec())
+            .map_err(|e| format!("Cannot download {}: {}", location, e))
+    } else {
+        let path = location.strip_prefix("file://").unwrap_or(location);
+        fs::read(path).map_err(|e| format!("Cannot read {}: {}", path, e))
+    }
+}
+
+/// Resolve a package URL relative to the index that listed it
+fn resolve_location(index_location: &str, url: &str) -> String {
+    if is_remote(url) || url.starts_with("file://") || Path::new(url).is_absolute() {
+        return url.to_string();
+    }
+    if is_remote(index_location) {
+        let base = index_location.rsplit_once('/').map(|(b, _)| b).unwrap_or(index_location);
+        return format!("{}/{}", base, url.trim_start_matches("./"));
+    }
+    let index_path = Path::new(index_location.strip_prefix("file://").unwrap_or(index_location));
+    index_path
+        .parent()
+        .unwrap_or(Path::new(""))
+        .join(url)
+        .to_string_lossy()
+        .to_string()
+}
+
+/// Load and parse a repository index
+pub fn load_catalog(location: &str) -> Result<CatalogIndex, String> {
+    let bytes = read_location(location)?;
+    let mut index: CatalogIndex = serde_json::from_slice(&bytes)
+        .map_err(|e| format!("Invalid catalog index {}: {}", location, e))?;
+
+    for package in &mut index.packages {
+        package.url = resolve_location(location, &package.url);
+        package.sha256 = package.sha256.trim().to_lowercase();
+        if package.name.is_empty() {
+            package.name = package.id.clone();
+        }
+        for host in &mut package.hosts {
+            host.name = installer::format_host_name(&host.name);
+        }
+    }
+    Ok(index)
+}
+
+/// Locations to read: the given one, or every configured catalog source
+fn catalog_locations(source: Option<&str>) -> Vec<String> {
+    match source {
+        Some(s) => vec![s.to_string()],
+        None => settings::load_settings().catalog_sources,
+    }
+}
+
+/// List catalog packages (newest version per id) and compare them with installed extensions
+pub fn browse_catalog(source: Option<&str>) -> Result<Vec<CatalogEntry>, String> {
+    let locations = catalog_locations(source);
+    if locations.is_empty() {
+        return Err("No catalog sources configured".to_string());
+    }
+
+    let mut newest: HashMap<String, (CatalogPackage, String)> = HashMap::new();
+    let mut errors = Vec::new();
+    for location in &locations {
+        match load_catalog(location) {
+            Ok(index) => {
+                for package in index.packages {
+                    let keep = newest
+                        .get(&package.id)
+                        .map(|(p, _)| version::is_newer(&package.version, &p.version))
+                        .unwrap_or(true);
+                    if keep {
+                        newest.insert(package.id.clone(), (package, location.clone()));
+                    }
+                }
+            }
+            Err(e) => {
+                log::warn!("Skipping catalog {}: {}", location, e);
+                errors.push(e);
+            }
+        }
+    }
+    // Only fail outright if no source could be read at all
+    if errors.len() == locations.len() {
+        return Err(errors.join("; "));
+    }
+
+    let installed: HashMap<String, String> = installer::list_extensions()
+        .into_iter()
+        .map(|e| (e.id, e.version))
+        .collect();
+
+    let mut entries: Vec<CatalogEntry> = newest
+        .into_values()
+        .map(|(package, source)| {
+            let installed_version = installed.get(&package.id).cloned();
+            let status = match &installed_version {
+                None => CatalogStatus::Installable,
+                Some(v) => match version::compare_versions(&package.version, v) {
+                    std::cmp::Ordering::Greater => CatalogStatus::Updatable,
+                    std::cmp::Ordering::Equal => CatalogStatus::Installed,
+                    std::cmp::Ordering::Less => CatalogStatus::Newer,
+                },
+            };
+            CatalogEntry { package, source, status, installed_version }
+        })
+        .collect();
+
+    entries.sort_by_key(|e| e.package.name.to_lowercase());
+    Ok(entries)
+}
+
+/// Download (or copy) a package into the download folder and check its SHA-256
+pub fn fetch_package(location: &str, expected_sha256: &str, file_stem: &str) -> Result<PathBuf, String> {
+    let downloads = installer::get_data_folder().join("downloads");
+    fs::create_dir_all(&downloads).map_err(|e| format!("Cannot create download folder: {}", e))?;
+
+    let target = downloads.join(format!("{}.zxp", installer::folder_name_for(file_stem)?));
+    let bytes = read_location(location)?;
+    fs::write(&target, bytes).map_err(|e| format!("Cannot save package: {}", e))?;
+
+    let actual = integrity::sha256_file(&target)?;
+    if !actual.eq_ignore_ascii_case(expected_sha256.trim()) {
+        let _ = fs::remove_file(&target);
+        return Err(format!(
+            "Hash mismatch for {}: expected {}, got {}",
+            location, expected_sha256, actual
+        ));
+    }
+    Ok(target)
+}
+
+/// Fetch a catalog package, verify its hash and install it
+pub fn install_package(package: &CatalogPackage) -> InstallResult {
+    install_package_with(package, installer::install_extension)
+}
+
+/// Fetch a catalog package, verify its hash and install it with `install`
+/// (e.g. `installer::install_extension_system`)
+pub fn install_package_with(package: &CatalogPackage, install: fn(&str) -> InstallResult) -> InstallResult {
+    let stem = format!("{}-{}", package.id, package.version);
+    let downloaded = match fetch_package(&package.url, &package.sha256, &stem) {
+        Ok(p) => p,
+        Err(e) => {
+            return InstallResult {
+                success: false,
+                message: e,
+                extension: None,
+                payload: Vec::new(),
+            };
+        }
+    };
+
+    let result = install(&downloaded.to_string_lossy());
+    let _ = fs::remove_file(&downloaded);
+    result
+}
+
+/// Install the newest catalog version of an extension
+pub fn install_from_catalog(extension_id: &str, source: Option<&str>) -> InstallResult {
+    let entry = match browse_catalog(source) {
+        Ok(entries) => entries.into_iter().find(|e| e.package.id == extension_id),
+        Err(e) => {
+            return InstallResult {
+                success: false,
+                message: e,
+                extension: None,
+                payload: Vec::new(),
+            };
+        }
+    };
+
+    match entry {
+        Some(entry) => install_package(&entry.package),
+        None => InstallResult {
+            success: false,
+            message: format!("'{}' is not listed in the catalog", extension_id),
+            extension: None,
+            payload: Vec::new(),
+        },
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::{write_zxp, Sandbox};
+
+    fn write_index(path: &Path, packages: &[(&str, &str, &str, &str)]) {
+        let packages: Vec<serde_json::Value> = packages
+            .iter()
+            .map(|(id, version, url, sha256)| {
+                serde_json::json!({ "id": id, "version": version, "url": url, "sha256": sha256,
+                    "hosts": [{ "name": "photoshop", "version": "[22.0,99.9]" }] })
+            })
+            .collect();
+        fs::create_dir_all(path.parent().unwrap()).unwrap();
+        fs::write(path, serde_json::json!({ "name": "Test", "packages": packages }).to_string()).unwrap();
+    }
+
+    #[test]
+    fn resolves_package_urls_against_the_index() {
+        assert_eq!(
+            resolve_location("https://example.com/repo/index.json", "./tool.zxp"),
+            "https://example.com/repo/tool.zxp"
+        );
+        assert_eq!(
+            resolve_location("https://example.com/repo/index.json", "https://cdn.example.com/tool.zxp"),
+            "https://cdn.example.com/tool.zxp"
+        );
+        assert_eq!(
+            resolve_location("file:///srv/repo/index.json", "tool.zxp"),
+            Path::new("/srv/repo").join("tool.zxp").to_string_lossy()
+        );
+        assert_eq!(resolve_location("/srv/repo/index.json", "/other/tool.zxp"), "/other/tool.zxp");
+    }
+
+    #[test]
+    fn load_catalog_normalizes_packages() {
+        let sandbox = Sandbox::new();
+        let index = sandbox.path("repo/index.json");
+        write_index(&index, &[("com.test.tool", "1.0.0", "tool.zxp", " ABCDEF ")]);
+
+        let catalog = load_catalog(&index.to_string_lossy()).unwrap();
+        let package = &catalog.packages[0];
+        assert_eq!(package.name, "com.test.tool");
+        assert_eq!(package.sha256, "abcdef");
+        assert_eq!(package.url, sandbox.path("repo/tool.zxp").to_string_lossy());
+        assert_eq!(package.hosts[0].name, installer::format_host_name("photoshop"));
+    }
+
+    #[test]
+    fn browse_keeps_the_newest_version_and_compares_with_installed() {
+        let sandbox = Sandbox::new();
+        let zxp = sandbox.path("in/tool.zxp");
+        write_zxp(&zxp, "com.test.tool", "1.0.0", &[]);
+        assert!(installer::install_extension(&zxp.to_string_lossy()).success);
+        let (first, second) = (sandbox.path("a/index.json"), sandbox.path("b/index.json"));
+        write_index(&first, &[("com.test.tool", "1.2.0", "tool.zxp", "00"), ("com.test.new", "1.0.0", "new.zxp", "00")]);
+        write_index(&second, &[("com.test.tool", "1.1.0", "tool.zxp", "00")]);
+
+        let mut settings = settings::load_settings();
+        settings.catalog_sources = vec![
+            first.to_string_lossy().to_string(),
+            second.to_string_lossy().to_string(),
+            sandbox.path("missing.json").to_string_lossy().to_string(),
+        ];
+        settings::save_settings(&settings).unwrap();
+
+        let entries = browse_catalog(None).unwrap();
+        assert_eq!(entries.len(), 2);
+        let tool = entries.iter().find(|e| e.package.id == "com.test.tool").unwrap();
+        assert_eq!(tool.package.version, "1.2.0");
+        assert_eq!(tool.status, CatalogStatus::Updatable);
+        assert_eq!(tool.installed_version.as_deref(), Some("1.0.0"));
+        let new = entries.iter().find(|e| e.package.id == "com.test.new").unwrap();
+        assert_eq!(new.status, CatalogStatus::Installable);
+    }
+
+    #[test]
+    fn installs_only_packages_matching_their_hash() {
+        let sandbox = Sandbox::new();
+        let zxp = sandbox.path("repo/tool.zxp");
+        write_zxp(&zxp, "com.test.tool", "1.0.0", &[]);
+        let sha256 = integrity::sha256_file(&zxp).unwrap();
+        let package = |sha256: &str| CatalogPackage {
+            id: "com.test.tool".to_string(),
+            name: "Tool".to_string(),
+            version: "1.0.0".to_string(),
+            description: String::new(),
+            url: zxp.to_string_lossy().to_string(),
+            sha256: sha256.to_string(),
+            hosts: Vec::new(),
+            changelog: String::new(),
+        };
+
+        let rejected = install_package(&package(&"0".repeat(64)));
+        assert!(!rejected.success);
+        assert!(rejected.message.contains("Hash mismatch"), "{}", rejected.message);
+        assert!(installer::list_extensions().is_empty());
+
+        assert!(install_package(&package(&sha256)).success);
+        assert_eq!(installer::list_extensions()[0].id, "com.test.tool");
+        // The download is removed once installed
+        let downloads = installer::get_data_folder().join("downloads");
+        assert_eq!(fs::read_dir(downloads).unwrap().count(), 0);
+    }
+
+    #[test]
+    fn download_names_cannot_leave_the_download_folder() {
+        let sandbox = Sandbox::new();
+        let zxp = sandbox.path("repo/tool.zxp");
+        write_zxp(&zxp, "com.test.tool", "1.0.0", &[]);
+        let sha256 = integrity::sha256_file(&zxp).unwrap();
+
+        let fetched = fetch_package(&zxp.to_string_lossy(), &sha256, "../../escape").unwrap();
+        assert_eq!(fetched.parent(), Some(installer::get_data_folder().join("downloads").as_path()));
+    }
+}
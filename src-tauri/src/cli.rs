@@ -0,0 +1,174 @@
+use crate::elevate;
+use crate::plan::{self, ActionResult, PlanAction, PlanItem};
+use crate::sync;
+
+const USAGE: &str = "Usage: zxp-magic sync <lockfile> [--apply] [--remove-unlisted | --keep-unlisted] [--json]";
+
+#[cfg(target_os = "windows")]
+#[link(name = "kernel32")]
+extern "system" {
+    fn AttachConsole(process_id: u32) -> i32;
+}
+
+/// Windows release builds use the GUI subsystem and start without a console.
+/// Attach to the console of the shell that started us so CLI output shows up
+/// there; redirected output keeps going where it was redirected.
+#[cfg(target_os = "windows")]
+fn attach_console() {
+    const ATTACH_PARENT_PROCESS: u32 = u32::MAX;
+    // SAFETY: plain Win32 call; failing (no parent console) is harmless
+    unsafe {
+        AttachConsole(ATTACH_PARENT_PROCESS);
+    }
+}
+
+#[cfg(not(target_os = "windows"))]
+fn attach_console() {}
+
+/// Handle command-line invocations; `None` means no subcommand was given and the GUI should start
+pub fn run(args: &[String]) -> Option<i32> {
+    match args.first().map(String::as_str) {
+        Some("sync") => {
+            attach_console();
+            Some(sync_command(&args[1..]))
+        }
+        // Re-invocation by the elevation broker; not listed in the usage text
+        Some("elevated") => Some(elevate::serve(&args[1..])),
+        Some("help") | Some("--help") | Some("-h") => {
+            attach_console();
+            println!("{}", USAGE);
+            Some(0)
+        }
+        _ => None,
+    }
+}
+
+fn action_label(action: PlanAction) -> &'static str {
+    match action {
+        PlanAction::Install => "install",
+        PlanAction::Upgrade => "upgrade",
+        PlanAction::Downgrade => "downgrade",
+        PlanAction::Remove => "remove",
+        PlanAction::Unchanged => "ok",
+    }
+}
+
+fn print_item(item: &PlanItem) {
+    let from = item.installed_version.as_deref().unwrap_or("-");
+    let to = item.target_version.as_deref().unwrap_or("-");
+    println!("{:<10} {:<40} {} -> {}", action_label(item.action), item.extension_id, from, to);
+}
+
+fn print_result(result: &ActionResult) {
+    let status = if result.success { "done" } else { "FAILED" };
+    println!("{:<10} {:<40} {}: {}", action_label(result.action), result.extension_id, status, result.message);
+}
+
+fn sync_command(args: &[String]) -> i32 {
+    let mut lockfile = None;
+    let mut apply = false;
+    let mut json = false;
+    let mut remove_unlisted = None;
+    for arg in args {
+        match arg.as_str() {
+            "--apply" => apply = true,
+            "--json" => json = true,
+            "--remove-unlisted" => remove_unlisted = Some(true),
+            "--keep-unlisted" => remove_unlisted = Some(false),
+            other if lockfile.is_none() && !other.starts_with("--") => lockfile = Some(other.to_string()),
+            other => {
+                eprintln!("Unknown argument '{}'\n{}", other, USAGE);
+                return 2;
+            }
+        }
+    }
+    let Some(lockfile) = lockfile else {
+        eprintln!("{}", USAGE);
+        return 2;
+    };
+
+    let plan = match sync::plan_sync(&lockfile, remove_unlisted) {
+        Ok(p) => p,
+        Err(e) => {
+            eprintln!("{}", e);
+            return 1;
+        }
+    };
+
+    if !apply {
+        if json {
+            println!("{}", serde_json::to_string_pretty(&plan).unwrap_or_default());
+        } else {
+            plan.items.iter().for_each(print_item);
+            for missing in &plan.unresolved {
+                println!("no package satisfies {}", missing);
+            }
+            if plan.is_noop() {
+                println!("Everything is in sync.");
+            } else {
+                println!("Run again with --apply to make these changes.");
+            }
+        }
+        return 0;
+    }
+
+    let results = plan::execute(&plan.items);
+    if json {
+        println!("{}", serde_json::to_string_pretty(&results).unwrap_or_default());
+    } else {
+        results.iter().for_each(print_result);
+    }
+    if results.iter().all(|r| r.success) {
+        0
+    } else {
+        1
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::{write_zxp, Sandbox};
+    use std::fs;
+
+    fn args(list: &[&str]) -> Vec<String> {
+        list.iter().map(|a| a.to_string()).collect()
+    }
+
+    #[test]
+    fn no_subcommand_starts_the_gui() {
+        assert_eq!(run(&[]), None);
+        assert_eq!(run(&args(&["/path/to/panel.zxp"])), None);
+        assert_eq!(run(&args(&["--help"])), Some(0));
+    }
+
+    #[test]
+    fn sync_rejects_bad_arguments() {
+        assert_eq!(run(&args(&["sync"])), Some(2));
+        assert_eq!(run(&args(&["sync", "panels.toml", "--force"])), Some(2));
+        assert_eq!(run(&args(&["sync", "a.toml", "b.toml"])), Some(2));
+    }
+
+    #[test]
+    fn sync_reports_unreadable_lockfile() {
+        let sandbox = Sandbox::new();
+        let missing = sandbox.path("missing.toml");
+        assert_eq!(run(&args(&["sync", &missing.to_string_lossy()])), Some(1));
+    }
+
+    #[test]
+    fn sync_plans_and_applies_a_lockfile() {
+        let sandbox = Sandbox::new();
+        let zxp = sandbox.path("panel.zxp");
+        write_zxp(&zxp, "com.test.cli", "1.0.0", &[("index.html", "<html></html>")]);
+        let lockfile = sandbox.path("panels.toml");
+        let source = zxp.to_string_lossy().replace('\\', "\\\\");
+        fs::write(&lockfile, format!("[[extension]]\nid = \"com.test.cli\"\nsource = \"{}\"\n", source)).unwrap();
+        let lockfile = lockfile.to_string_lossy().to_string();
+
+        assert_eq!(run(&args(&["sync", &lockfile, "--json"])), Some(0));
+        assert!(crate::integrity::load_manifest("com.test.cli").is_none());
+        assert_eq!(run(&args(&["sync", &lockfile, "--apply"])), Some(0));
+        assert!(crate::integrity::load_manifest("com.test.cli").is_some());
+    }
+}
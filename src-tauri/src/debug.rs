@@ -0,0 +1,346 @@
+use crate::installer;
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// First port handed out; CEP samples conventionally start at 8088
+const FIRST_PORT: u16 = 8088;
+
+/// One `<Host Name=".." Port=".."/>` entry of a `.debug` file
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DebugHost {
+    /// Host code as CEP expects it, e.g. "PHXS"
+    pub host: String,
+    pub port: u16,
+    /// `http://localhost:<port>`; ignored when writing
+    #[serde(default)]
+    pub url: String,
+}
+
+/// Debug ports of one extension (panel) of a bundle
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DebugTarget {
+    pub extension_id: String,
+    pub hosts: Vec<DebugHost>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DebugConfig {
+    pub install_path: String,
+    /// True when the extension has a `.debug` file; otherwise `extensions`
+    /// is a suggestion with free ports
+    pub exists: bool,
+    pub extensions: Vec<DebugTarget>,
+}
+
+fn debug_file(dir: &Path) -> PathBuf {
+    dir.join(".debug")
+}
+
+fn url(port: u16) -> String {
+    format!("http://localhost:{}", port)
+}
+
+/// Panel ids and raw host codes from an extension's manifest
+fn manifest_targets(dir: &Path) -> Result<(Vec<String>, Vec<String>), String> {
+    let content = fs::read_to_string(dir.join("CSXS").join("manifest.xml"))
+        .map_err(|_| format!("No extension manifest in {}", dir.display()))?;
+    let mut ids: Vec<String> = Vec::new();
+    let mut hosts: Vec<String> = Vec::new();
+    let mut in_extension_list = false;
+    for line in content.lines() {
+        if line.contains("<ExtensionList") {
+            in_extension_list = true;
+        }
+        if in_extension_list && line.contains("<Extension ") {
+            if let Some(id) = installer::extract_attr(line, "Id") {
+                if !ids.contains(&id) {
+                    ids.push(id);
+                }
+            }
+        }
+        if line.contains("</ExtensionList>") {
+            in_extension_list = false;
+        }
+        if line.contains("<Host ") {
+            if let Some(host) = installer::extract_attr(line, "Name") {
+                if !hosts.contains(&host) {
+                    hosts.push(host);
+                }
+            }
+        }
+    }
+    if ids.is_empty() {
+        ids = installer::manifest_extension_ids(dir).into_iter().take(1).collect();
+    }
+    if ids.is_empty() || hosts.is_empty() {
+        return Err("The manifest does not list any extension or host app".to_string());
+    }
+    Ok((ids, hosts))
+}
+
+/// Parse a `.debug` file
+fn parse_debug_file(content: &str) -> Vec<DebugTarget> {
+    let mut targets: Vec<DebugTarget> = Vec::new();
+    for line in content.lines() {
+        if line.contains("<Extension ") {
+            if let Some(id) = installer::extract_attr(line, "Id") {
+                targets.push(DebugTarget {
+                    extension_id: id,
+                    hosts: Vec::new(),
+                });
+            }
+        }
+        if line.contains("<Host ") {
+            let host = installer::extract_attr(line, "Name");
+            let port = installer::extract_attr(line, "Port").and_then(|p| p.parse::<u16>().ok());
+            if let (Some(target), Some(host), Some(port)) = (targets.last_mut(), host, port) {
+                target.hosts.push(DebugHost { host, port, url: url(port) });
+            }
+        }
+    }
+    targets
+}
+
+fn render_debug_file(targets: &[DebugTarget]) -> String {
+    let mut xml = String::from("<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n<ExtensionList>\n");
+    for target in targets {
+        xml.push_str(&format!("  <Extension Id=\"{}\">\n    <HostList>\n", target.extension_id));
+        for host in &target.hosts {
+            xml.push_str(&format!("      <Host Name=\"{}\" Port=\"{}\"/>\n", host.host, host.port));
+        }
+        xml.push_str("    </HostList>\n  </Extension>\n");
+    }
+    xml.push_str("</ExtensionList>\n");
+    xml
+}
+
+/// Ports taken by the `.debug` files of every other installed extension
+fn used_ports(except: &Path) -> HashSet<u16> {
+    let except = fs::canonicalize(except).unwrap_or_else(|_| except.to_path_buf());
+    installer::get_all_extension_folders()
+        .iter()
+        .filter_map(|root| fs::read_dir(root).ok())
+        .flat_map(|entries| entries.flatten().map(|e| e.path()))
+        .filter(|dir| fs::canonicalize(dir).map(|d| d != except).unwrap_or(true))
+        .filter_map(|dir| fs::read_to_string(debug_file(&dir)).ok())
+        .flat_map(|content| parse_debug_file(&content))
+        .flat_map(|target| target.hosts.into_iter().map(|h| h.port))
+        .collect()
+}
+
+/// One port per panel and host, skipping ports other extensions already use
+fn suggest(dir: &Path) -> Result<Vec<DebugTarget>, String> {
+    let (ids, hosts) = manifest_targets(dir)?;
+    let used = used_ports(dir);
+    let mut free = (FIRST_PORT..=u16::MAX).filter(|p| !used.contains(p));
+    ids.into_iter()
+        .map(|extension_id| {
+            let hosts = hosts
+                .iter()
+                .map(|host| {
+                    let port = free.next().ok_or("No free debug port left")?;
+                    Ok(DebugHost {
+                        host: host.clone(),
+                        port,
+                        url: url(port),
+                    })
+                })
+                .collect::<Result<Vec<_>, String>>()?;
+            Ok(DebugTarget { extension_id, hosts })
+        })
+        .collect()
+}
+
+fn is_plain(value: &str) -> bool {
+    !value.is_empty() && !value.contains(['"', '<', '>', '&', '\n'])
+}
+
+fn validate(dir: &Path, targets: &[DebugTarget]) -> Result<(), String> {
+    let used = used_ports(dir);
+    let mut seen = HashSet::new();
+    for target in targets {
+        if !is_plain(&target.extension_id) {
+            return Err(format!("Invalid extension id '{}'", target.extension_id));
+        }
+        for host in &target.hosts {
+            if !is_plain(&host.host) {
+                return Err(format!("Invalid host code '{}'", host.host));
+            }
+            if host.port < 1024 {
+                return Err(format!("Port {} is reserved — use 1024 or higher", host.port));
+            }
+            if used.contains(&host.port) {
+                return Err(format!("Port {} is already used by another extension", host.port));
+            }
+            if !seen.insert(host.port) {
+                return Err(format!("Port {} is assigned twice", host.port));
+            }
+        }
+    }
+    Ok(())
+}
+
+fn open_extension(install_path: &str) -> Result<PathBuf, String> {
+    let dir = PathBuf::from(install_path);
+    if installer::read_manifest(&dir).is_none() {
+        return Err(format!("No extension manifest in {}", install_path));
+    }
+    Ok(dir)
+}
+
+/// The extension's `.debug` ports, or free ports to suggest if it has none
+pub fn get_debug_config(install_path: &str) -> Result<DebugConfig, String> {
+    let dir = open_extension(install_path)?;
+    let (exists, extensions) = match fs::read_to_string(debug_file(&dir)) {
+        Ok(content) => (true, parse_debug_file(&content)),
+        Err(_) => (false, suggest(&dir)?),
+    };
+    Ok(DebugConfig {
+        install_path: install_path.to_string(),
+        exists,
+        extensions,
+    })
+}
+
+/// Write the extension's `.debug` file, from `extensions` if given or with
+/// freshly assigned ports for every panel and host otherwise
+pub fn write_debug_file(install_path: &str, extensions: Option<Vec<DebugTarget>>) -> Result<DebugConfig, String> {
+    let dir = open_extension(install_path)?;
+    let mut extensions = match extensions {
+        Some(extensions) => extensions,
+        None => suggest(&dir)?,
+    };
+    validate(&dir, &extensions)?;
+    for host in extensions.iter_mut().flat_map(|t| t.hosts.iter_mut()) {
+        host.url = url(host.port);
+    }
+
+    let path = debug_file(&dir);
+    fs::write(&path, render_debug_file(&extensions)).map_err(|e| format!("Cannot write {}: {}", path.display(), e))?;
+    log::info!("Wrote {}", path.display());
+    Ok(DebugConfig {
+        install_path: install_path.to_string(),
+        exists: true,
+        extensions,
+    })
+}
+
+/// Delete the extension's `.debug` file
+pub fn remove_debug_file(install_path: &str) -> Result<(), String> {
+    let path = debug_file(&open_extension(install_path)?);
+    match fs::remove_file(&path) {
+        Ok(()) => Ok(()),
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(()),
+        Err(e) => Err(format!("Cannot remove {}: {}", path.display(), e)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::{manifest, Sandbox};
+
+    fn target(id: &str, hosts: &[(&str, u16)]) -> DebugTarget {
+        DebugTarget {
+            extension_id: id.to_string(),
+            hosts: hosts
+                .iter()
+                .map(|(host, port)| DebugHost {
+                    host: host.to_string(),
+                    port: *port,
+                    url: url(*port),
+                })
+                .collect(),
+        }
+    }
+
+    #[test]
+    fn parse_debug_file_groups_hosts_by_extension() {
+        let parsed = parse_debug_file(
+            r#"<?xml version="1.0" encoding="UTF-8"?>
+<ExtensionList>
+  <Extension Id="com.vendor.panel">
+    <HostList>
+      <Host Name="PHXS" Port="8088"/>
+      <Host Name="ILST" Port="8089"/>
+      <Host Name="IDSN" Port="not a port"/>
+    </HostList>
+  </Extension>
+  <Extension Id="com.vendor.panel.settings">
+    <HostList>
+      <Host Name="PHXS" Port="8090"/>
+    </HostList>
+  </Extension>
+</ExtensionList>"#,
+        );
+        assert_eq!(parsed.len(), 2);
+        assert_eq!(parsed[0].extension_id, "com.vendor.panel");
+        assert_eq!(parsed[0].hosts.len(), 2);
+        assert_eq!(parsed[0].hosts[1].host, "ILST");
+        assert_eq!(parsed[0].hosts[1].url, "http://localhost:8089");
+        assert_eq!(parsed[1].hosts[0].port, 8090);
+    }
+
+    #[test]
+    fn render_debug_file_round_trips() {
+        let targets = vec![
+            target("com.vendor.panel", &[("PHXS", 8088), ("AEFT", 8089)]),
+            target("com.vendor.other", &[("PPRO", 8090)]),
+        ];
+        let xml = render_debug_file(&targets);
+        assert!(xml.contains(r#"<Host Name="AEFT" Port="8089"/>"#));
+
+        let parsed = parse_debug_file(&xml);
+        assert_eq!(parsed.len(), 2);
+        for (parsed, original) in parsed.iter().zip(&targets) {
+            assert_eq!(parsed.extension_id, original.extension_id);
+            let ports: Vec<(&str, u16)> = parsed.hosts.iter().map(|h| (h.host.as_str(), h.port)).collect();
+            let expected: Vec<(&str, u16)> = original.hosts.iter().map(|h| (h.host.as_str(), h.port)).collect();
+            assert_eq!(ports, expected);
+        }
+    }
+
+    fn installed(id: &str) -> String {
+        let dir = installer::get_extensions_folder().join(id);
+        fs::create_dir_all(dir.join("CSXS")).unwrap();
+        fs::write(dir.join("CSXS/manifest.xml"), manifest(id, "1.0.0")).unwrap();
+        dir.to_string_lossy().to_string()
+    }
+
+    #[test]
+    fn suggestions_skip_ports_of_other_extensions() {
+        let _sandbox = Sandbox::new();
+        let first = installed("com.test.first");
+        let second = installed("com.test.second");
+        write_debug_file(&first, None).unwrap();
+
+        let config = get_debug_config(&second).unwrap();
+        assert!(!config.exists);
+        assert_eq!(config.extensions, vec![target("com.test.second.panel", &[("PHXS", 8089)])]);
+        // Its own file does not count against it
+        let written = write_debug_file(&first, None).unwrap();
+        assert_eq!(written.extensions, vec![target("com.test.first.panel", &[("PHXS", 8088)])]);
+        assert!(get_debug_config(&first).unwrap().exists);
+    }
+
+    #[test]
+    fn write_rejects_bad_ports_and_names() {
+        let _sandbox = Sandbox::new();
+        let first = installed("com.test.first");
+        let second = installed("com.test.second");
+        write_debug_file(&first, Some(vec![target("com.test.first.panel", &[("PHXS", 9000)])])).unwrap();
+
+        let attempt = |targets: Vec<DebugTarget>| write_debug_file(&second, Some(targets)).unwrap_err();
+        assert!(attempt(vec![target("com.test.second.panel", &[("PHXS", 80)])]).contains("reserved"));
+        assert!(attempt(vec![target("com.test.second.panel", &[("PHXS", 9000)])]).contains("already used"));
+        assert!(attempt(vec![target("com.test.second.panel", &[("PHXS", 9001), ("ILST", 9001)])]).contains("twice"));
+        assert!(attempt(vec![target("com.test\"/><x", &[("PHXS", 9002)])]).contains("Invalid extension id"));
+        assert!(!Path::new(&second).join(".debug").exists());
+
+        remove_debug_file(&first).unwrap();
+        remove_debug_file(&first).unwrap();
+        assert!(!Path::new(&first).join(".debug").exists());
+    }
+}
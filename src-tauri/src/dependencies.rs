@@ -0,0 +1,625 @@
+use crate::elevate::{self, PrivilegedOp};
+use crate::installer::{self, ExtensionInfo};
+use crate::sidecar::{self, Companion, CompanionKind};
+use crate::trash;
+use crate::version;
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::{Component, Path, PathBuf};
+use walkdir::WalkDir;
+
+/// Plug-in folders can be deep, but companions sit near the top
+const SEARCH_DEPTH: usize = 4;
+
+/// Host apps searched when a companion does not name its hosts
+pub const PRODUCTS: &[&str] = &[
+    "After Effects",
+    "Premiere Pro",
+    "Photoshop",
+    "Illustrator",
+    "InDesign",
+    "Audition",
+    "Animate",
+    "Character Animator",
+    "Media Encoder",
+];
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DependencyStatus {
+    pub kind: CompanionKind,
+    pub name: String,
+    pub optional: bool,
+    pub satisfied: bool,
+    /// Where the companion was found
+    pub found_at: Vec<String>,
+    /// Installed version of an extension companion
+    pub installed_version: Option<String>,
+    /// A copy ships with the extension and can be installed
+    pub installable: bool,
+    /// Why it is not satisfied, or what went wrong installing it
+    pub message: Option<String>,
+}
+
+/// Companion items an installed extension declares and whether they are present
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DependencyReport {
+    pub extension_id: String,
+    pub install_path: String,
+    pub items: Vec<DependencyStatus>,
+    /// Names of required companions that are missing
+    pub missing: Vec<String>,
+}
+
+fn display_name(companion: &Companion) -> String {
+    companion
+        .name
+        .clone()
+        .or_else(|| companion.file.clone())
+        .or_else(|| companion.id.clone())
+        .unwrap_or_else(|| "Unnamed companion".to_string())
+}
+
+/// Sidecar companions (in file order, so indexes stay stable) followed by
+/// `<DependencyList>` entries the sidecar does not already cover
+fn declared(dir: &Path, info: &ExtensionInfo) -> Vec<Companion> {
+    let mut companions = sidecar::load_sidecar(dir).map(|s| s.dependencies).unwrap_or_default();
+    for dep in &info.dependencies {
+        let covered = companions
+            .iter()
+            .any(|c| c.kind == CompanionKind::Extension && c.id.as_deref() == Some(dep.id.as_str()));
+        if !covered {
+            companions.push(Companion {
+                kind: CompanionKind::Extension,
+                name: None,
+                file: None,
+                id: Some(dep.id.clone()),
+                // The manifest's Version is a minimum
+                version: dep.version.as_ref().map(|v| format!(">={}", v)),
+                hosts: Vec::new(),
+                bundled: None,
+                optional: false,
+            });
+        }
+    }
+    companions
+}
+
+fn products(hosts: &[String]) -> Vec<String> {
+    if hosts.is_empty() {
+        PRODUCTS.iter().map(|p| p.to_string()).collect()
+    } else {
+        hosts.iter().map(|h| installer::format_host_name(h)).collect()
+    }
+}
+
+/// Where Adobe apps are installed
+fn apps_folder() -> Option<PathBuf> {
+    #[cfg(test)]
+    if let Some(home) = installer::test_home() {
+        return Some(home.join("apps"));
+    }
+    #[cfg(target_os = "windows")]
+    {
+        let program_files = std::env::var("ProgramFiles").unwrap_or_else(|_| "C:\\Program Files".to_string());
+        Some(PathBuf::from(program_files).join("Adobe"))
+    }
+    #[cfg(target_os = "macos")]
+    {
+        Some(PathBuf::from("/Applications"))
+    }
+    #[cfg(not(any(target_os = "windows", target_os = "macos")))]
+    {
+        None
+    }
+}
+
+/// Install folders of the given host apps, newest release first
+/// (e.g. "Adobe Photoshop 2025", "Adobe Photoshop 2024")
+pub fn app_dirs(products: &[String]) -> Vec<PathBuf> {
+    let Some(Ok(entries)) = apps_folder().map(fs::read_dir) else {
+        return Vec::new();
+    };
+    let mut dirs: Vec<PathBuf> = entries
+        .flatten()
+        .filter(|e| {
+            let name = e.file_name().to_string_lossy().to_string();
+            products.iter().any(|p| name.starts_with(&format!("Adobe {}", p)))
+        })
+        .map(|e| e.path())
+        .filter(|p| p.is_dir())
+        .collect();
+    dirs.sort_by(|a, b| b.cmp(a));
+    dirs
+}
+
+/// MediaCore folder shared by After Effects, Premiere Pro and Media Encoder
+pub fn shared_plugin_folder() -> Option<PathBuf> {
+    #[cfg(target_os = "windows")]
+    {
+        let program_files = std::env::var("ProgramFiles").unwrap_or_else(|_| "C:\\Program Files".to_string());
+        Some(PathBuf::from(program_files).join("Adobe\\Common\\Plug-ins\\7.0\\MediaCore"))
+    }
+    #[cfg(target_os = "macos")]
+    {
+        Some(PathBuf::from("/Library/Application Support/Adobe/Common/Plug-ins/7.0/MediaCore"))
+    }
+    #[cfg(not(any(target_os = "windows", target_os = "macos")))]
+    {
+        None
+    }
+}
+
+fn plugin_folders(products: &[String]) -> Vec<PathBuf> {
+    let mut folders: Vec<PathBuf> = shared_plugin_folder().into_iter().collect();
+    for app in app_dirs(products) {
+        folders.push(app.join("Plug-ins"));
+        folders.push(app.join("Support Files").join("Plug-ins"));
+    }
+    folders
+}
+
+/// `Startup Scripts CC` folders for the current user and for all users
+pub fn startup_script_roots() -> Vec<PathBuf> {
+    #[cfg(target_os = "windows")]
+    {
+        let mut roots = Vec::new();
+        if let Ok(appdata) = std::env::var("APPDATA") {
+            roots.push(PathBuf::from(appdata).join("Adobe\\Startup Scripts CC"));
+        }
+        let common = std::env::var("CommonProgramFiles").unwrap_or_else(|_| "C:\\Program Files\\Common Files".to_string());
+        roots.push(PathBuf::from(common).join("Adobe\\Startup Scripts CC"));
+        roots
+    }
+    #[cfg(target_os = "macos")]
+    {
+        let home = std::env::var("HOME").unwrap_or_else(|_| "~".to_string());
+        vec![
+            PathBuf::from(home).join("Library/Application Support/Adobe/Startup Scripts CC"),
+            PathBuf::from("/Library/Application Support/Adobe/Startup Scripts CC"),
+        ]
+    }
+    #[cfg(not(any(target_os = "windows", target_os = "macos")))]
+    {
+        Vec::new()
+    }
+}
+
+fn startup_script_folders(products: &[String]) -> Vec<PathBuf> {
+    let mut folders = startup_script_roots();
+    // After Effects runs startup scripts from its own install folder
+    for app in app_dirs(products) {
+        folders.push(app.join("Scripts").join("Startup"));
+        folders.push(app.join("Support Files").join("Scripts").join("Startup"));
+    }
+    folders
+}
+
+/// Per-user font folder first, then the system ones
+fn font_folders() -> Vec<PathBuf> {
+    #[cfg(target_os = "windows")]
+    {
+        let mut folders = Vec::new();
+        if let Ok(local) = std::env::var("LOCALAPPDATA") {
+            folders.push(PathBuf::from(local).join("Microsoft\\Windows\\Fonts"));
+        }
+        let windir = std::env::var("WINDIR").unwrap_or_else(|_| "C:\\Windows".to_string());
+        folders.push(PathBuf::from(windir).join("Fonts"));
+        folders
+    }
+    #[cfg(target_os = "macos")]
+    {
+        let home = std::env::var("HOME").unwrap_or_else(|_| "~".to_string());
+        vec![
+            PathBuf::from(home).join("Library/Fonts"),
+            PathBuf::from("/Library/Fonts"),
+            PathBuf::from("/System/Library/Fonts"),
+        ]
+    }
+    #[cfg(not(any(target_os = "windows", target_os = "macos")))]
+    {
+        let home = std::env::var("HOME").unwrap_or_else(|_| "~".to_string());
+        vec![
+            PathBuf::from(home).join(".local/share/fonts"),
+            PathBuf::from("/usr/local/share/fonts"),
+            PathBuf::from("/usr/share/fonts"),
+        ]
+    }
+}
+
+/// Entries named `file_name` (any letter case) in the given folders. Matches
+/// folders too, since macOS plug-ins are `.plugin` bundles.
+fn find_named(folders: &[PathBuf], file_name: &str) -> Vec<String> {
+    let mut found = Vec::new();
+    for folder in folders.iter().filter(|f| f.is_dir()) {
+        let mut walk = WalkDir::new(folder).max_depth(SEARCH_DEPTH).into_iter();
+        while let Some(Ok(entry)) = walk.next() {
+            if entry.file_name().to_string_lossy().eq_ignore_ascii_case(file_name) {
+                found.push(entry.path().to_string_lossy().to_string());
+                if entry.file_type().is_dir() {
+                    walk.skip_current_dir();
+                }
+            }
+        }
+    }
+    found
+}
+
+/// Installed folders holding extension `id` (bundle or panel id), with their versions
+fn find_extension(id: &str) -> Vec<(PathBuf, String)> {
+    installer::get_all_extension_folders()
+        .iter()
+        .filter_map(|root| fs::read_dir(root).ok())
+        .flat_map(|entries| entries.flatten().map(|e| e.path()))
+        .filter(|dir| installer::manifest_extension_ids(dir).iter().any(|i| i == id))
+        .filter_map(|dir| installer::read_manifest(&dir).map(|info| (dir, info.version)))
+        .collect()
+}
+
+fn check(companion: &Companion) -> DependencyStatus {
+    let mut status = DependencyStatus {
+        kind: companion.kind,
+        name: display_name(companion),
+        optional: companion.optional,
+        satisfied: false,
+        found_at: Vec::new(),
+        installed_version: None,
+        installable: companion.bundled.is_some(),
+        message: None,
+    };
+    let products = products(&companion.hosts);
+
+    match companion.kind {
+        CompanionKind::Extension => {
+            let Some(id) = companion.id.as_deref() else {
+                status.message = Some("No extension id given".to_string());
+                return status;
+            };
+            let found = find_extension(id);
+            status.found_at = found.iter().map(|(dir, _)| dir.to_string_lossy().to_string()).collect();
+            status.installed_version = found.iter().map(|(_, v)| v.clone()).max_by(|a, b| version::compare_versions(a, b));
+            let constraint = companion.version.as_deref().unwrap_or("*");
+            status.satisfied = match &status.installed_version {
+                Some(installed) => version::satisfies(installed, constraint).unwrap_or(false),
+                None => false,
+            };
+            if !status.satisfied {
+                status.message = Some(match &status.installed_version {
+                    Some(installed) => format!("Version {} installed, {} required", installed, constraint),
+                    None => "Not installed".to_string(),
+                });
+            }
+        }
+        kind => {
+            let Some(file) = companion.file.as_deref() else {
+                status.message = Some("No file name given".to_string());
+                return status;
+            };
+            let folders = match kind {
+                CompanionKind::HostPlugin => plugin_folders(&products),
+                CompanionKind::StartupScript => startup_script_folders(&products),
+                _ => font_folders(),
+            };
+            status.found_at = find_named(&folders, file);
+            status.satisfied = !status.found_at.is_empty();
+            if !status.satisfied {
+                status.message = Some(format!("{} not found", file));
+            }
+        }
+    }
+    status
+}
+
+fn report(dir: &Path, info: &ExtensionInfo) -> DependencyReport {
+    let items: Vec<DependencyStatus> = declared(dir, info).iter().map(check).collect();
+    DependencyReport {
+        extension_id: info.id.clone(),
+        install_path: dir.to_string_lossy().to_string(),
+        missing: items
+            .iter()
+            .filter(|i| !i.satisfied && !i.optional)
+            .map(|i| i.name.clone())
+            .collect(),
+        items,
+    }
+}
+
+fn open_extension(install_path: &str) -> Result<(PathBuf, ExtensionInfo), String> {
+    let dir = PathBuf::from(install_path);
+    let info = installer::read_manifest(&dir).ok_or_else(|| format!("No extension manifest in {}", install_path))?;
+    Ok((dir, info))
+}
+
+/// Check which companions an installed extension declares and which are present
+pub fn check_dependencies(install_path: &str) -> Result<DependencyReport, String> {
+    let (dir, info) = open_extension(install_path)?;
+    Ok(report(&dir, &info))
+}
+
+/// Names of required companions that are missing, for install messages.
+/// Extensions without declared companions are not searched for.
+pub fn missing_companions(dir: &Path, info: &ExtensionInfo) -> Vec<String> {
+    if declared(dir, info).is_empty() {
+        return Vec::new();
+    }
+    report(dir, info).missing
+}
+
+/// Per-user folder a host runs startup scripts from; After Effects uses its own
+/// install folder (newest release)
+pub fn startup_script_folder(product: &str) -> Option<PathBuf> {
+    if product == "After Effects" {
+        return app_dirs(&[product.to_string()])
+            .first()
+            .map(|app| app.join("Scripts").join("Startup"));
+    }
+    let folder = if product == "Photoshop" { "Adobe Photoshop" } else { product };
+    startup_script_roots().first().map(|root| root.join(folder))
+}
+
+/// Folder a bundled companion of this kind is installed into
+fn destination(companion: &Companion) -> Option<PathBuf> {
+    let products = products(&companion.hosts);
+    match companion.kind {
+        CompanionKind::HostPlugin => {
+            let file = companion.file.as_deref()?.to_lowercase();
+            // After Effects and Premiere plug-ins load from the shared MediaCore folder;
+            // macOS .plugin bundles go there too unless they are for Photoshop or Illustrator
+            let shared = file.ends_with(".aex")
+                || file.ends_with(".prm")
+                || (file.ends_with(".plugin") && !products.iter().all(|p| p == "Photoshop" || p == "Illustrator"));
+            if shared {
+                shared_plugin_folder()
+            } else {
+                app_dirs(&products).first().map(|app| app.join("Plug-ins"))
+            }
+        }
+        CompanionKind::StartupScript => startup_script_folder(products.first()?),
+        CompanionKind::Font => font_folders().into_iter().next(),
+        CompanionKind::Extension => None,
+    }
+}
+
+/// Resolve a `bundled` path inside the extension folder, refusing anything that climbs out
+fn bundled_source(dir: &Path, rel: &str) -> Result<PathBuf, String> {
+    let rel = Path::new(rel);
+    if !rel.components().all(|c| matches!(c, Component::Normal(_) | Component::CurDir)) {
+        return Err(format!("Bundled path '{}' is outside the extension", rel.display()));
+    }
+    let source = dir.join(rel);
+    if !source.exists() {
+        return Err(format!("Bundled file '{}' is missing", rel.display()));
+    }
+    Ok(source)
+}
+
+/// A companion's `file` is a single name inside its destination folder
+fn plain_file_name(name: &str) -> Result<&str, String> {
+    let mut components = Path::new(name).components();
+    match (components.next(), components.next()) {
+        (Some(Component::Normal(_)), None) => Ok(name),
+        _ => Err(format!("Companion file name '{}' is not a plain file name", name)),
+    }
+}
+
+/// Checks the elevated helper makes before writing: the source stays inside the
+/// extension and the target, wherever links point, stays inside `folder`
+fn check_privileged_copy(dir: &Path, source: &Path, folder: &Path, target: &Path) -> Result<(), String> {
+    let canonical_dir = fs::canonicalize(dir).map_err(|e| format!("Cannot resolve {}: {}", dir.display(), e))?;
+    let canonical_source = fs::canonicalize(source).map_err(|e| format!("Cannot resolve {}: {}", source.display(), e))?;
+    if !canonical_source.starts_with(&canonical_dir) || elevate::has_symlink_component(source) {
+        return Err(format!("Refusing to copy {}", source.display()));
+    }
+    if elevate::has_symlink_component(target) {
+        return Err(format!("Refusing to write through a link at {}", target.display()));
+    }
+    let canonical_folder = fs::canonicalize(folder).map_err(|e| format!("Cannot resolve {}: {}", folder.display(), e))?;
+    let inside = match fs::canonicalize(target) {
+        Ok(existing) => existing.starts_with(&canonical_folder),
+        // Not there yet: its parent is `folder` itself
+        Err(_) => target.parent().and_then(|p| fs::canonicalize(p).ok()).as_deref() == Some(canonical_folder.as_path()),
+    };
+    // An existing bundle folder must not hide links that `copy` would write through
+    let linked_inside = target.is_dir()
+        && WalkDir::new(target)
+            .into_iter()
+            .flatten()
+            .any(|e| e.path_is_symlink());
+    if !inside || linked_inside {
+        return Err(format!("Refusing to write {} outside {}", target.display(), folder.display()));
+    }
+    Ok(())
+}
+
+/// Let Windows know about a font copied into the per-user font folder
+#[cfg(target_os = "windows")]
+fn register_font(path: &Path) -> Result<(), String> {
+    use winreg::enums::HKEY_CURRENT_USER;
+    use winreg::RegKey;
+    let (key, _) = RegKey::predef(HKEY_CURRENT_USER)
+        .create_subkey("Software\\Microsoft\\Windows NT\\CurrentVersion\\Fonts")
+        .map_err(|e| format!("Cannot open font registry: {}", e))?;
+    let stem = path.file_stem().unwrap_or_default().to_string_lossy().to_string();
+    key.set_value(format!("{} (TrueType)", stem), &path.to_string_lossy().to_string())
+        .map_err(|e| format!("Cannot register font: {}", e))
+}
+
+#[cfg(not(target_os = "windows"))]
+fn register_font(_path: &Path) -> Result<(), String> {
+    Ok(())
+}
+
+/// Copy one bundled companion into place. Only elevates when `allow_elevation`
+/// is set, so the elevated helper never re-launches itself.
+fn install_one(dir: &Path, index: usize, companion: &Companion, allow_elevation: bool) -> Result<(), String> {
+    let rel = companion.bundled.as_deref().ok_or("Nothing bundled to install")?;
+    let source = bundled_source(dir, rel)?;
+
+    if companion.kind == CompanionKind::Extension {
+        let result = installer::install_extension(&source.to_string_lossy());
+        return if result.success { Ok(()) } else { Err(result.message) };
+    }
+
+    let folder = destination(companion).ok_or("No install location for this companion on this system")?;
+    let file_name = companion
+        .file
+        .clone()
+        .or_else(|| source.file_name().map(|n| n.to_string_lossy().to_string()))
+        .ok_or("No file name given")?;
+    let target = folder.join(plain_file_name(&file_name)?);
+
+    // In the elevated helper the sidecar is untrusted input
+    if !allow_elevation {
+        fs::create_dir_all(&folder).map_err(|e| format!("Cannot create {}: {}", folder.display(), e))?;
+        check_privileged_copy(dir, &source, &folder, &target)?;
+    }
+
+    let copied = fs::create_dir_all(&folder).and_then(|_| {
+        if source.is_dir() {
+            trash::copy_dir(&source, &target)
+        } else {
+            fs::copy(&source, &target).map(|_| ())
+        }
+    });
+    match copied {
+        Ok(()) if companion.kind == CompanionKind::Font => register_font(&target),
+        Ok(()) => Ok(()),
+        Err(e) if e.kind() == std::io::ErrorKind::PermissionDenied && allow_elevation => {
+            let result = elevate::run_elevated(&PrivilegedOp::InstallCompanion {
+                extension_dir: dir.to_string_lossy().to_string(),
+                index,
+            });
+            if result.success {
+                Ok(())
+            } else {
+                Err(result.message)
+            }
+        }
+        Err(e) => Err(format!("Cannot copy {} to {}: {}", rel, folder.display(), e)),
+    }
+}
+
+/// Install the sidecar companion at `index` without elevating (used by the elevated helper)
+pub fn install_bundled(dir: &Path, index: usize) -> Result<(), String> {
+    let companion = sidecar::load_sidecar(dir)
+        .and_then(|s| s.dependencies.into_iter().nth(index))
+        .ok_or_else(|| format!("No companion #{} in {}", index, dir.display()))?;
+    if companion.kind == CompanionKind::Extension {
+        return Err("Extension companions are installed per user".to_string());
+    }
+    install_one(dir, index, &companion, false)
+}
+
+/// Install every missing companion the extension ships a copy of, then re-check
+pub fn install_companions(install_path: &str) -> Result<DependencyReport, String> {
+    let (dir, info) = open_extension(install_path)?;
+    let companions = declared(&dir, &info);
+    let mut errors = Vec::new();
+    for (index, companion) in companions.iter().enumerate() {
+        if companion.bundled.is_none() || check(companion).satisfied {
+            continue;
+        }
+        if let Err(e) = install_one(&dir, index, companion, true) {
+            log::warn!("Cannot install companion '{}': {}", display_name(companion), e);
+            errors.push((index, e));
+        }
+    }
+
+    let mut report = report(&dir, &info);
+    for (index, e) in errors {
+        if let Some(item) = report.items.get_mut(index) {
+            item.message = Some(format!("Install failed: {}", e));
+        }
+    }
+    Ok(report)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::{manifest, write_zxp, Sandbox};
+
+    /// An installed extension with the given sidecar companions
+    fn installed_with(companions: serde_json::Value) -> PathBuf {
+        let dir = installer::get_extensions_folder().join("com.test.needs");
+        fs::create_dir_all(dir.join("CSXS")).unwrap();
+        fs::write(dir.join("CSXS/manifest.xml"), manifest("com.test.needs", "1.0.0")).unwrap();
+        let sidecar = serde_json::json!({ "dependencies": companions });
+        fs::write(dir.join(sidecar::SIDECAR_FILE), sidecar.to_string()).unwrap();
+        dir
+    }
+
+    #[test]
+    fn extension_companions_check_the_installed_version() {
+        let sandbox = Sandbox::new();
+        let zxp = sandbox.path("in/base.zxp");
+        write_zxp(&zxp, "com.test.base", "1.5.0", &[]);
+        assert!(installer::install_extension(&zxp.to_string_lossy()).success);
+        let dir = installed_with(serde_json::json!([
+            { "kind": "extension", "id": "com.test.base", "version": ">=1.0" },
+            { "kind": "extension", "id": "com.test.base.panel", "version": ">=2.0" },
+            { "kind": "extension", "id": "com.test.absent", "optional": true },
+        ]));
+
+        let report = check_dependencies(&dir.to_string_lossy()).unwrap();
+        assert!(report.items[0].satisfied);
+        assert_eq!(report.items[0].installed_version.as_deref(), Some("1.5.0"));
+        // Panel ids count as the bundle they belong to
+        assert!(!report.items[1].satisfied);
+        assert_eq!(report.items[1].message.as_deref(), Some("Version 1.5.0 installed, >=2.0 required"));
+        assert!(!report.items[2].satisfied);
+        assert_eq!(report.missing, vec!["com.test.base.panel".to_string()]);
+    }
+
+    #[test]
+    fn bundled_plugins_are_installed_into_the_host_app() {
+        let sandbox = Sandbox::new();
+        let app = sandbox.path("apps/Adobe Photoshop 2025");
+        fs::create_dir_all(&app).unwrap();
+        let dir = installed_with(serde_json::json!([
+            { "kind": "host_plugin", "file": "Tool.8bf", "hosts": ["PHXS"], "bundled": "plugins/Tool.8bf" },
+        ]));
+        fs::create_dir_all(dir.join("plugins")).unwrap();
+        fs::write(dir.join("plugins/Tool.8bf"), "plugin").unwrap();
+
+        let before = check_dependencies(&dir.to_string_lossy()).unwrap();
+        assert_eq!(before.missing, vec!["Tool.8bf".to_string()]);
+        assert!(before.items[0].installable);
+
+        let after = install_companions(&dir.to_string_lossy()).unwrap();
+        assert!(after.missing.is_empty());
+        assert!(app.join("Plug-ins/Tool.8bf").is_file());
+    }
+
+    #[test]
+    fn companion_paths_stay_where_they_belong() {
+        let sandbox = Sandbox::new();
+        assert!(plain_file_name("Tool.8bf").is_ok());
+        assert!(plain_file_name("../Tool.8bf").is_err());
+        assert!(plain_file_name("sub/Tool.8bf").is_err());
+
+        let dir = sandbox.path("ext");
+        fs::create_dir_all(dir.join("plugins")).unwrap();
+        fs::write(dir.join("plugins/Tool.8bf"), "plugin").unwrap();
+        assert!(bundled_source(&dir, "./plugins/Tool.8bf").is_ok());
+        assert!(bundled_source(&dir, "../ext/plugins/Tool.8bf").is_err());
+        assert!(bundled_source(&dir, "plugins/Missing.8bf").is_err());
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn privileged_copies_refuse_links() {
+        let sandbox = Sandbox::new();
+        let dir = sandbox.path("ext");
+        let folder = sandbox.path("Plug-ins");
+        fs::create_dir_all(&dir).unwrap();
+        fs::create_dir_all(&folder).unwrap();
+        fs::write(dir.join("Tool.8bf"), "plugin").unwrap();
+        fs::write(sandbox.path("secret"), "secret").unwrap();
+        std::os::unix::fs::symlink(sandbox.path("secret"), dir.join("Linked.8bf")).unwrap();
+        std::os::unix::fs::symlink(sandbox.path("secret"), folder.join("Existing.8bf")).unwrap();
+
+        assert!(check_privileged_copy(&dir, &dir.join("Tool.8bf"), &folder, &folder.join("Tool.8bf")).is_ok());
+        assert!(check_privileged_copy(&dir, &dir.join("Linked.8bf"), &folder, &folder.join("Linked.8bf")).is_err());
+        assert!(check_privileged_copy(&dir, &dir.join("Tool.8bf"), &folder, &folder.join("Existing.8bf")).is_err());
+    }
+}
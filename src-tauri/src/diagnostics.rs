@@ -0,0 +1,548 @@
+use crate::dependencies;
+use crate::installer::{self, ExtensionInfo};
+use crate::support;
+use crate::version;
+use quick_xml::events::Event;
+use quick_xml::Reader;
+use serde::{Deserialize, Serialize};
+use std::cmp::Ordering;
+use std::fs;
+use std::path::{Path, PathBuf};
+use walkdir::WalkDir;
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CheckStatus {
+    Pass,
+    Warning,
+    Fail,
+    /// Not applicable here, or an earlier failure makes it meaningless
+    Skipped,
+}
+
+/// One line of the "why isn't my panel showing" checklist
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DiagnosticCheck {
+    /// Stable key, e.g. "signature"
+    pub id: String,
+    pub title: String,
+    pub status: CheckStatus,
+    pub detail: String,
+    /// What to do about a warning or failure
+    pub fix: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DiagnosticsReport {
+    pub extension_id: String,
+    pub install_path: String,
+    /// In the order CEP gets to them when loading a panel
+    pub checks: Vec<DiagnosticCheck>,
+    /// Title of the first failed check
+    pub likely_cause: Option<String>,
+}
+
+fn check(id: &str, title: &str, status: CheckStatus, detail: String, fix: Option<&str>) -> DiagnosticCheck {
+    DiagnosticCheck {
+        id: id.to_string(),
+        title: title.to_string(),
+        status,
+        detail,
+        fix: fix.map(str::to_string),
+    }
+}
+
+/// CEP silently skips manifests that are not well-formed XML
+fn check_manifest(dir: &Path) -> (DiagnosticCheck, Option<(ExtensionInfo, String)>) {
+    const TITLE: &str = "Manifest is valid";
+    let path = dir.join("CSXS").join("manifest.xml");
+    let content = match fs::read_to_string(&path) {
+        Ok(content) => content,
+        Err(e) => {
+            let detail = format!("Cannot read {}: {}", path.display(), e);
+            return (check("manifest", TITLE, CheckStatus::Fail, detail, Some("Reinstall the extension")), None);
+        }
+    };
+
+    let mut reader = Reader::from_str(&content);
+    loop {
+        match reader.read_event() {
+            Ok(Event::Eof) => break,
+            Ok(_) => {}
+            Err(e) => {
+                let detail = format!("manifest.xml is not well-formed at byte {}: {}", reader.buffer_position(), e);
+                let fix = Some("Ask the developer for a fixed package");
+                return (check("manifest", TITLE, CheckStatus::Fail, detail, fix), None);
+            }
+        }
+    }
+
+    let Some(info) = installer::read_manifest(dir) else {
+        let detail = "The manifest has no extension id".to_string();
+        return (check("manifest", TITLE, CheckStatus::Fail, detail, Some("Ask the developer for a fixed package")), None);
+    };
+    if info.host_list.is_empty() {
+        let detail = "The manifest does not list any host app".to_string();
+        return (check("manifest", TITLE, CheckStatus::Fail, detail, Some("Ask the developer for a fixed package")), None);
+    }
+    let detail = format!("{} {}, CEP {}", info.id, info.version, if info.cep_version.is_empty() { "?" } else { &info.cep_version });
+    (check("manifest", TITLE, CheckStatus::Pass, detail, None), Some((info, content)))
+}
+
+/// `(code, Version)` of every `<Host>` in a manifest
+fn manifest_hosts(manifest: &str) -> Vec<(String, String)> {
+    manifest
+        .lines()
+        .filter(|line| line.contains("<Host "))
+        .filter_map(|line| {
+            let code = installer::extract_attr(line, "Name")?;
+            Some((code, installer::extract_attr(line, "Version").unwrap_or_default()))
+        })
+        .collect()
+}
+
+/// Installed releases of the manifest's host apps, as (product, app folder)
+fn detected_hosts(manifest: &str) -> Vec<(String, PathBuf)> {
+    let mut detected = Vec::new();
+    for (code, _) in manifest_hosts(manifest) {
+        let product = installer::format_host_name(&code);
+        for dir in dependencies::app_dirs(std::slice::from_ref(&product)) {
+            if !detected.iter().any(|(_, d)| d == &dir) {
+                detected.push((product.clone(), dir));
+            }
+        }
+    }
+    detected
+}
+
+/// Hosts only load unsigned extensions with PlayerDebugMode on for the CSXS
+/// version they run, so check the versions of the installed host releases
+fn check_signature(dir: &Path, manifest: &str) -> DiagnosticCheck {
+    const TITLE: &str = "Signature or debug mode";
+    if dir.join("META-INF").join("signatures.xml").is_file() {
+        return check("signature", TITLE, CheckStatus::Pass, "The extension is signed".to_string(), None);
+    }
+    const HINT: Option<&str> = Some("Turn on Debug Mode, then restart the host app");
+
+    let releases: Vec<(String, &str)> = detected_hosts(manifest)
+        .iter()
+        .filter_map(|(_, app)| {
+            let release = app.file_name()?.to_string_lossy().to_string();
+            Some((release, csxs_version(app)?))
+        })
+        .collect();
+    if releases.is_empty() {
+        // Host releases unknown — any enabled CSXS version may be the right one
+        return if installer::get_debug_mode() {
+            check(
+                "signature",
+                TITLE,
+                CheckStatus::Pass,
+                "Unsigned, loaded because PlayerDebugMode is on".to_string(),
+                None,
+            )
+        } else {
+            check(
+                "signature",
+                TITLE,
+                CheckStatus::Fail,
+                "The extension folder has no signature and PlayerDebugMode is off".to_string(),
+                HINT,
+            )
+        };
+    }
+
+    let enabled: Vec<String> = support::csxs_settings()
+        .into_iter()
+        .filter(|s| s.player_debug_mode.as_deref() == Some("1"))
+        .map(|s| s.version)
+        .collect();
+    let (on, off): (Vec<_>, Vec<_>) = releases.iter().partition(|(_, version)| enabled.iter().any(|e| e == version));
+    let describe = |releases: &[&(String, &str)]| {
+        releases
+            .iter()
+            .map(|(release, version)| format!("{} (CSXS.{})", release, version))
+            .collect::<Vec<_>>()
+            .join(", ")
+    };
+    if off.is_empty() {
+        check(
+            "signature",
+            TITLE,
+            CheckStatus::Pass,
+            format!("Unsigned, loaded because PlayerDebugMode is on for {}", describe(&on)),
+            None,
+        )
+    } else if on.is_empty() {
+        check(
+            "signature",
+            TITLE,
+            CheckStatus::Fail,
+            format!("The extension folder has no signature and PlayerDebugMode is off for {}", describe(&off)),
+            HINT,
+        )
+    } else {
+        check(
+            "signature",
+            TITLE,
+            CheckStatus::Warning,
+            format!(
+                "Unsigned: loads in {}, but PlayerDebugMode is off for {}",
+                describe(&on),
+                describe(&off)
+            ),
+            HINT,
+        )
+    }
+}
+
+/// Release year from an app folder name ("Adobe Photoshop 2025" → 2025)
+fn release_year(folder: &Path) -> Option<u32> {
+    let name = folder.file_name()?.to_string_lossy().to_string();
+    name.split_whitespace().find_map(|word| word.parse().ok().filter(|y| *y >= 2013))
+}
+
+/// Major version of a host release from its folder name ("Adobe Photoshop 2025" → 26)
+fn release_version(product: &str, folder: &Path) -> Option<u32> {
+    let offset = match product {
+        "Photoshop" => 1999,
+        "Illustrator" => 1996,
+        "InDesign" => 2005,
+        "Bridge" => 2010,
+        _ => 2000,
+    };
+    release_year(folder)?.checked_sub(offset)
+}
+
+/// CSXS version a host release runs (its debug-mode settings key), from its release year
+fn csxs_version(folder: &Path) -> Option<&'static str> {
+    Some(match release_year(folder)? {
+        2025.. => "12",
+        2021..=2024 => "11",
+        2020 => "10",
+        2019 => "9",
+        2018 => "8",
+        _ => "7",
+    })
+}
+
+/// `Version` of a `<Host>`: "[20.0,99.9]" is a range, "20.0" a minimum
+fn host_range(spec: &str) -> (Option<String>, Option<String>) {
+    let spec = spec.trim();
+    if spec.is_empty() || spec.eq_ignore_ascii_case("all") {
+        return (None, None);
+    }
+    let inner = spec.trim_start_matches(['[', '(']).trim_end_matches([']', ')']);
+    let mut parts = inner.split(',').map(str::trim).filter(|p| !p.is_empty());
+    let min = parts.next().map(str::to_string);
+    let max = if inner.contains(',') { parts.next().map(str::to_string) } else { None };
+    (min, max)
+}
+
+fn in_range(major: u32, (min, max): &(Option<String>, Option<String>)) -> bool {
+    let major = major.to_string();
+    // Only the major version is known, so compare against the bounds' majors
+    let major_of = |v: &String| v.split('.').next().unwrap_or_default().to_string();
+    min.as_ref().map(|m| version::compare_versions(&major, &major_of(m)) != Ordering::Less).unwrap_or(true)
+        && max.as_ref().map(|m| version::compare_versions(&major, &major_of(m)) != Ordering::Greater).unwrap_or(true)
+}
+
+fn check_hosts(manifest: &str) -> DiagnosticCheck {
+    const TITLE: &str = "Compatible host app installed";
+    let hosts = manifest_hosts(manifest);
+
+    let mut compatible = Vec::new();
+    let mut incompatible = Vec::new();
+    let mut detected_any = false;
+    for (code, spec) in &hosts {
+        let product = installer::format_host_name(code);
+        let range = host_range(spec);
+        for dir in dependencies::app_dirs(std::slice::from_ref(&product)) {
+            detected_any = true;
+            let release = dir.file_name().unwrap_or_default().to_string_lossy().to_string();
+            match release_version(&product, &dir) {
+                Some(major) if !in_range(major, &range) => incompatible.push(format!("{} ({}, needs {})", release, major, spec)),
+                _ => compatible.push(release),
+            }
+        }
+    }
+    compatible.sort();
+    compatible.dedup();
+    incompatible.dedup();
+
+    if !compatible.is_empty() {
+        check("hosts", TITLE, CheckStatus::Pass, format!("Loads in {}", compatible.join(", ")), None)
+    } else if detected_any {
+        check(
+            "hosts",
+            TITLE,
+            CheckStatus::Fail,
+            format!("No installed release is in the supported range: {}", incompatible.join(", ")),
+            Some("Install a supported version of the host app or get an updated extension"),
+        )
+    } else if dependencies::app_dirs(&dependencies::PRODUCTS.iter().map(|p| p.to_string()).collect::<Vec<_>>()).is_empty() {
+        check("hosts", TITLE, CheckStatus::Skipped, "Cannot detect installed host apps here".to_string(), None)
+    } else {
+        let wanted: Vec<String> = hosts.iter().map(|(code, _)| installer::format_host_name(code)).collect();
+        check(
+            "hosts",
+            TITLE,
+            CheckStatus::Warning,
+            format!("None of its host apps is installed ({})", wanted.join(", ")),
+            Some("The panel only appears in the apps listed in its manifest"),
+        )
+    }
+}
+
+/// The file a local MainPath points at, without its `?query` or `#hash`;
+/// None for a remote page (`https://..`), which has no file to check
+fn main_path_file(main_path: &str) -> Option<&str> {
+    if main_path.contains("://") {
+        return None;
+    }
+    let end = main_path.find(['?', '#']).unwrap_or(main_path.len());
+    Some(main_path[..end].trim_start_matches("./"))
+}
+
+fn check_main_paths(dir: &Path, manifest: &str) -> DiagnosticCheck {
+    const TITLE: &str = "Panel entry files exist";
+    let main_paths: Vec<String> = manifest
+        .lines()
+        .filter(|line| line.contains("<MainPath>"))
+        .map(|line| installer::extract_text(line, "MainPath"))
+        .filter(|p| !p.is_empty())
+        .collect();
+    if main_paths.is_empty() {
+        return check("main_path", TITLE, CheckStatus::Warning, "The manifest has no <MainPath>".to_string(), None);
+    }
+    if main_paths.iter().all(|p| main_path_file(p).is_none()) {
+        return check(
+            "main_path",
+            TITLE,
+            CheckStatus::Skipped,
+            format!("Loads remote content: {}", main_paths.join(", ")),
+            None,
+        );
+    }
+    let missing: Vec<&String> = main_paths
+        .iter()
+        .filter(|p| main_path_file(p).is_some_and(|file| !dir.join(file).is_file()))
+        .collect();
+    if missing.is_empty() {
+        check("main_path", TITLE, CheckStatus::Pass, main_paths.join(", "), None)
+    } else {
+        check(
+            "main_path",
+            TITLE,
+            CheckStatus::Fail,
+            format!("Missing: {}", missing.iter().map(|p| p.as_str()).collect::<Vec<_>>().join(", ")),
+            Some("Verify or repair the extension, or reinstall it"),
+        )
+    }
+}
+
+/// The same id in another extensions folder hides one of the copies
+fn check_duplicates(dir: &Path, info: &ExtensionInfo) -> DiagnosticCheck {
+    const TITLE: &str = "No duplicate installs";
+    let own = fs::canonicalize(dir).unwrap_or_else(|_| dir.to_path_buf());
+    let others: Vec<PathBuf> = installer::get_all_extension_folders()
+        .iter()
+        .flat_map(|root| installer::find_installed_dirs(root, &info.id))
+        .filter(|other| fs::canonicalize(other).map(|o| o != own).unwrap_or(true))
+        .collect();
+    if others.is_empty() {
+        return check("duplicates", TITLE, CheckStatus::Pass, "Installed once".to_string(), None);
+    }
+    let versions: Vec<String> = others
+        .iter()
+        .map(|other| {
+            let version = installer::read_manifest(other).map(|i| i.version).unwrap_or_default();
+            format!("{} ({})", other.display(), version)
+        })
+        .collect();
+    check(
+        "duplicates",
+        TITLE,
+        CheckStatus::Warning,
+        format!("Also installed at {}", versions.join(", ")),
+        Some("Remove the copies you don't use; the host may load an older one"),
+    )
+}
+
+fn check_permissions(dir: &Path) -> DiagnosticCheck {
+    const TITLE: &str = "Files are readable";
+    let unreadable: Vec<String> = WalkDir::new(dir)
+        .into_iter()
+        .filter_map(|entry| match entry {
+            Ok(entry) if entry.file_type().is_file() => {
+                fs::File::open(entry.path()).err().map(|_| entry.path().display().to_string())
+            }
+            Ok(_) => None,
+            Err(e) => Some(e.path().map(|p| p.display().to_string()).unwrap_or_else(|| e.to_string())),
+        })
+        .collect();
+    if unreadable.is_empty() {
+        return check("permissions", TITLE, CheckStatus::Pass, "All files can be read".to_string(), None);
+    }
+    let shown: Vec<&str> = unreadable.iter().take(5).map(String::as_str).collect();
+    check(
+        "permissions",
+        TITLE,
+        CheckStatus::Fail,
+        format!("{} item(s) cannot be read: {}", unreadable.len(), shown.join(", ")),
+        Some("Fix the folder's permissions or reinstall the extension"),
+    )
+}
+
+/// Files still carrying the downloaded-from-the-internet mark
+fn quarantined_files(dir: &Path) -> Option<Vec<String>> {
+    #[cfg(target_os = "macos")]
+    {
+        // Lines look like "<path>: com.apple.quarantine: 0081;…"
+        let output = std::process::Command::new("xattr").arg("-rl").arg(dir).output().ok()?;
+        let mut files: Vec<String> = String::from_utf8_lossy(&output.stdout)
+            .lines()
+            .filter_map(|line| line.split_once(": com.apple.quarantine").map(|(path, _)| path.to_string()))
+            .collect();
+        files.dedup();
+        Some(files)
+    }
+    #[cfg(target_os = "windows")]
+    {
+        Some(
+            WalkDir::new(dir)
+                .into_iter()
+                .flatten()
+                .filter(|e| e.file_type().is_file())
+                .filter(|e| fs::metadata(format!("{}:Zone.Identifier", e.path().display())).is_ok())
+                .map(|e| e.path().display().to_string())
+                .collect(),
+        )
+    }
+    #[cfg(not(any(target_os = "macos", target_os = "windows")))]
+    {
+        let _ = dir;
+        None
+    }
+}
+
+fn check_quarantine(dir: &Path) -> DiagnosticCheck {
+    const TITLE: &str = "Not quarantined";
+    match quarantined_files(dir) {
+        None => check("quarantine", TITLE, CheckStatus::Skipped, "Not applicable on this platform".to_string(), None),
+        Some(files) if files.is_empty() => {
+            check("quarantine", TITLE, CheckStatus::Pass, "No file is marked as downloaded".to_string(), None)
+        }
+        Some(files) => check(
+            "quarantine",
+            TITLE,
+            CheckStatus::Warning,
+            format!("{} file(s) are marked as downloaded, e.g. {}", files.len(), files[0]),
+            Some("Helper binaries may be blocked; reinstall the extension with ZXP Magic"),
+        ),
+    }
+}
+
+/// Checklist of the usual reasons a panel is missing from Window > Extensions
+pub fn diagnose_extension(install_path: &str) -> Result<DiagnosticsReport, String> {
+    let dir = PathBuf::from(install_path);
+    if !dir.is_dir() {
+        return Err(format!("Extension folder not found: {}", install_path));
+    }
+
+    let (manifest_check, parsed) = check_manifest(&dir);
+    let mut checks = vec![manifest_check];
+    let mut extension_id = dir.file_name().unwrap_or_default().to_string_lossy().to_string();
+    match &parsed {
+        Some((info, manifest)) => {
+            extension_id = info.id.clone();
+            checks.push(check_signature(&dir, manifest));
+            checks.push(check_hosts(manifest));
+            checks.push(check_main_paths(&dir, manifest));
+            checks.push(check_duplicates(&dir, info));
+        }
+        None => {
+            for (id, title) in [
+                ("signature", "Signature or debug mode"),
+                ("hosts", "Compatible host app installed"),
+                ("main_path", "Panel entry files exist"),
+                ("duplicates", "No duplicate installs"),
+            ] {
+                checks.push(check(id, title, CheckStatus::Skipped, "Needs a valid manifest".to_string(), None));
+            }
+        }
+    }
+    checks.push(check_permissions(&dir));
+    checks.push(check_quarantine(&dir));
+
+    let likely_cause = checks
+        .iter()
+        .find(|c| c.status == CheckStatus::Fail)
+        .map(|c| c.title.clone());
+    Ok(DiagnosticsReport {
+        extension_id,
+        install_path: install_path.to_string(),
+        checks,
+        likely_cause,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn range(min: Option<&str>, max: Option<&str>) -> (Option<String>, Option<String>) {
+        (min.map(str::to_string), max.map(str::to_string))
+    }
+
+    #[test]
+    fn host_range_forms() {
+        assert_eq!(host_range("[20.0,99.9]"), range(Some("20.0"), Some("99.9")));
+        assert_eq!(host_range("(20.0, 25.9)"), range(Some("20.0"), Some("25.9")));
+        assert_eq!(host_range("20.0"), range(Some("20.0"), None));
+        assert_eq!(host_range("[20.0,]"), range(Some("20.0"), None));
+        assert_eq!(host_range("ALL"), range(None, None));
+        assert_eq!(host_range(""), range(None, None));
+    }
+
+    #[test]
+    fn in_range_compares_majors() {
+        let bounded = host_range("[22.0,25.9]");
+        assert!(in_range(22, &bounded));
+        assert!(in_range(25, &bounded));
+        assert!(!in_range(21, &bounded));
+        assert!(!in_range(26, &bounded));
+        assert!(in_range(26, &host_range("22.0")));
+        assert!(in_range(1, &host_range("")));
+    }
+
+    #[test]
+    fn release_versions_from_folder_names() {
+        assert_eq!(release_version("Photoshop", Path::new("/Apps/Adobe Photoshop 2025")), Some(26));
+        assert_eq!(release_version("Bridge", Path::new("/Apps/Adobe Bridge 2025")), Some(15));
+        assert_eq!(release_version("After Effects", Path::new("/Apps/Adobe After Effects 2024")), Some(24));
+        assert_eq!(csxs_version(Path::new("/Apps/Adobe Photoshop 2024")), Some("11"));
+        assert_eq!(csxs_version(Path::new("/Apps/Adobe Photoshop 2025")), Some("12"));
+        assert_eq!(release_version("Photoshop", Path::new("/Apps/Adobe Photoshop CC")), None);
+    }
+
+    #[test]
+    fn main_paths_ignore_query_hash_and_remote_pages() {
+        let sandbox = crate::test_support::Sandbox::new();
+        let dir = sandbox.path("panel");
+        fs::create_dir_all(dir.join("client")).unwrap();
+        fs::write(dir.join("client/index.html"), "").unwrap();
+        let manifest = |paths: &[&str]| {
+            paths.iter().map(|p| format!("<MainPath>{}</MainPath>\n", p)).collect::<String>()
+        };
+
+        let local = check_main_paths(&dir, &manifest(&["./client/index.html?panel=main#top"]));
+        assert!(matches!(local.status, CheckStatus::Pass));
+        let mixed = check_main_paths(&dir, &manifest(&["./client/index.html", "https://example.com/panel"]));
+        assert!(matches!(mixed.status, CheckStatus::Pass));
+        let remote = check_main_paths(&dir, &manifest(&["https://example.com/panel?x=1"]));
+        assert!(matches!(remote.status, CheckStatus::Skipped));
+        let missing = check_main_paths(&dir, &manifest(&["./client/other.html#top"]));
+        assert!(matches!(missing.status, CheckStatus::Fail));
+    }
+}
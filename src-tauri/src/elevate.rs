@@ -0,0 +1,580 @@
+use crate::dependencies;
+use crate::hybrid::{self, PayloadMove};
+use crate::installer::{self, InstallResult};
+use crate::trash;
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::{Component, Path, PathBuf};
+use walkdir::WalkDir;
+
+/// Everything the elevated helper is willing to do. The helper is this binary
+/// re-invoked as `zxp-magic elevated <request.json>`; it checks that the request
+/// file is private to the caller, re-validates the request itself and never
+/// runs anything but these operations.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "op", rename_all = "snake_case")]
+pub enum PrivilegedOp {
+    /// Install a package into the system-wide extensions folder
+    Install { package: String },
+    /// Move an extension folder between an extensions folder and the trash
+    MoveDir { from: String, to: String },
+    /// Delete an extension folder or a trash item
+    RemoveDir { path: String },
+    /// Copy a companion bundled with an installed extension (by its index in
+    /// the extension's sidecar) to where the host apps load it from
+    InstallCompanion { extension_dir: String, index: usize },
+    /// Place the host app files of a hybrid package
+    InstallPayload { package: String },
+    /// Move host app files of an extension into or out of the trash; the helper
+    /// re-checks each against the record and the admin-owned host app folders
+    MovePayload { extension_id: String, moves: Vec<PayloadMove> },
+}
+
+/// Per-user folder for request files. Only the caller may be able to place or
+/// swap a request, so on Linux this is not the shared data folder.
+fn requests_folder() -> PathBuf {
+    if cfg!(test) {
+        return installer::get_data_folder().join("elevation");
+    }
+    #[cfg(any(target_os = "windows", target_os = "macos"))]
+    {
+        installer::get_data_folder().join("elevation")
+    }
+    #[cfg(not(any(target_os = "windows", target_os = "macos")))]
+    {
+        match std::env::var_os("XDG_RUNTIME_DIR").filter(|dir| !dir.is_empty()) {
+            Some(runtime) => PathBuf::from(runtime).join("zxp-magic"),
+            None => PathBuf::from(std::env::var_os("HOME").unwrap_or_default())
+                .join(".local")
+                .join("share")
+                .join("zxp-magic")
+                .join("elevation"),
+        }
+    }
+}
+
+/// Create the requests folder, readable and writable by the current user only
+fn create_requests_folder() -> Result<PathBuf, String> {
+    let folder = requests_folder();
+    fs::create_dir_all(&folder).map_err(|e| format!("Cannot create request folder: {}", e))?;
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::{MetadataExt, PermissionsExt};
+        let meta = fs::symlink_metadata(&folder).map_err(|e| format!("Cannot read request folder: {}", e))?;
+        // SAFETY: geteuid has no preconditions
+        if !meta.is_dir() || meta.uid() != unsafe { libc::geteuid() } {
+            return Err(format!("{} is not a folder owned by you", folder.display()));
+        }
+        fs::set_permissions(&folder, fs::Permissions::from_mode(0o700))
+            .map_err(|e| format!("Cannot protect request folder: {}", e))?;
+    }
+    Ok(folder)
+}
+
+/// Write a new file only the current user can read; fails if the name is taken
+fn write_private(path: &Path, content: &str) -> std::io::Result<fs::File> {
+    use std::io::Write;
+    let mut options = fs::OpenOptions::new();
+    options.write(true).create_new(true);
+    #[cfg(unix)]
+    std::os::unix::fs::OpenOptionsExt::mode(&mut options, 0o600);
+    let mut file = options.open(path)?;
+    file.write_all(content.as_bytes())?;
+    Ok(file)
+}
+
+/// Read a request in the helper. The path comes from the command line, so
+/// trust comes from the file itself: a regular, unlinked-elsewhere file that
+/// only its owner can access, in a folder only that owner can access, and
+/// (under pkexec) owned by the user who asked. Returns the content and owner.
+#[cfg(unix)]
+fn read_request(path: &Path) -> Result<(String, u32), String> {
+    use std::io::Read;
+    use std::os::unix::fs::{MetadataExt, OpenOptionsExt};
+    let mut file = fs::OpenOptions::new()
+        .read(true)
+        .custom_flags(libc::O_NOFOLLOW)
+        .open(path)
+        .map_err(|e| format!("Cannot read request: {}", e))?;
+    let meta = file.metadata().map_err(|e| format!("Cannot read request: {}", e))?;
+    let folder = path
+        .parent()
+        .and_then(|p| fs::symlink_metadata(p).ok())
+        .ok_or_else(|| "Cannot read request folder".to_string())?;
+    let caller = std::env::var("PKEXEC_UID").ok().and_then(|uid| uid.parse::<u32>().ok());
+
+    let private = meta.is_file()
+        && meta.nlink() == 1
+        && meta.mode() & 0o077 == 0
+        && caller.map(|uid| uid == meta.uid()).unwrap_or(true)
+        && folder.is_dir()
+        && folder.uid() == meta.uid()
+        && folder.mode() & 0o077 == 0;
+    if !private {
+        return Err(format!("Refusing request that is not private to its owner: {}", path.display()));
+    }
+
+    let mut content = String::new();
+    file.read_to_string(&mut content).map_err(|e| format!("Cannot read request: {}", e))?;
+    Ok((content, meta.uid()))
+}
+
+/// Read a request in the helper. It must sit in this account's own requests
+/// folder, which only the account (and administrators) can write to.
+#[cfg(not(unix))]
+fn read_request(path: &Path) -> Result<(String, u32), String> {
+    let expected = fs::canonicalize(requests_folder()).ok();
+    let actual = path.parent().and_then(|p| fs::canonicalize(p).ok());
+    if expected.is_none() || expected != actual {
+        return Err(format!("Refusing request outside {}", requests_folder().display()));
+    }
+    let content = fs::read_to_string(path).map_err(|e| format!("Cannot read request: {}", e))?;
+    Ok((content, 0))
+}
+
+/// Home folder of a user account, from the system user database
+#[cfg(unix)]
+fn home_of(uid: u32) -> Option<PathBuf> {
+    use std::os::unix::ffi::OsStrExt;
+    // SAFETY: the helper is single-threaded here, and the entry is copied out
+    // before anything else can call into the user database
+    unsafe {
+        let entry = libc::getpwuid(uid);
+        if entry.is_null() || (*entry).pw_dir.is_null() {
+            return None;
+        }
+        let dir = std::ffi::CStr::from_ptr((*entry).pw_dir);
+        Some(PathBuf::from(std::ffi::OsStr::from_bytes(dir.to_bytes())))
+    }
+}
+
+fn failure(message: String) -> InstallResult {
+    InstallResult {
+        success: false,
+        message,
+        extension: None,
+        payload: Vec::new(),
+    }
+}
+
+fn done(message: &str) -> InstallResult {
+    InstallResult {
+        success: true,
+        message: message.to_string(),
+        extension: None,
+        payload: Vec::new(),
+    }
+}
+
+/// Absolute and free of `..`, so prefix checks mean what they say
+fn is_clean_absolute(path: &Path) -> bool {
+    path.is_absolute() && !path.components().any(|c| c == Component::ParentDir)
+}
+
+fn is_symlink(path: &Path) -> bool {
+    fs::symlink_metadata(path)
+        .map(|m| m.file_type().is_symlink())
+        .unwrap_or(false)
+}
+
+/// True if `path` or any folder above it is a symbolic link
+pub fn has_symlink_component(path: &Path) -> bool {
+    path.ancestors().any(is_symlink)
+}
+
+/// Canonical form of `path`, or None if any part of it is a link. Paths that
+/// don't exist yet resolve through their parent.
+fn resolve(path: &Path) -> Option<PathBuf> {
+    if has_symlink_component(path) {
+        return None;
+    }
+    match fs::canonicalize(path) {
+        Ok(resolved) => Some(resolved),
+        Err(_) => Some(fs::canonicalize(path.parent()?).ok()?.join(path.file_name()?)),
+    }
+}
+
+/// A folder directly inside one of the CEP extensions folders
+fn is_extension_folder(path: &Path) -> bool {
+    let Some(path) = resolve(path) else {
+        return false;
+    };
+    let mut roots = installer::get_all_extension_folders();
+    roots.extend(installer::get_system_extensions_folder());
+    path.file_name().is_some()
+        && roots
+            .iter()
+            .filter_map(|root| fs::canonicalize(root).ok())
+            .any(|root| path.parent() == Some(root.as_path()))
+}
+
+/// Strictly inside the trash folder, not reached through a link
+pub fn is_in_trash(path: &Path) -> bool {
+    let (Some(path), Ok(trash)) = (resolve(path), fs::canonicalize(trash::trash_folder())) else {
+        return false;
+    };
+    path.starts_with(&trash) && path != trash
+}
+
+fn validate(op: &PrivilegedOp) -> Result<(), String> {
+    match op {
+        PrivilegedOp::Install { package } | PrivilegedOp::InstallPayload { package } => {
+            let package = Path::new(package);
+            let is_package = package
+                .extension()
+                .map(|e| e.eq_ignore_ascii_case("zxp") || e.eq_ignore_ascii_case("zxpinstall"))
+                .unwrap_or(false);
+            if !is_clean_absolute(package) || !is_package || !package.is_file() {
+                return Err(format!("Not a ZXP package: {}", package.display()));
+            }
+        }
+        PrivilegedOp::MoveDir { from, to } => {
+            let (from, to) = (Path::new(from), Path::new(to));
+            if !is_clean_absolute(from) || !is_clean_absolute(to) || has_symlink_component(from) {
+                return Err(format!("Refusing to move {}", from.display()));
+            }
+            let uninstall = is_extension_folder(from) && is_in_trash(to);
+            let restore = is_in_trash(from) && is_extension_folder(to);
+            if !uninstall && !restore {
+                return Err(format!(
+                    "Moves are only allowed between an extensions folder and the trash: {} → {}",
+                    from.display(),
+                    to.display()
+                ));
+            }
+        }
+        PrivilegedOp::RemoveDir { path } => {
+            let path = Path::new(path);
+            if !is_clean_absolute(path) || has_symlink_component(path) || !(is_extension_folder(path) || is_in_trash(path)) {
+                return Err(format!("Refusing to remove {}", path.display()));
+            }
+        }
+        PrivilegedOp::InstallCompanion { extension_dir, .. } => {
+            let dir = Path::new(extension_dir);
+            if !is_clean_absolute(dir) || has_symlink_component(dir) || !is_extension_folder(dir) {
+                return Err(format!("Not an installed extension: {}", dir.display()));
+            }
+        }
+        PrivilegedOp::MovePayload { extension_id, moves } => {
+            // Used as a record file name
+            if extension_id.is_empty() || extension_id.contains(['/', '\\']) || extension_id.starts_with('.') {
+                return Err(format!("Invalid extension id '{}'", extension_id));
+            }
+            if moves.is_empty() {
+                return Err("No files to move".to_string());
+            }
+            let clean = |m: &&PayloadMove| is_clean_absolute(Path::new(&m.from)) && is_clean_absolute(Path::new(&m.to));
+            if let Some(item) = moves.iter().find(|m| !clean(m)) {
+                return Err(format!("Refusing to move {}", item.from));
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Hand a moved tree to the owner of its new parent folder, so files taken out
+/// of a root-owned folder stay manageable by the user (and vice versa)
+#[cfg(unix)]
+fn adopt_parent_owner(path: &Path) {
+    use std::os::unix::fs::MetadataExt;
+    let Some(owner) = path.parent().and_then(|p| fs::metadata(p).ok()) else {
+        return;
+    };
+    for entry in WalkDir::new(path).into_iter().flatten() {
+        let _ = std::os::unix::fs::lchown(entry.path(), Some(owner.uid()), Some(owner.gid()));
+    }
+}
+
+#[cfg(not(unix))]
+fn adopt_parent_owner(_path: &Path) {}
+
+/// Carry out a validated operation in the elevated process
+fn perform(op: &PrivilegedOp) -> InstallResult {
+    if let Err(e) = validate(op) {
+        return failure(e);
+    }
+    match op {
+        PrivilegedOp::Install { package } => match installer::get_system_extensions_folder() {
+            Some(root) => installer::install_into(package, &root),
+            None => failure("There is no system extensions folder on this platform".to_string()),
+        },
+        PrivilegedOp::MoveDir { from, to } => match trash::move_dir_direct(Path::new(from), Path::new(to)) {
+            Ok(()) => {
+                adopt_parent_owner(Path::new(to));
+                done("Moved")
+            }
+            Err(e) => failure(format!("Cannot move {}: {}", from, e)),
+        },
+        PrivilegedOp::RemoveDir { path } => match fs::remove_dir_all(path) {
+            Ok(()) => done("Removed"),
+            Err(e) => failure(format!("Cannot remove {}: {}", path, e)),
+        },
+        PrivilegedOp::InstallCompanion { extension_dir, index } => {
+            match dependencies::install_bundled(Path::new(extension_dir), *index) {
+                Ok(()) => done("Installed"),
+                Err(e) => failure(e),
+            }
+        }
+        PrivilegedOp::InstallPayload { package } => hybrid::install_payload_only(package),
+        PrivilegedOp::MovePayload { extension_id, moves } => {
+            let result = hybrid::move_payload_only(extension_id, moves);
+            if result.success {
+                // Trashed files become the user's, restored ones the app folder owner's
+                for item in moves {
+                    adopt_parent_owner(Path::new(&item.to));
+                }
+            }
+            result
+        }
+    }
+}
+
+/// Entry point of the elevated helper (`zxp-magic elevated <request.json>`)
+pub fn serve(args: &[String]) -> i32 {
+    let Some(request) = args.first().map(PathBuf::from) else {
+        return 2;
+    };
+    let (content, owner) = match read_request(&request) {
+        Ok(request) => request,
+        Err(e) => {
+            eprintln!("{}", e);
+            return 2;
+        }
+    };
+    // pkexec and macOS authorization run the helper as root with root's
+    // environment; work in the request owner's home so the data, trash and
+    // extensions folders are the caller's. Windows keeps the caller's profile.
+    #[cfg(unix)]
+    match home_of(owner) {
+        Some(home) => std::env::set_var("HOME", home),
+        None => {
+            eprintln!("Unknown request owner {}", owner);
+            return 2;
+        }
+    }
+
+    let result = match serde_json::from_str::<PrivilegedOp>(&content) {
+        Ok(op) => perform(&op),
+        Err(e) => failure(format!("Invalid request: {}", e)),
+    };
+
+    let json = serde_json::to_string(&result).unwrap_or_default();
+    let written = write_private(&request.with_extension("result.json"), &json);
+    // Hand the result to the caller, who cannot read a root-owned 0600 file
+    #[cfg(unix)]
+    let written = written.and_then(|file| std::os::unix::fs::fchown(&file, Some(owner), None));
+    #[cfg(not(unix))]
+    let _ = owner;
+    if let Err(e) = written.map(|_| ()) {
+        eprintln!("Cannot write result: {}", e);
+        return 1;
+    }
+    if result.success {
+        0
+    } else {
+        1
+    }
+}
+
+/// Start the helper with administrator rights and wait for it to exit
+fn launch(exe: &Path, request: &Path) -> Result<std::process::ExitStatus, String> {
+    #[cfg(target_os = "windows")]
+    {
+        // Only our own executable and request paths are quoted in; the operation
+        // itself travels in the request file. Windows paths cannot contain '"'.
+        // The helper only trusts its own profile, so over-the-shoulder elevation
+        // (approving as a different account) is refused rather than redirected.
+        let quote = |p: &Path| p.to_string_lossy().replace('\'', "''");
+        let script = format!(
+            "$p = Start-Process -FilePath '{}' -ArgumentList 'elevated \"{}\"' -Verb RunAs -Wait -PassThru -WindowStyle Hidden; exit $p.ExitCode",
+            quote(exe),
+            quote(request)
+        );
+        std::process::Command::new("powershell")
+            .args(["-NoProfile", "-NonInteractive", "-Command", &script])
+            .status()
+            .map_err(|e| format!("Cannot elevate: {}", e))
+    }
+    #[cfg(target_os = "macos")]
+    {
+        // Paths are passed as argv and quoted by AppleScript itself
+        std::process::Command::new("osascript")
+            .args([
+                "-e",
+                "on run argv",
+                "-e",
+                "do shell script quoted form of item 1 of argv & \" elevated \" & quoted form of item 2 of argv with administrator privileges",
+                "-e",
+                "end run",
+            ])
+            .arg(exe)
+            .arg(request)
+            .status()
+            .map_err(|e| format!("Cannot elevate: {}", e))
+    }
+    #[cfg(not(any(target_os = "windows", target_os = "macos")))]
+    {
+        std::process::Command::new("pkexec")
+            .arg(exe)
+            .arg("elevated")
+            .arg(request)
+            .status()
+            .map_err(|e| format!("Cannot elevate (is pkexec installed?): {}", e))
+    }
+}
+
+/// Run an operation with administrator rights in a re-invoked copy of this binary
+pub fn run_elevated(op: &PrivilegedOp) -> InstallResult {
+    // Fail early on requests the helper would reject anyway
+    if let Err(e) = validate(op) {
+        return failure(e);
+    }
+    let exe = match std::env::current_exe() {
+        Ok(exe) => exe,
+        Err(e) => return failure(format!("Cannot locate helper executable: {}", e)),
+    };
+    let folder = match create_requests_folder() {
+        Ok(folder) => folder,
+        Err(e) => return failure(e),
+    };
+
+    let stamp = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map(|d| d.as_nanos())
+        .unwrap_or(0);
+    let request = folder.join(format!("{}-{}.json", std::process::id(), stamp));
+    let response = request.with_extension("result.json");
+    let json = match serde_json::to_string(op) {
+        Ok(json) => json,
+        Err(e) => return failure(e.to_string()),
+    };
+    // A leftover result from an earlier run must not be mistaken for this one
+    let _ = fs::remove_file(&response);
+    if let Err(e) = write_private(&request, &json) {
+        return failure(format!("Cannot write request: {}", e));
+    }
+
+    let status = launch(&exe, &request);
+    let result = fs::read_to_string(&response)
+        .ok()
+        .and_then(|content| serde_json::from_str::<InstallResult>(&content).ok());
+    let _ = fs::remove_file(&request);
+    let _ = fs::remove_file(&response);
+
+    match (status, result) {
+        (_, Some(result)) => result,
+        (Ok(_), None) => failure("Administrator rights were not granted".to_string()),
+        (Err(e), None) => failure(e),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::Sandbox;
+
+    #[cfg(unix)]
+    fn chmod(path: &Path, mode: u32) {
+        use std::os::unix::fs::PermissionsExt;
+        fs::set_permissions(path, fs::Permissions::from_mode(mode)).unwrap();
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn requests_folder_is_private() {
+        use std::os::unix::fs::PermissionsExt;
+        let _sandbox = Sandbox::new();
+        fs::create_dir_all(requests_folder()).unwrap();
+        chmod(&requests_folder(), 0o777);
+        let folder = create_requests_folder().unwrap();
+        assert_eq!(fs::metadata(folder).unwrap().permissions().mode() & 0o777, 0o700);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn reads_private_request() {
+        let _sandbox = Sandbox::new();
+        let request = create_requests_folder().unwrap().join("1.json");
+        write_private(&request, "{}").unwrap();
+        let (content, owner) = read_request(&request).unwrap();
+        assert_eq!(content, "{}");
+        assert_eq!(owner, unsafe { libc::geteuid() });
+        // Never overwrites an existing file
+        assert!(write_private(&request, "{}").is_err());
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn refuses_readable_request() {
+        let _sandbox = Sandbox::new();
+        let request = create_requests_folder().unwrap().join("1.json");
+        write_private(&request, "{}").unwrap();
+        chmod(&request, 0o644);
+        assert!(read_request(&request).is_err());
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn refuses_request_in_shared_folder() {
+        let _sandbox = Sandbox::new();
+        let folder = create_requests_folder().unwrap();
+        let request = folder.join("1.json");
+        write_private(&request, "{}").unwrap();
+        chmod(&folder, 0o777);
+        assert!(read_request(&request).is_err());
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn refuses_linked_request() {
+        let sandbox = Sandbox::new();
+        let folder = create_requests_folder().unwrap();
+        let target = sandbox.path("target.json");
+        write_private(&target, "{}").unwrap();
+        std::os::unix::fs::symlink(&target, folder.join("link.json")).unwrap();
+        fs::hard_link(&target, folder.join("hard.json")).unwrap();
+        assert!(read_request(&folder.join("link.json")).is_err());
+        assert!(read_request(&folder.join("hard.json")).is_err());
+    }
+
+    #[test]
+    fn validate_limits_moves_to_extensions_and_trash() {
+        let sandbox = Sandbox::new();
+        let extension = installer::get_extensions_folder().join("com.test.panel");
+        fs::create_dir_all(&extension).unwrap();
+        fs::create_dir_all(trash::trash_folder()).unwrap();
+        let trashed = trash::trash_folder().join("item").to_string_lossy().to_string();
+        let outside = sandbox.path("outside").to_string_lossy().to_string();
+        let extension = extension.to_string_lossy().to_string();
+
+        let uninstall = PrivilegedOp::MoveDir { from: extension.clone(), to: trashed.clone() };
+        assert!(validate(&uninstall).is_ok());
+        let restore = PrivilegedOp::MoveDir { from: trashed.clone(), to: extension.clone() };
+        assert!(validate(&restore).is_ok());
+        let escape = PrivilegedOp::MoveDir { from: extension.clone(), to: outside.clone() };
+        assert!(validate(&escape).is_err());
+        assert!(validate(&PrivilegedOp::RemoveDir { path: outside }).is_err());
+        assert!(validate(&PrivilegedOp::RemoveDir { path: format!("{}/../..", trashed) }).is_err());
+        assert!(validate(&PrivilegedOp::RemoveDir { path: extension }).is_ok());
+    }
+
+    #[test]
+    fn validate_checks_payload_requests() {
+        let _sandbox = Sandbox::new();
+        let step = |from: &str, to: &str| PayloadMove {
+            from: from.to_string(),
+            to: to.to_string(),
+        };
+        let moves = vec![step("/opt/host/plugin.8bf", "/home/user/trash/item/payload/files/0-plugin.8bf")];
+        let request = |extension_id: &str, moves: &[PayloadMove]| PrivilegedOp::MovePayload {
+            extension_id: extension_id.to_string(),
+            moves: moves.to_vec(),
+        };
+        assert!(validate(&request("com.test.panel", &moves)).is_ok());
+        assert!(validate(&request("../escape", &moves)).is_err());
+        assert!(validate(&request("com.test.panel", &[])).is_err());
+        assert!(validate(&request("com.test.panel", &[step("relative/file", "/tmp/x")])).is_err());
+        assert!(validate(&request("com.test.panel", &[step("/opt/a", "/tmp/../etc/x")])).is_err());
+        assert!(validate(&PrivilegedOp::Install { package: "/no/such/package.zxp".to_string() }).is_err());
+    }
+}
@@ -0,0 +1,316 @@
+use std::fs;
+use std::io::{Read, Seek};
+use std::path::{Component, Path, PathBuf};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use zip::read::ZipFile;
+use zip::{ExtraField, ZipArchive};
+
+/// Files that archivers on macOS and Windows add and extensions never need
+pub fn is_os_junk(entry_name: &str) -> bool {
+    let file_name = entry_name.trim_end_matches('/').rsplit('/').next().unwrap_or("");
+    entry_name.split('/').any(|part| part == "__MACOSX")
+        || file_name == ".DS_Store"
+        || file_name == "Thumbs.db"
+        // AppleDouble resource forks
+        || file_name.starts_with("._")
+}
+
+/// Locate the extension inside an archive: the index of the shallowest
+/// `CSXS/manifest.xml` and the folder prefix before it ("" or ending in '/'),
+/// however many wrapper folders the packer added
+pub fn find_extension_root<R: Read + Seek>(archive: &ZipArchive<R>) -> Option<(usize, String)> {
+    (0..archive.len())
+        .filter_map(|i| {
+            let name = archive.name_for_index(i)?;
+            if is_os_junk(name) {
+                return None;
+            }
+            // ASCII-only case folding keeps byte offsets valid for `name`
+            const SUFFIX: &str = "csxs/manifest.xml";
+            let prefix_len = name.len().checked_sub(SUFFIX.len())?;
+            if !name.is_char_boundary(prefix_len) || !name[prefix_len..].eq_ignore_ascii_case(SUFFIX) {
+                return None;
+            }
+            let prefix = &name[..prefix_len];
+            (prefix.is_empty() || prefix.ends_with('/')).then(|| (i, prefix.to_string()))
+        })
+        .min_by_key(|(_, prefix)| prefix.matches('/').count())
+}
+
+/// A path below the extension root with the manifest folder and file spelled
+/// `CSXS/manifest.xml`, however the packer cased them — the root is matched
+/// case-insensitively, but every reader opens that exact path
+pub fn normalize_manifest_path(rel: &Path) -> PathBuf {
+    let parts: Vec<Component> = rel.components().collect();
+    let is = |part: Option<&Component>, name: &str| {
+        matches!(part, Some(Component::Normal(part)) if part.to_str().is_some_and(|p| p.eq_ignore_ascii_case(name)))
+    };
+    if !is(parts.first(), "CSXS") {
+        return rel.to_path_buf();
+    }
+    let mut normalized = PathBuf::from("CSXS");
+    if parts.len() == 2 && is(parts.get(1), "manifest.xml") {
+        normalized.push("manifest.xml");
+    } else {
+        normalized.extend(&parts[1..]);
+    }
+    normalized
+}
+
+/// Days from 1970-01-01 to a civil date (proleptic Gregorian)
+fn days_from_civil(year: i64, month: i64, day: i64) -> i64 {
+    let y = if month <= 2 { year - 1 } else { year };
+    let era = y.div_euclid(400);
+    let yoe = y - era * 400;
+    let mp = (month + 9) % 12;
+    let doy = (153 * mp + 2) / 5 + day - 1;
+    let doe = yoe * 365 + yoe / 4 - yoe / 100 + doy;
+    era * 146_097 + doe - 719_468
+}
+
+/// Modification time of an entry: the UTC extended timestamp when present,
+/// otherwise the DOS timestamp (local time of the packer, taken as UTC)
+pub fn entry_mtime(entry: &ZipFile) -> Option<SystemTime> {
+    let extended = entry.extra_data_fields().find_map(|field| match field {
+        ExtraField::ExtendedTimestamp(ts) => ts.mod_time(),
+        _ => None,
+    });
+    if let Some(secs) = extended {
+        return Some(UNIX_EPOCH + Duration::from_secs(secs as u64));
+    }
+
+    let dt = entry.last_modified()?;
+    let days = days_from_civil(dt.year() as i64, dt.month() as i64, dt.day() as i64);
+    let secs = days * 86_400 + dt.hour() as i64 * 3600 + dt.minute() as i64 * 60 + dt.second() as i64;
+    u64::try_from(secs).ok().map(|s| UNIX_EPOCH + Duration::from_secs(s))
+}
+
+/// Apply an entry's stored Unix permissions and modification time to the extracted file
+pub fn apply_metadata(file: &fs::File, entry: &ZipFile) {
+    #[cfg(unix)]
+    if let Some(mode) = entry.unix_mode() {
+        use std::os::unix::fs::PermissionsExt;
+        // Keep the executable bits helpers need, drop setuid/setgid/sticky, and
+        // always leave the file owner-writable so updates and uninstall work
+        let mode = (mode & 0o777) | 0o600;
+        let _ = file.set_permissions(fs::Permissions::from_mode(mode));
+    }
+    if let Some(mtime) = entry_mtime(entry) {
+        let _ = file.set_modified(mtime);
+    }
+}
+
+/// Resolve a link target relative to the link's folder without touching the
+/// filesystem; None if it is absolute or climbs out of the extension root
+fn resolve_inside(link_rel: &Path, target: &str) -> Option<PathBuf> {
+    let mut resolved: Vec<Component> = link_rel.parent()?.components().collect();
+    for component in Path::new(target).components() {
+        match component {
+            Component::Normal(_) => resolved.push(component),
+            Component::CurDir => {}
+            Component::ParentDir => {
+                resolved.pop()?;
+            }
+            Component::RootDir | Component::Prefix(_) => return None,
+        }
+    }
+    Some(resolved.iter().collect())
+}
+
+/// Create symlink entries once every regular file is in place, so nothing is ever
+/// written through a link. Links that point outside `root` are skipped.
+pub fn create_links(root: &Path, links: &[(PathBuf, String)]) {
+    for (rel, target) in links {
+        let Some(resolved) = resolve_inside(rel, target) else {
+            log::warn!("Skipping symlink '{}' → '{}': points outside the extension", rel.display(), target);
+            continue;
+        };
+        let link = root.join(rel);
+        if let Some(parent) = link.parent() {
+            let _ = fs::create_dir_all(parent);
+        }
+
+        #[cfg(unix)]
+        {
+            if let Err(e) = std::os::unix::fs::symlink(target, &link) {
+                log::warn!("Cannot create symlink '{}': {}", rel.display(), e);
+                continue;
+            }
+            // A chain of individually harmless links can still lead outside — the real
+            // target must be where the link's text says and inside the extension
+            let inside = match (fs::canonicalize(&link), fs::canonicalize(root.join(&resolved)), fs::canonicalize(root)) {
+                (Ok(real), Ok(expected), Ok(root)) => real == expected && real.starts_with(root),
+                _ => false,
+            };
+            if !inside {
+                log::warn!("Removing symlink '{}': target is missing or outside the extension", rel.display());
+                let _ = fs::remove_file(&link);
+            }
+        }
+        #[cfg(not(unix))]
+        {
+            // Creating symlinks needs extra privileges on Windows — copy linked files instead
+            let source = root.join(resolved);
+            if source.is_file() {
+                if let Err(e) = fs::copy(&source, &link) {
+                    log::warn!("Cannot copy linked file '{}': {}", rel.display(), e);
+                }
+            } else {
+                log::warn!("Skipping symlink '{}': only links to files are supported here", rel.display());
+            }
+        }
+    }
+}
+
+/// Clear the macOS quarantine flag inside an installed extension so Gatekeeper
+/// does not block the helper binaries it ships
+pub fn clear_quarantine(dir: &Path) {
+    #[cfg(target_os = "macos")]
+    {
+        match std::process::Command::new("xattr")
+            .args(["-dr", "com.apple.quarantine"])
+            .arg(dir)
+            .output()
+        {
+            // xattr also fails when nothing was quarantined, so this is not worth a warning
+            Ok(output) if !output.status.success() => {
+                log::debug!(
+                    "Cannot clear quarantine on {}: {}",
+                    dir.display(),
+                    String::from_utf8_lossy(&output.stderr).trim()
+                );
+            }
+            Err(e) => log::warn!("Cannot run xattr: {}", e),
+            _ => {}
+        }
+    }
+    #[cfg(not(target_os = "macos"))]
+    let _ = dir;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::{Cursor, Write};
+    use zip::write::SimpleFileOptions;
+    use zip::ZipWriter;
+
+    fn archive(names: &[&str]) -> ZipArchive<Cursor<Vec<u8>>> {
+        let mut writer = ZipWriter::new(Cursor::new(Vec::new()));
+        for name in names {
+            writer.start_file(*name, SimpleFileOptions::default()).unwrap();
+            writer.write_all(b"x").unwrap();
+        }
+        ZipArchive::new(writer.finish().unwrap()).unwrap()
+    }
+
+    #[test]
+    fn finds_top_level_manifest() {
+        let archive = archive(&["index.html", "CSXS/manifest.xml"]);
+        assert_eq!(find_extension_root(&archive), Some((1, String::new())));
+    }
+
+    #[test]
+    fn prefers_the_shallowest_wrapper_folder() {
+        let archive = archive(&[
+            "Wrapper/node_modules/dep/CSXS/manifest.xml",
+            "Wrapper/CSXS/manifest.xml",
+            "__MACOSX/Wrapper/CSXS/manifest.xml",
+        ]);
+        assert_eq!(find_extension_root(&archive), Some((1, "Wrapper/".to_string())));
+    }
+
+    #[test]
+    fn matches_case_insensitively_with_non_ascii_prefix() {
+        // 'İ' changes byte length when lowercased; the prefix must still be sliced correctly
+        let archive = archive(&["İnstall/csxs/Manifest.XML"]);
+        assert_eq!(find_extension_root(&archive), Some((0, "İnstall/".to_string())));
+    }
+
+    #[test]
+    fn normalizes_manifest_case_only() {
+        assert_eq!(
+            normalize_manifest_path(Path::new("csxs/Manifest.XML")),
+            PathBuf::from("CSXS/manifest.xml")
+        );
+        assert_eq!(normalize_manifest_path(Path::new("Csxs/icons/A.png")), PathBuf::from("CSXS/icons/A.png"));
+        assert_eq!(normalize_manifest_path(Path::new("csxs")), PathBuf::from("CSXS"));
+        assert_eq!(
+            normalize_manifest_path(Path::new("js/csxs/manifest.xml")),
+            PathBuf::from("js/csxs/manifest.xml")
+        );
+    }
+
+    #[test]
+    fn ignores_lookalike_names() {
+        let archive = archive(&["NotCSXS/manifest.xml", "CSXS/manifest.xml.bak"]);
+        assert_eq!(find_extension_root(&archive), None);
+    }
+
+    #[test]
+    fn civil_dates_count_from_the_epoch() {
+        assert_eq!(days_from_civil(1970, 1, 1), 0);
+        assert_eq!(days_from_civil(2000, 3, 1), 11_017);
+        assert_eq!(days_from_civil(2024, 2, 29), 19_782);
+    }
+
+    #[test]
+    fn resolve_inside_keeps_links_in_the_root() {
+        assert_eq!(resolve_inside(Path::new("lib/current"), "v2/lib.so"), Some(PathBuf::from("lib/v2/lib.so")));
+        assert_eq!(resolve_inside(Path::new("lib/current"), "../bin/./tool"), Some(PathBuf::from("bin/tool")));
+        assert_eq!(resolve_inside(Path::new("lib/current"), "../../outside"), None);
+        assert_eq!(resolve_inside(Path::new("current"), "/etc/passwd"), None);
+    }
+
+    #[test]
+    fn entry_metadata_is_applied() {
+        let mut writer = ZipWriter::new(Cursor::new(Vec::new()));
+        let stamp = zip::DateTime::from_date_and_time(2021, 6, 15, 12, 30, 10).unwrap();
+        let options = SimpleFileOptions::default().last_modified_time(stamp).unix_permissions(0o4755);
+        writer.start_file("bin/tool", options).unwrap();
+        writer.write_all(b"#!/bin/sh").unwrap();
+        let mut archive = ZipArchive::new(writer.finish().unwrap()).unwrap();
+        let entry = archive.by_index(0).unwrap();
+
+        let expected = UNIX_EPOCH + Duration::from_secs(days_from_civil(2021, 6, 15) as u64 * 86_400 + 45_010);
+        assert_eq!(entry_mtime(&entry), Some(expected));
+
+        let dir = std::env::temp_dir().join(format!("zxp-magic-metadata-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let file = fs::File::create(dir.join("tool")).unwrap();
+        apply_metadata(&file, &entry);
+        let metadata = fs::metadata(dir.join("tool")).unwrap();
+        let _ = fs::remove_dir_all(&dir);
+        assert_eq!(metadata.modified().unwrap(), expected);
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            // setuid is dropped, the executable bits stay
+            assert_eq!(metadata.permissions().mode() & 0o7777, 0o755);
+        }
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn only_links_that_stay_inside_are_created() {
+        let root = std::env::temp_dir().join(format!("zxp-magic-links-{}", std::process::id()));
+        fs::create_dir_all(root.join("lib/v2")).unwrap();
+        fs::write(root.join("lib/v2/lib.so"), "lib").unwrap();
+        let links = vec![
+            (PathBuf::from("lib/current"), "v2".to_string()),
+            (PathBuf::from("lib/escape"), "../../../etc".to_string()),
+            // Harmless text, but it goes through "lib/up", which leads outside
+            (PathBuf::from("lib/up"), "..".to_string()),
+            (PathBuf::from("lib/chain"), "up/..".to_string()),
+            (PathBuf::from("lib/missing"), "v3".to_string()),
+        ];
+
+        create_links(&root, &links);
+        let exists = |rel: &str| fs::symlink_metadata(root.join(rel)).is_ok();
+        let created = (exists("lib/current"), exists("lib/escape"), exists("lib/up"), exists("lib/missing"));
+        let chain = exists("lib/chain");
+        let _ = fs::remove_dir_all(&root);
+        assert_eq!(created, (true, false, true, false));
+        assert!(!chain);
+    }
+}
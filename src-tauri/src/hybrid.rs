@@ -0,0 +1,767 @@
+use crate::dependencies;
+use crate::elevate::{self, PrivilegedOp};
+use crate::extract;
+use crate::installer::{self, InstallResult};
+use crate::settings;
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::io::{Read, Seek};
+use std::path::{Component, Path, PathBuf};
+use zip::ZipArchive;
+
+/// A host-app file from a hybrid package (scripts, plug-ins, presets)
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PayloadFile {
+    /// Path inside the package
+    pub source: String,
+    /// Where it was installed
+    pub path: String,
+    /// Host app it was installed for, e.g. "Photoshop"
+    pub product: String,
+}
+
+/// One `<file>` element of an Extension Manager `.mxi`
+#[derive(Debug, Clone, Default)]
+pub struct MxiFile {
+    pub source: String,
+    /// Token path such as `$scripts` or `$scriptuipanels/Vendor`
+    pub destination: String,
+    pub products: Vec<String>,
+    pub file_type: String,
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct Mxi {
+    /// Folder of the `.mxi` inside the archive ("" or ending in '/'); sources are relative to it
+    pub prefix: String,
+    pub products: Vec<String>,
+    pub files: Vec<MxiFile>,
+}
+
+impl MxiFile {
+    /// The CEP panel itself, as a nested ZXP
+    fn is_panel(&self) -> bool {
+        let lower = self.source.to_lowercase();
+        self.file_type.eq_ignore_ascii_case("CSXS") || lower.ends_with(".zxp") || lower.ends_with(".zxpinstall")
+    }
+}
+
+/// "Photoshop64", "PHXS" and "Photoshop" all name the same product folder
+fn normalize_product(name: &str) -> String {
+    let name = name.trim();
+    let name = name.strip_suffix("64").or_else(|| name.strip_suffix("32")).unwrap_or(name);
+    installer::format_host_name(name.trim())
+}
+
+fn split_products(list: &str) -> Vec<String> {
+    list.split(',')
+        .map(normalize_product)
+        .filter(|p| !p.is_empty())
+        .collect()
+}
+
+/// Parse the parts of an `.mxi` that say where files go
+pub fn parse_mxi(xml: &str) -> Mxi {
+    let mut mxi = Mxi::default();
+    // Attributes often wrap across lines — look at whole tags instead
+    for tag in xml.split('<').map(|t| t.replace(['\r', '\n', '\t'], " ")) {
+        if tag.starts_with("product ") {
+            if let Some(name) = installer::extract_attr(&tag, "name") {
+                let product = normalize_product(&name);
+                if !mxi.products.contains(&product) {
+                    mxi.products.push(product);
+                }
+            }
+        } else if tag.starts_with("file ") {
+            let Some(source) = installer::extract_attr(&tag, "source") else { continue };
+            mxi.files.push(MxiFile {
+                source: source.replace('\\', "/"),
+                destination: installer::extract_attr(&tag, "destination").unwrap_or_default(),
+                products: installer::extract_attr(&tag, "products")
+                    .map(|p| split_products(&p))
+                    .unwrap_or_default(),
+                file_type: installer::extract_attr(&tag, "file-type").unwrap_or_default(),
+            });
+        }
+    }
+    mxi
+}
+
+/// The shallowest `.mxi` in an archive, parsed
+pub fn find_mxi<R: Read + Seek>(archive: &mut ZipArchive<R>) -> Option<Mxi> {
+    let (index, name) = (0..archive.len())
+        .filter_map(|i| archive.name_for_index(i).map(|n| (i, n.to_string())))
+        .filter(|(_, n)| n.to_lowercase().ends_with(".mxi") && !extract::is_os_junk(n))
+        .min_by_key(|(_, n)| n.matches('/').count())?;
+    let mut xml = String::new();
+    archive.by_index(index).ok()?.read_to_string(&mut xml).ok()?;
+    let mut mxi = parse_mxi(&xml);
+    mxi.prefix = name.rfind('/').map(|i| name[..=i].to_string()).unwrap_or_default();
+    Some(mxi)
+}
+
+/// Read the `.mxi` of a package on disk, if it is a hybrid package
+pub fn read_mxi(path: &str) -> Option<Mxi> {
+    let file = fs::File::open(path).ok()?;
+    let mut archive = ZipArchive::new(file).ok()?;
+    find_mxi(&mut archive)
+}
+
+/// A panel ZXP extracted from a hybrid package; deleted when dropped
+pub struct NestedPackage {
+    pub path: PathBuf,
+}
+
+impl Drop for NestedPackage {
+    fn drop(&mut self) {
+        let _ = fs::remove_file(&self.path);
+    }
+}
+
+/// Extract the CEP panel a hybrid package carries as a nested ZXP
+pub fn extract_panel_package<R: Read + Seek>(archive: &mut ZipArchive<R>) -> Result<NestedPackage, String> {
+    let mxi = find_mxi(archive).ok_or("CSXS/manifest.xml not found in ZXP")?;
+    let panel = mxi
+        .files
+        .iter()
+        .find(|f| f.is_panel())
+        .ok_or("The package's .mxi does not list a CEP panel")?;
+    let name = format!("{}{}", mxi.prefix, panel.source);
+    let mut entry = archive
+        .by_name(&name)
+        .map_err(|e| format!("Cannot read '{}' from the package: {}", name, e))?;
+
+    let folder = installer::get_data_folder().join("tmp");
+    fs::create_dir_all(&folder).map_err(|e| format!("Cannot create temp folder: {}", e))?;
+    let stamp = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map(|d| d.as_nanos())
+        .unwrap_or(0);
+    let nested = NestedPackage {
+        path: folder.join(format!("{}-{}.zxp", std::process::id(), stamp)),
+    };
+    let mut out = fs::File::create(&nested.path).map_err(|e| format!("Cannot create temp file: {}", e))?;
+    let limits = settings::load_settings().extraction_limits;
+    installer::copy_limited(&mut entry, &mut out, limits.max_file_bytes)?;
+    Ok(nested)
+}
+
+/// The install folder of an app, looking through AE's "Support Files" on Windows
+fn app_root(app: &Path) -> PathBuf {
+    let support = app.join("Support Files");
+    if support.is_dir() {
+        support
+    } else {
+        app.to_path_buf()
+    }
+}
+
+/// Resolve a destination token (the part before the first '/') for one installed app
+fn token_folder(token: &str, product: &str, app: &Path) -> Option<PathBuf> {
+    let root = app_root(app);
+    let folder = match token.to_lowercase().as_str() {
+        "$scripts" => match product {
+            "After Effects" => root.join("Scripts"),
+            "Illustrator" => {
+                // Illustrator keeps presets per locale (Presets/en_US/Scripts)
+                let presets = root.join("Presets");
+                let locale = fs::read_dir(&presets)
+                    .ok()?
+                    .flatten()
+                    .map(|e| e.path())
+                    .find(|p| p.join("Scripts").is_dir())?;
+                locale.join("Scripts")
+            }
+            _ => root.join("Presets").join("Scripts"),
+        },
+        "$scriptuipanels" => root.join("Scripts").join("ScriptUI Panels"),
+        "$startupscripts" | "$startup" => dependencies::startup_script_folder(product)?,
+        "$plugins" | "$pluginsfolder" => root.join("Plug-ins"),
+        "$mediacore" => dependencies::shared_plugin_folder()?,
+        "$presets" => root.join("Presets"),
+        "$automate" => root.join("Plug-ins").join("Automate"),
+        "$filters" => root.join("Plug-ins").join("Filters"),
+        _ => return None,
+    };
+    Some(folder)
+}
+
+/// Only plain folder names may follow the token
+fn is_plain_relative(rel: &str) -> bool {
+    Path::new(rel).components().all(|c| matches!(c, Component::Normal(_) | Component::CurDir))
+}
+
+/// Folders payload files may be written to or removed from
+fn payload_roots() -> Vec<PathBuf> {
+    let products: Vec<String> = dependencies::PRODUCTS.iter().map(|p| p.to_string()).collect();
+    let mut roots = dependencies::app_dirs(&products);
+    roots.extend(dependencies::shared_plugin_folder());
+    roots.extend(dependencies::startup_script_roots());
+    roots
+}
+
+/// Folders the elevated helper may remove payload files from — only the
+/// admin-owned ones, not the per-user script folders
+fn elevated_payload_roots() -> Vec<PathBuf> {
+    let products: Vec<String> = dependencies::PRODUCTS.iter().map(|p| p.to_string()).collect();
+    let mut roots = dependencies::app_dirs(&products);
+    roots.extend(dependencies::shared_plugin_folder());
+    roots
+}
+
+/// Canonical form of a payload file strictly inside one of `roots`, or None
+/// if it lies elsewhere or is reached through a link. Missing files resolve
+/// through their parent.
+fn contained_payload_path(path: &Path, roots: &[PathBuf]) -> Option<PathBuf> {
+    if !path.is_absolute() || path.components().any(|c| c == Component::ParentDir) || elevate::has_symlink_component(path) {
+        return None;
+    }
+    let resolved = match fs::canonicalize(path) {
+        Ok(resolved) => resolved,
+        Err(_) => fs::canonicalize(path.parent()?).ok()?.join(path.file_name()?),
+    };
+    roots
+        .iter()
+        .filter_map(|root| fs::canonicalize(root).ok())
+        .any(|root| resolved.starts_with(&root) && resolved != root)
+        .then_some(resolved)
+}
+
+/// Outcome of placing a package's payload
+#[derive(Default)]
+struct Placement {
+    files: Vec<PayloadFile>,
+    errors: Vec<String>,
+    /// Some folder needs administrator rights
+    denied: bool,
+}
+
+fn place_entry<R: Read + Seek>(
+    archive: &mut ZipArchive<R>,
+    index: usize,
+    target: &Path,
+    placement: &mut Placement,
+) -> Result<(), String> {
+    let mut entry = archive.by_index(index).map_err(|e| e.to_string())?;
+    if elevate::has_symlink_component(target) {
+        return Err(format!("Not writing {}: the path goes through a link", target.display()));
+    }
+    if let Some(parent) = target.parent() {
+        if let Err(e) = fs::create_dir_all(parent) {
+            placement.denied |= e.kind() == std::io::ErrorKind::PermissionDenied;
+            return Err(format!("Cannot create {}: {}", parent.display(), e));
+        }
+    }
+    let mut out = match fs::File::create(target) {
+        Ok(out) => out,
+        Err(e) => {
+            placement.denied |= e.kind() == std::io::ErrorKind::PermissionDenied;
+            return Err(format!("Cannot write {}: {}", target.display(), e));
+        }
+    };
+    let limits = settings::load_settings().extraction_limits;
+    installer::copy_limited(&mut entry, &mut out, limits.max_file_bytes)?;
+    extract::apply_metadata(&out, &entry);
+    Ok(())
+}
+
+/// Copy every non-panel file of a hybrid package into each installed app it is meant for
+fn place_payload(package: &str) -> Result<Placement, String> {
+    let file = fs::File::open(package).map_err(|e| format!("Cannot open package: {}", e))?;
+    let mut archive = ZipArchive::new(file).map_err(|e| format!("Not a valid ZXP: {}", e))?;
+    let mxi = find_mxi(&mut archive).ok_or("No .mxi in package")?;
+    let names: Vec<String> = (0..archive.len())
+        .map(|i| archive.name_for_index(i).unwrap_or_default().to_string())
+        .collect();
+
+    let mut placement = Placement::default();
+    for item in mxi.files.iter().filter(|f| !f.is_panel()) {
+        let (token, rest) = item.destination.split_once('/').unwrap_or((item.destination.as_str(), ""));
+        if !token.starts_with('$') || !is_plain_relative(rest) || !is_plain_relative(&item.source) {
+            placement
+                .errors
+                .push(format!("Skipped '{}': unsupported destination '{}'", item.source, item.destination));
+            continue;
+        }
+        let source = format!("{}{}", mxi.prefix, item.source.trim_end_matches('/'));
+        let source_name = source.rsplit('/').next().unwrap_or_default().to_string();
+        // A file, or every file below a folder
+        let entries: Vec<(usize, String)> = names
+            .iter()
+            .enumerate()
+            .filter(|(_, n)| !n.ends_with('/') && !extract::is_os_junk(n))
+            .filter_map(|(i, n)| {
+                if *n == source {
+                    Some((i, source_name.clone()))
+                } else {
+                    n.strip_prefix(&format!("{}/", source))
+                        .filter(|rel| is_plain_relative(rel))
+                        .map(|rel| (i, format!("{}/{}", source_name, rel)))
+                }
+            })
+            .collect();
+        if entries.is_empty() {
+            placement.errors.push(format!("'{}' is listed in the .mxi but missing", item.source));
+            continue;
+        }
+
+        let products = if item.products.is_empty() { &mxi.products } else { &item.products };
+        let mut placed_any = false;
+        for product in products {
+            for app in dependencies::app_dirs(std::slice::from_ref(product)) {
+                let Some(folder) = token_folder(token, product, &app) else { continue };
+                let folder = if rest.is_empty() { folder } else { folder.join(rest) };
+                for (index, rel) in &entries {
+                    let target = folder.join(rel);
+                    match place_entry(&mut archive, *index, &target, &mut placement) {
+                        Ok(()) => {
+                            placed_any = true;
+                            placement.files.push(PayloadFile {
+                                source: names[*index].clone(),
+                                path: target.to_string_lossy().to_string(),
+                                product: product.clone(),
+                            });
+                        }
+                        Err(e) => placement.errors.push(e),
+                    }
+                }
+            }
+        }
+        if !placed_any && !placement.denied {
+            placement.errors.push(format!(
+                "'{}' not installed: no installed app matches {} ({})",
+                item.source,
+                item.destination,
+                products.join(", ")
+            ));
+        }
+    }
+    Ok(placement)
+}
+
+/// Install a package's payload without elevating (used by the elevated helper)
+pub fn install_payload_only(package: &str) -> InstallResult {
+    match place_payload(package) {
+        Ok(placement) => InstallResult {
+            success: placement.errors.is_empty(),
+            message: placement.errors.join("; "),
+            extension: None,
+            payload: placement.files,
+        },
+        Err(e) => InstallResult {
+            success: false,
+            message: e,
+            extension: None,
+            payload: Vec::new(),
+        },
+    }
+}
+
+fn records_folder() -> PathBuf {
+    installer::get_data_folder().join("payloads")
+}
+
+fn record_path(extension_id: &str) -> Result<PathBuf, String> {
+    Ok(records_folder().join(format!("{}.json", installer::folder_name_for(extension_id)?)))
+}
+
+/// Payload files installed along with an extension
+pub fn load_payload(extension_id: &str) -> Vec<PayloadFile> {
+    record_path(extension_id)
+        .ok()
+        .and_then(|path| fs::read_to_string(path).ok())
+        .and_then(|content| serde_json::from_str(&content).ok())
+        .unwrap_or_default()
+}
+
+fn save_payload(extension_id: &str, files: &[PayloadFile]) -> Result<(), String> {
+    fs::create_dir_all(records_folder()).map_err(|e| format!("Cannot create payload folder: {}", e))?;
+    let json = serde_json::to_string_pretty(files).map_err(|e| e.to_string())?;
+    fs::write(record_path(extension_id)?, json).map_err(|e| format!("Cannot write payload record: {}", e))
+}
+
+/// Install a hybrid package: the CEP panel (top-level or nested ZXP) into
+/// `extensions_dir`, then its host-app files
+pub fn install(path: &str, extensions_dir: &Path) -> InstallResult {
+    let has_panel = fs::File::open(path)
+        .ok()
+        .and_then(|f| ZipArchive::new(f).ok())
+        .map(|archive| extract::find_extension_root(&archive).is_some())
+        .unwrap_or(false);
+    let mut result = if has_panel {
+        installer::install_panel(path, extensions_dir)
+    } else {
+        let nested = fs::File::open(path)
+            .map_err(|e| format!("Cannot open ZXP file: {}", e))
+            .and_then(|f| ZipArchive::new(f).map_err(|e| format!("Cannot read ZXP archive: {}", e)))
+            .and_then(|mut archive| extract_panel_package(&mut archive));
+        match nested {
+            Ok(nested) => installer::install_panel(&nested.path.to_string_lossy(), extensions_dir),
+            Err(e) => InstallResult {
+                success: false,
+                message: format!("Invalid ZXP file: {}", e),
+                extension: None,
+                payload: Vec::new(),
+            },
+        }
+    };
+    let Some(extension_id) = result.extension.as_ref().filter(|_| result.success).map(|e| e.id.clone()) else {
+        return result;
+    };
+
+    let mut files = Vec::new();
+    let mut errors = Vec::new();
+    match place_payload(path) {
+        Ok(placement) => {
+            files = placement.files;
+            if placement.denied {
+                // Retry the whole payload with administrator rights; it overwrites what was placed
+                let elevated = elevate::run_elevated(&PrivilegedOp::InstallPayload {
+                    package: path.to_string(),
+                });
+                for file in elevated.payload {
+                    if !files.contains(&file) {
+                        files.push(file);
+                    }
+                }
+                if !elevated.success {
+                    errors.push(elevated.message);
+                }
+            } else {
+                errors = placement.errors;
+            }
+        }
+        Err(e) => errors.push(e),
+    }
+
+    // Keep earlier files too — an upgrade may have dropped some that are still on disk
+    let mut recorded = load_payload(&extension_id);
+    recorded.retain(|old| !files.iter().any(|f| f.path == old.path));
+    recorded.extend(files.iter().cloned());
+    if let Err(e) = save_payload(&extension_id, &recorded) {
+        log::warn!("{}", e);
+    }
+
+    if !files.is_empty() {
+        result.message = format!("{} Installed {} host app file(s).", result.message, files.len());
+    }
+    if !errors.is_empty() {
+        result.message = format!("{} Some host app files were not installed: {}", result.message, errors.join("; "));
+    }
+    result.payload = files;
+    result
+}
+
+/// A payload file going into or coming back out of a trash item
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PayloadMove {
+    pub from: String,
+    pub to: String,
+}
+
+/// Inside a trash item's payload folder: the record and the files themselves
+const STASH_RECORD: &str = "record.json";
+const STASH_FILES: &str = "files";
+
+/// A payload file kept in a trash item
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct StashedFile {
+    file: PayloadFile,
+    /// Name inside the stash's files folder
+    stored: String,
+}
+
+/// Move one file, copying across volumes. Unlike a folder, a file is removed
+/// in one step, so when that fails the original is intact and the copy can go.
+fn move_file(from: &Path, to: &Path) -> std::io::Result<()> {
+    match fs::rename(from, to) {
+        Ok(()) => Ok(()),
+        Err(e) if e.kind() == std::io::ErrorKind::PermissionDenied => Err(e),
+        Err(_) => {
+            fs::copy(from, to)?;
+            fs::remove_file(from).inspect_err(|_| {
+                let _ = fs::remove_file(to);
+            })
+        }
+    }
+}
+
+/// Carry out moves with the current user's rights. Returns the ones that
+/// need administrator rights.
+fn move_files(moves: &[PayloadMove]) -> Result<Vec<PayloadMove>, String> {
+    let mut denied = Vec::new();
+    for item in moves {
+        let (from, to) = (Path::new(&item.from), Path::new(&item.to));
+        let moved = match to.parent() {
+            Some(parent) => fs::create_dir_all(parent),
+            None => Ok(()),
+        }
+        .and_then(|()| move_file(from, to));
+        match moved {
+            Ok(()) => {}
+            Err(e) if e.kind() == std::io::ErrorKind::PermissionDenied => denied.push(item.clone()),
+            Err(e) => return Err(format!("Cannot move {}: {}", item.from, e)),
+        }
+    }
+    Ok(denied)
+}
+
+/// Move payload files without elevating (used by the elevated helper). Each
+/// move must either take a file recorded for the extension from an admin-owned
+/// host app folder into the trash, or bring one back from the trash into such
+/// a folder; the request is refused as a whole otherwise.
+pub fn move_payload_only(extension_id: &str, moves: &[PayloadMove]) -> InstallResult {
+    let recorded = load_payload(extension_id);
+    let roots = elevated_payload_roots();
+    let allowed = |item: &PayloadMove| {
+        let (from, to) = (Path::new(&item.from), Path::new(&item.to));
+        let trashing = contained_payload_path(from, &roots).is_some()
+            && recorded.iter().any(|r| r.path == item.from)
+            && elevate::is_in_trash(to);
+        let restoring = elevate::is_in_trash(from) && contained_payload_path(to, &roots).is_some();
+        trashing || restoring
+    };
+    let (success, message) = match moves.iter().find(|item| !allowed(item)) {
+        Some(item) => (false, format!("Refusing to move {} to {}", item.from, item.to)),
+        None => match move_files(moves) {
+            Ok(denied) if denied.is_empty() => (true, "Moved".to_string()),
+            Ok(_) => (false, "Permission denied".to_string()),
+            Err(e) => (false, e),
+        },
+    };
+    InstallResult {
+        success,
+        message,
+        extension: None,
+        payload: Vec::new(),
+    }
+}
+
+/// Move files, asking for administrator rights for those that need it
+fn move_payload(extension_id: &str, moves: &[PayloadMove]) -> Result<(), String> {
+    let denied = move_files(moves)?;
+    if denied.is_empty() {
+        return Ok(());
+    }
+    let result = elevate::run_elevated(&PrivilegedOp::MovePayload {
+        extension_id: extension_id.to_string(),
+        moves: denied,
+    });
+    if result.success {
+        Ok(())
+    } else {
+        Err(format!("Cannot move host app files: {}", result.message))
+    }
+}
+
+/// Move the host-app files installed with an extension, and their record,
+/// into `stash` (inside the extension's trash item)
+pub fn trash_payload(extension_id: &str, stash: &Path) -> Result<(), String> {
+    let recorded = load_payload(extension_id);
+    if recorded.is_empty() {
+        return Ok(());
+    }
+    let files = stash.join(STASH_FILES);
+    fs::create_dir_all(&files).map_err(|e| format!("Cannot create {}: {}", files.display(), e))?;
+
+    let roots = payload_roots();
+    let mut stashed = Vec::new();
+    let mut moves = Vec::new();
+    for (i, file) in recorded.into_iter().enumerate() {
+        let Some(path) = contained_payload_path(Path::new(&file.path), &roots) else {
+            log::warn!("Not moving {}: outside the host app folders", file.path);
+            continue;
+        };
+        if fs::symlink_metadata(&path).is_err() {
+            continue;
+        }
+        let name = path.file_name().map(|n| n.to_string_lossy().to_string()).unwrap_or_default();
+        let stored = format!("{}-{}", i, name);
+        moves.push(PayloadMove {
+            from: file.path.clone(),
+            to: files.join(&stored).to_string_lossy().to_string(),
+        });
+        stashed.push(StashedFile { file, stored });
+    }
+
+    // The stash record goes first, so a partly moved payload can still be restored
+    let json = serde_json::to_string_pretty(&stashed).map_err(|e| e.to_string())?;
+    fs::write(stash.join(STASH_RECORD), json).map_err(|e| format!("Cannot write payload record: {}", e))?;
+    move_payload(extension_id, &moves)?;
+    if let Ok(path) = record_path(extension_id) {
+        let _ = fs::remove_file(path);
+    }
+    Ok(())
+}
+
+/// Put back what `trash_payload` kept. Files that exist again are left alone;
+/// ones already moved back by an earlier, interrupted restore are recorded.
+pub fn restore_payload(extension_id: &str, stash: &Path) -> Result<(), String> {
+    let Ok(content) = fs::read_to_string(stash.join(STASH_RECORD)) else {
+        return Ok(());
+    };
+    let stashed: Vec<StashedFile> =
+        serde_json::from_str(&content).map_err(|e| format!("Invalid payload record: {}", e))?;
+
+    let roots = payload_roots();
+    let mut restored = Vec::new();
+    let mut moves = Vec::new();
+    for item in stashed {
+        let from = stash.join(STASH_FILES).join(&item.stored);
+        let Some(to) = contained_payload_path(Path::new(&item.file.path), &roots) else {
+            log::warn!("Not restoring {}: outside the host app folders", item.file.path);
+            continue;
+        };
+        if !from.is_file() {
+            if to.is_file() {
+                restored.push(item.file);
+            }
+            continue;
+        }
+        if fs::symlink_metadata(&to).is_ok() {
+            log::warn!("Not restoring {}: the file exists again", item.file.path);
+            continue;
+        }
+        moves.push(PayloadMove {
+            from: from.to_string_lossy().to_string(),
+            to: item.file.path.clone(),
+        });
+        restored.push(item.file);
+    }
+
+    move_payload(extension_id, &moves)?;
+    let mut recorded = load_payload(extension_id);
+    recorded.retain(|old| !restored.iter().any(|f| f.path == old.path));
+    recorded.extend(restored);
+    save_payload(extension_id, &recorded)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::{write_zxp, Sandbox};
+    use crate::trash;
+
+    const MXI: &str = r#"<?xml version="1.0"?>
+<macromedia-extension name="Tool" version="3.1.0">
+<products>
+  <product name="Photoshop64" version="25" primary="true"/>
+  <product name="After Effects" version="24"/>
+  <product name="Photoshop32" version="25"/>
+</products>
+<files>
+  <file source="panel\Tool.zxp" destination="" file-type="CSXS" products="Photoshop"/>
+  <file source="scripts/Export.jsx"
+        destination="$scripts" products="Photoshop64,AEFT"/>
+  <file destination="$presets"/>
+</files>
+</macromedia-extension>"#;
+
+    #[test]
+    fn parse_mxi_normalizes_products() {
+        let mxi = parse_mxi(MXI);
+        assert_eq!(mxi.products, vec!["Photoshop", "After Effects"]);
+    }
+
+    #[test]
+    fn parse_mxi_reads_files_across_lines() {
+        let mxi = parse_mxi(MXI);
+        assert_eq!(mxi.files.len(), 2);
+
+        let panel = &mxi.files[0];
+        assert_eq!(panel.source, "panel/Tool.zxp");
+        assert!(panel.is_panel());
+
+        let script = &mxi.files[1];
+        assert_eq!(script.destination, "$scripts");
+        assert_eq!(script.products, vec!["Photoshop", "After Effects"]);
+        assert!(!script.is_panel());
+    }
+
+    fn installed_plugin(sandbox: &Sandbox) -> PathBuf {
+        let plugin = sandbox.path("apps/Adobe Photoshop 2025/Plug-ins/Tool.8bf");
+        fs::create_dir_all(plugin.parent().unwrap()).unwrap();
+        fs::write(&plugin, "plugin").unwrap();
+        let file = PayloadFile {
+            source: "plugins/Tool.8bf".to_string(),
+            path: plugin.to_string_lossy().to_string(),
+            product: "Photoshop".to_string(),
+        };
+        save_payload("com.test.hybrid", &[file]).unwrap();
+        plugin
+    }
+
+    #[test]
+    fn trash_and_restore_payload_round_trip() {
+        let sandbox = Sandbox::new();
+        let plugin = installed_plugin(&sandbox);
+        let stash = sandbox.path("stash");
+
+        trash_payload("com.test.hybrid", &stash).unwrap();
+        assert!(!plugin.exists());
+        assert!(load_payload("com.test.hybrid").is_empty());
+        assert_eq!(fs::read_dir(stash.join(STASH_FILES)).unwrap().count(), 1);
+
+        restore_payload("com.test.hybrid", &stash).unwrap();
+        assert_eq!(fs::read_to_string(&plugin).unwrap(), "plugin");
+        let recorded = load_payload("com.test.hybrid");
+        assert_eq!(recorded.len(), 1);
+        assert_eq!(recorded[0].path, plugin.to_string_lossy());
+    }
+
+    #[test]
+    fn restore_leaves_files_that_exist_again() {
+        let sandbox = Sandbox::new();
+        let plugin = installed_plugin(&sandbox);
+        let stash = sandbox.path("stash");
+        trash_payload("com.test.hybrid", &stash).unwrap();
+        fs::write(&plugin, "newer").unwrap();
+
+        restore_payload("com.test.hybrid", &stash).unwrap();
+        assert_eq!(fs::read_to_string(&plugin).unwrap(), "newer");
+        assert!(load_payload("com.test.hybrid").is_empty());
+    }
+
+    #[test]
+    fn helper_moves_only_recorded_files_into_the_trash() {
+        let sandbox = Sandbox::new();
+        let plugin = installed_plugin(&sandbox);
+        let other = plugin.with_file_name("Other.8bf");
+        fs::write(&other, "other").unwrap();
+        let stash = trash::trash_folder().join("item/payload/files");
+        fs::create_dir_all(&stash).unwrap();
+        let into_trash = |from: &Path| PayloadMove {
+            from: from.to_string_lossy().to_string(),
+            to: stash.join("0-Tool.8bf").to_string_lossy().to_string(),
+        };
+        let elsewhere = PayloadMove {
+            from: plugin.to_string_lossy().to_string(),
+            to: sandbox.path("elsewhere/Tool.8bf").to_string_lossy().to_string(),
+        };
+
+        assert!(!move_payload_only("com.test.hybrid", &[into_trash(&other)]).success);
+        assert!(!move_payload_only("com.test.hybrid", &[elsewhere]).success);
+        assert!(other.exists() && plugin.exists());
+        assert!(move_payload_only("com.test.hybrid", &[into_trash(&plugin)]).success);
+        assert!(!plugin.exists());
+    }
+
+    #[test]
+    fn host_app_files_travel_with_the_trash_item() {
+        let sandbox = Sandbox::new();
+        let zxp = sandbox.path("in/panel.zxp");
+        write_zxp(&zxp, "com.test.hybrid", "1.0.0", &[]);
+        assert!(installer::install_extension(&zxp.to_string_lossy()).success);
+        let plugin = installed_plugin(&sandbox);
+
+        installer::uninstall_extension("com.test.hybrid", None).unwrap();
+        assert!(!plugin.exists());
+        let items = trash::list_trash();
+        assert!(trash::payload_stash(&items[0]).is_dir());
+
+        trash::restore_from_trash(&items[0].id).unwrap();
+        assert!(plugin.is_file());
+        assert_eq!(load_payload("com.test.hybrid").len(), 1);
+        assert!(trash::list_trash().is_empty());
+    }
+}
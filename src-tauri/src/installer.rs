@@ -1,10 +1,20 @@
+use crate::dependencies;
+use crate::elevate::{self, PrivilegedOp};
+use crate::extract;
+use crate::hybrid::{self, PayloadFile};
+use crate::integrity;
+use crate::locale;
+use crate::scan_cache;
+use crate::security::{self, SecurityReport};
+use crate::settings::{self, ExtractionLimits, FilterRules};
+use crate::trash;
 use serde::{Deserialize, Serialize};
 use std::fs;
-use std::io::Read;
+use std::io::{Read, Seek};
 use std::path::{Path, PathBuf};
 use zip::ZipArchive;
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
 pub struct ExtensionInfo {
     pub id: String,
     pub name: String,
@@ -15,9 +25,65 @@ pub struct ExtensionInfo {
     pub cep_version: String,
     pub install_path: Option<String>,
     pub icon_path: Option<String>,
+    /// Matched a built-in rule (vendor-bundled rather than user-installed)
+    #[serde(default)]
+    pub is_builtin: bool,
+    /// Why the extension is hidden from the default list, if it is
+    #[serde(default)]
+    pub hidden_reason: Option<String>,
+    /// `ExtensionBundleName` from the manifest
+    #[serde(default)]
+    pub bundle_name: String,
+    #[serde(default)]
+    pub vendor: String,
+    #[serde(default)]
+    pub copyright: String,
+    /// `<RequiredRuntime>` entries (CSXS and friends)
+    #[serde(default)]
+    pub required_runtimes: Vec<RuntimeRequirement>,
+    /// `<CEFCommandLine>` parameters, e.g. `--enable-nodejs`
+    #[serde(default)]
+    pub cef_parameters: Vec<String>,
+    #[serde(default)]
+    pub node_enabled: bool,
+    #[serde(default)]
+    pub mixed_context: bool,
+    /// Locale codes from `<LocaleList>` ("All" if unrestricted)
+    #[serde(default)]
+    pub locales: Vec<String>,
+    /// Seconds since the Unix epoch
+    #[serde(default)]
+    pub installed_at: Option<u64>,
+    #[serde(default)]
+    pub size_bytes: u64,
+    #[serde(default)]
+    pub file_count: u64,
+    /// SHA-256 of the package it was installed from (ZXP Magic installs only)
+    #[serde(default)]
+    pub package_sha256: Option<String>,
+    /// What the panel is allowed to do and which risky APIs its scripts use
+    #[serde(default)]
+    pub security: SecurityReport,
+    /// Other extensions the manifest declares as dependencies
+    #[serde(default)]
+    pub dependencies: Vec<ManifestDependency>,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RuntimeRequirement {
+    pub name: String,
+    pub version: String,
+}
+
+/// `<Dependency>` on another CEP extension from the manifest's `<DependencyList>`
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ManifestDependency {
+    pub id: String,
+    /// Minimum version, if the manifest gives one
+    pub version: Option<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct HostApp {
     pub name: String,
     pub version: String,
@@ -28,10 +94,26 @@ pub struct InstallResult {
     pub success: bool,
     pub message: String,
     pub extension: Option<ExtensionInfo>,
+    /// Host app files placed by a hybrid package
+    #[serde(default)]
+    pub payload: Vec<PayloadFile>,
+}
+
+/// Stand-in for the user's folders while a test sandbox is active
+#[cfg(test)]
+pub static TEST_HOME: std::sync::Mutex<Option<PathBuf>> = std::sync::Mutex::new(None);
+
+#[cfg(test)]
+pub fn test_home() -> Option<PathBuf> {
+    TEST_HOME.lock().unwrap_or_else(|e| e.into_inner()).clone()
 }
 
 /// Returns the primary (user-level) CEP extensions folder — used as install target
 pub fn get_extensions_folder() -> PathBuf {
+    #[cfg(test)]
+    if let Some(home) = test_home() {
+        return home.join("extensions");
+    }
     #[cfg(target_os = "windows")]
     {
         let appdata = std::env::var("APPDATA")
@@ -59,9 +141,64 @@ pub fn get_extensions_folder() -> PathBuf {
     }
 }
 
+/// Returns ZXP Magic's own data folder (package cache, install records, settings)
+pub fn get_data_folder() -> PathBuf {
+    #[cfg(test)]
+    if let Some(home) = test_home() {
+        return home.join("data");
+    }
+    #[cfg(target_os = "windows")]
+    {
+        let appdata = std::env::var("APPDATA")
+            .unwrap_or_else(|_| std::env::var("USERPROFILE")
+                .map(|p| format!("{}\\AppData\\Roaming", p))
+                .unwrap_or_else(|_| "C:\\Users\\Default\\AppData\\Roaming".to_string()));
+        PathBuf::from(appdata).join("com.egehan.zxp-magic")
+    }
+    #[cfg(target_os = "macos")]
+    {
+        let home = std::env::var("HOME").unwrap_or_else(|_| "~".to_string());
+        PathBuf::from(home)
+            .join("Library")
+            .join("Application Support")
+            .join("com.egehan.zxp-magic")
+    }
+    #[cfg(not(any(target_os = "windows", target_os = "macos")))]
+    {
+        PathBuf::from("/tmp/zxp_magic")
+    }
+}
+
+/// Returns the folder for extensions installed for all users, if the platform has one
+pub fn get_system_extensions_folder() -> Option<PathBuf> {
+    #[cfg(test)]
+    if test_home().is_some() {
+        return None;
+    }
+    #[cfg(target_os = "windows")]
+    {
+        let program_files = std::env::var("ProgramFiles(x86)")
+            .or_else(|_| std::env::var("ProgramFiles"))
+            .unwrap_or_else(|_| "C:\\Program Files (x86)".to_string());
+        Some(PathBuf::from(program_files).join("Common Files").join("Adobe").join("CEP").join("extensions"))
+    }
+    #[cfg(target_os = "macos")]
+    {
+        Some(PathBuf::from("/Library/Application Support/Adobe/CEP/extensions"))
+    }
+    #[cfg(not(any(target_os = "windows", target_os = "macos")))]
+    {
+        None
+    }
+}
+
 /// Returns all CEP extension folders to scan (user-level + system-level)
-fn get_all_extension_folders() -> Vec<PathBuf> {
+pub fn get_all_extension_folders() -> Vec<PathBuf> {
     let mut folders = vec![get_extensions_folder()];
+    #[cfg(test)]
+    if test_home().is_some() {
+        return folders;
+    }
 
     #[cfg(target_os = "windows")]
     {
@@ -90,40 +227,194 @@ fn get_all_extension_folders() -> Vec<PathBuf> {
 
 /// Parse manifest.xml from a ZXP (zip) file
 pub fn get_extension_info_from_zxp(path: &str) -> Result<ExtensionInfo, String> {
+    get_extension_info_from_zxp_localized(path, None)
+}
+
+/// Parse manifest.xml from a ZXP, resolving `%key` strings for `language`
+/// (the preferred display language when None)
+pub fn get_extension_info_from_zxp_localized(path: &str, language: Option<&str>) -> Result<ExtensionInfo, String> {
+    zxp_info(path, language, true)
+}
+
+/// `allow_nested` lets a hybrid package's nested panel ZXP be read — one level only,
+/// so a package that contains itself cannot recurse
+fn zxp_info(path: &str, language: Option<&str>, allow_nested: bool) -> Result<ExtensionInfo, String> {
     let file = fs::File::open(path).map_err(|e| format!("Cannot open file: {}", e))?;
     let mut archive = ZipArchive::new(file).map_err(|e| format!("Not a valid ZXP: {}", e))?;
 
-    // Find CSXS/manifest.xml inside the ZXP
-    let manifest_xml = {
-        let mut found = None;
-        for i in 0..archive.len() {
-            if let Ok(entry) = archive.by_index(i) {
-                let name = entry.name().to_lowercase();
-                if name.ends_with("manifest.xml") {
-                    found = Some(i);
-                    break;
-                }
-            }
+    let Some((manifest_index, root)) = extract::find_extension_root(&archive) else {
+        if !allow_nested {
+            return Err("CSXS/manifest.xml not found in ZXP".to_string());
         }
-        let idx = found.ok_or("manifest.xml not found in ZXP")?;
-        let mut entry = archive.by_index(idx).map_err(|e| e.to_string())?;
+        // Hybrid packages carry the panel as a nested ZXP
+        let nested = hybrid::extract_panel_package(&mut archive)?;
+        return zxp_info(&nested.path.to_string_lossy(), language, false);
+    };
+    let manifest_xml = {
+        let mut entry = archive.by_index(manifest_index).map_err(|e| e.to_string())?;
         let mut content = String::new();
         entry.read_to_string(&mut content).map_err(|e| e.to_string())?;
         content
     };
 
-    parse_manifest_xml(&manifest_xml, None)
+    let mut info = parse_manifest_xml(&manifest_xml, None)?;
+
+    // Locale files are small — read them all up front, keyed by path below the root.
+    // Bundled scripts are scanned in the same pass for the security report.
+    let mut locale_files = std::collections::HashMap::new();
+    for i in 0..archive.len() {
+        let Ok(mut entry) = archive.by_index(i) else { continue };
+        let Some(rel) = entry.name().strip_prefix(root.as_str()).map(str::to_string) else { continue };
+        if rel.starts_with("locale/") && rel.ends_with("messages.properties") {
+            let mut content = String::new();
+            if entry.read_to_string(&mut content).is_ok() {
+                locale_files.insert(rel, content);
+            }
+        } else if security::should_scan(&rel, entry.size()) {
+            let mut bytes = Vec::new();
+            if entry.read_to_end(&mut bytes).is_ok() {
+                info.security.scan_source(&rel, &String::from_utf8_lossy(&bytes));
+            }
+        }
+    }
+    info.security.finish_scan();
+    let available: Vec<String> = locale_files
+        .keys()
+        .filter_map(|rel| rel.strip_prefix("locale/")?.split_once('/').map(|(folder, _)| folder.to_string()))
+        .collect();
+
+    let language = language.map(locale::normalize).unwrap_or_else(locale::preferred_language);
+    locale::localize(&mut info, &language, &available, |rel| locale_files.get(rel).cloned());
+    Ok(info)
 }
 
-/// Returns true if the extension ID looks like an Adobe built-in (not user-installed)
-fn is_adobe_builtin(id: &str) -> bool {
-    // All of Adobe's own internal extensions use the com.adobe. namespace.
-    // Users never install extensions under this namespace themselves.
+/// Returns true if the extension ID is in Adobe's own namespace
+pub fn is_adobe_builtin(id: &str) -> bool {
     id.to_lowercase().starts_with("com.adobe.")
 }
 
-/// Scan all CEP extension folders and return all installed extensions
+/// Parse one extension directory (CSXS/manifest.xml), filling in icon and install path
+/// and resolving localized strings for `language`
+pub fn read_extension_dir(path: &Path, language: &str) -> Option<ExtensionInfo> {
+    if !path.is_dir() {
+        return None;
+    }
+    let content = fs::read_to_string(path.join("CSXS").join("manifest.xml")).ok()?;
+    let mut info = parse_manifest_xml(&content, Some(path)).ok()?;
+    locale::localize_dir(&mut info, path, language);
+    security::scan_dir(&mut info.security, path);
+
+    // Look for extension icon (manifest icon takes priority)
+    if info.icon_path.is_none() {
+        info.icon_path = scan_for_icon(path);
+    }
+    info.install_path = Some(path.to_string_lossy().to_string());
+    fill_install_details(&mut info, path);
+    Some(info)
+}
+
+/// Add on-disk facts: size, file count, install time, source package and .mxi vendor info
+fn fill_install_details(info: &mut ExtensionInfo, dir: &Path) {
+    for entry in walkdir::WalkDir::new(dir).into_iter().flatten() {
+        if entry.file_type().is_file() {
+            info.file_count += 1;
+            info.size_bytes += entry.metadata().map(|m| m.len()).unwrap_or(0);
+        }
+    }
+
+    match integrity::load_manifest(&info.id) {
+        Some(record) if Path::new(&record.install_path) == dir => {
+            info.installed_at = record.installed_at;
+            info.package_sha256 = Some(record.package_sha256);
+        }
+        _ => {}
+    }
+    if info.installed_at.is_none() {
+        // Folder creation time is the best guess for other installers
+        info.installed_at = fs::metadata(dir)
+            .and_then(|m| m.created().or_else(|_| m.modified()))
+            .ok()
+            .and_then(|t| t.duration_since(std::time::UNIX_EPOCH).ok())
+            .map(|d| d.as_secs());
+    }
+
+    if info.vendor.is_empty() || info.copyright.is_empty() {
+        if let Some(mxi) = read_mxi(dir) {
+            if info.vendor.is_empty() {
+                info.vendor = mxi.0;
+            }
+            if info.copyright.is_empty() {
+                info.copyright = mxi.1;
+            }
+        }
+    }
+}
+
+/// Vendor and copyright from a legacy Extension Manager `.mxi` file, if one ships
+fn read_mxi(dir: &Path) -> Option<(String, String)> {
+    let mxi = fs::read_dir(dir).ok()?.flatten().map(|e| e.path()).find(|p| {
+        p.extension().map(|e| e.eq_ignore_ascii_case("mxi")).unwrap_or(false)
+    })?;
+    let content = fs::read_to_string(mxi).ok()?;
+    let mut vendor = String::new();
+    let mut copyright = String::new();
+    for line in content.lines() {
+        let line = line.trim();
+        if line.contains("<author") && vendor.is_empty() {
+            vendor = extract_attr(line, "name").unwrap_or_default();
+        }
+        if line.contains("<copyright>") && copyright.is_empty() {
+            copyright = extract_text(line, "copyright");
+        }
+    }
+    Some((vendor, copyright))
+}
+
+/// Apply the filter rules to an extension found in `extensions_dir`, setting
+/// `is_builtin` and `hidden_reason`. Returns true if it is hidden.
+pub fn apply_filter_rules(extensions_dir: &Path, info: &mut ExtensionInfo, rules: &FilterRules) -> bool {
+    let id = info.id.to_lowercase();
+    let is_user_folder = extensions_dir == get_extensions_folder();
+
+    let builtin_prefix = rules
+        .builtin_id_prefixes
+        .iter()
+        .find(|p| !p.is_empty() && id.starts_with(&p.to_lowercase()));
+    info.is_builtin = builtin_prefix.is_some() && !(rules.builtin_system_only && is_user_folder);
+
+    let always_show = rules.always_show_ids.iter().any(|s| s.eq_ignore_ascii_case(&info.id));
+    info.hidden_reason = if always_show {
+        None
+    } else if info.is_builtin {
+        builtin_prefix.map(|p| format!("Built-in ({}*)", p))
+    } else if let Some(folder) = rules
+        .hidden_folders
+        .iter()
+        .find(|f| Path::new(f) == extensions_dir)
+    {
+        Some(format!("Hidden folder: {}", folder))
+    } else {
+        rules
+            .hidden_vendors
+            .iter()
+            .find(|v| !info.author.is_empty() && v.eq_ignore_ascii_case(&info.author))
+            .map(|v| format!("Hidden vendor: {}", v))
+    };
+    info.hidden_reason.is_some()
+}
+
+/// Scan all CEP extension folders and return the visible installed extensions
 pub fn list_extensions() -> Vec<ExtensionInfo> {
+    let result = list_extensions_filtered(false, None);
+    scan_cache::store_listing(&result);
+    result
+}
+
+/// Scan all CEP extension folders; `show_hidden` also returns built-in and hidden ones,
+/// `language` overrides the preferred display language
+pub fn list_extensions_filtered(show_hidden: bool, language: Option<&str>) -> Vec<ExtensionInfo> {
+    let rules = settings::load_settings().filter_rules;
+    let language = language.map(locale::normalize).unwrap_or_else(locale::preferred_language);
     let mut result: Vec<ExtensionInfo> = Vec::new();
     let mut seen_ids: std::collections::HashSet<String> = std::collections::HashSet::new();
 
@@ -132,41 +423,23 @@ pub fn list_extensions() -> Vec<ExtensionInfo> {
             continue;
         }
 
-        if let Ok(entries) = fs::read_dir(&extensions_dir) {
-            for entry in entries.flatten() {
-                let path = entry.path();
-                if !path.is_dir() {
-                    continue;
-                }
-
-                let manifest_path = path.join("CSXS").join("manifest.xml");
-                if !manifest_path.exists() {
-                    continue;
-                }
-
-                if let Ok(content) = fs::read_to_string(&manifest_path) {
-                    if let Ok(mut info) = parse_manifest_xml(&content, Some(&path)) {
-                        // Skip Adobe built-in extensions from system folders
-                        let is_user_folder = extensions_dir == get_extensions_folder();
-                        if !is_user_folder && is_adobe_builtin(&info.id) {
-                            continue;
-                        }
+        let dirs: Vec<PathBuf> = match fs::read_dir(&extensions_dir) {
+            Ok(entries) => entries.flatten().map(|e| e.path()).collect(),
+            Err(_) => continue,
+        };
 
-                        // Skip duplicates (same extension in multiple folders)
-                        if seen_ids.contains(&info.id) {
-                            continue;
-                        }
-                        seen_ids.insert(info.id.clone());
+        // Unchanged directories come from the scan index instead of being re-parsed
+        for mut info in scan_cache::read_dirs(&dirs, &language).into_iter().flatten() {
+            if apply_filter_rules(&extensions_dir, &mut info, &rules) && !show_hidden {
+                continue;
+            }
 
-                        // Look for extension icon (manifest icon takes priority)
-                        if info.icon_path.is_none() {
-                            info.icon_path = scan_for_icon(&path);
-                        }
-                        info.install_path = Some(path.to_string_lossy().to_string());
-                        result.push(info);
-                    }
-                }
+            // Skip duplicates (same extension in multiple folders)
+            if seen_ids.contains(&info.id) {
+                continue;
             }
+            seen_ids.insert(info.id.clone());
+            result.push(info);
         }
     }
 
@@ -174,29 +447,6 @@ pub fn list_extensions() -> Vec<ExtensionInfo> {
     result
 }
 
-/// If the target directory contains a single subfolder that has CSXS/manifest.xml,
-/// move its contents up one level (unwrap the wrapper folder).
-fn unwrap_nested_folder(target_dir: &Path) {
-    let entries: Vec<_> = fs::read_dir(target_dir)
-        .ok()
-        .map(|rd| rd.flatten().collect())
-        .unwrap_or_default();
-
-    if entries.len() == 1 && entries[0].path().is_dir() {
-        let wrapper = entries[0].path();
-        if wrapper.join("CSXS").join("manifest.xml").exists() {
-            // Move all contents from wrapper to target_dir
-            if let Ok(inner_entries) = fs::read_dir(&wrapper) {
-                for entry in inner_entries.flatten() {
-                    let dest = target_dir.join(entry.file_name());
-                    let _ = fs::rename(entry.path(), &dest);
-                }
-            }
-            let _ = fs::remove_dir_all(&wrapper);
-        }
-    }
-}
-
 /// Scan common icon filenames in an extension directory
 fn scan_for_icon(dir: &Path) -> Option<String> {
     // Check root folder
@@ -217,98 +467,523 @@ fn scan_for_icon(dir: &Path) -> Option<String> {
     None
 }
 
-/// Install a ZXP file into the extensions folder
+/// Why an archive entry is not extracted on install, or None if it is.
+/// `root` is the extension folder inside the archive, see `extract::find_extension_root`.
+pub fn skip_reason(entry_name: &str, root: &str) -> Option<&'static str> {
+    if entry_name.starts_with("META-INF") {
+        return Some("ZXP signature files");
+    }
+    if extract::is_os_junk(entry_name) {
+        return Some("Operating system junk");
+    }
+    if entry_name.ends_with('/') && root.starts_with(entry_name) {
+        return Some("Wrapper folder");
+    }
+    if !entry_name.starts_with(root) {
+        return Some("Outside the extension folder");
+    }
+    None
+}
+
+/// Characters Windows does not allow in file names
+const ILLEGAL_NAME_CHARS: &str = "<>:\"/\\|?*";
+
+/// Device names Windows reserves, with or without an extension
+const RESERVED_NAMES: &[&str] = &[
+    "CON", "PRN", "AUX", "NUL", "COM1", "COM2", "COM3", "COM4", "COM5", "COM6", "COM7", "COM8", "COM9", "LPT1",
+    "LPT2", "LPT3", "LPT4", "LPT5", "LPT6", "LPT7", "LPT8", "LPT9",
+];
+
+const MAX_FOLDER_NAME: usize = 200;
+
+/// Folder name for an extension id that is valid on every platform
+pub fn folder_name_for(extension_id: &str) -> Result<String, String> {
+    let mut name: String = extension_id
+        .chars()
+        .map(|c| if c.is_control() || ILLEGAL_NAME_CHARS.contains(c) { '_' } else { c })
+        .collect();
+    // Windows silently drops trailing dots and spaces, making the folder unreachable
+    name = name.trim().trim_end_matches('.').to_string();
+    if name.chars().all(|c| c == '.' || c == '_') {
+        return Err(format!("'{}' cannot be used as a folder name", extension_id));
+    }
+
+    let stem = name.split('.').next().unwrap_or("").to_uppercase();
+    if RESERVED_NAMES.contains(&stem.as_str()) {
+        name.insert(0, '_');
+    }
+    while name.len() > MAX_FOLDER_NAME {
+        name.pop();
+    }
+    Ok(name)
+}
+
+/// Manifest data of the extension in a folder, without the install details
+pub fn read_manifest(dir: &Path) -> Option<ExtensionInfo> {
+    let content = fs::read_to_string(dir.join("CSXS").join("manifest.xml")).ok()?;
+    parse_manifest_xml(&content, Some(dir)).ok()
+}
+
+/// Bundle id plus the id of every `<Extension>` in an installed folder's manifest
+pub fn manifest_extension_ids(dir: &Path) -> Vec<String> {
+    let Ok(content) = fs::read_to_string(dir.join("CSXS").join("manifest.xml")) else {
+        return Vec::new();
+    };
+    let mut ids = Vec::new();
+    for line in content.lines() {
+        let attr = if line.contains("<ExtensionManifest") {
+            "ExtensionBundleId"
+        } else if line.contains("<Extension ") {
+            "Id"
+        } else {
+            continue;
+        };
+        if let Some(id) = extract_attr(line, attr) {
+            if !ids.contains(&id) {
+                ids.push(id);
+            }
+        }
+    }
+    ids
+}
+
+/// Folders in `extensions_dir` holding `extension_id`: its own folder in any
+/// letter case, plus copies other installers put under a different name
+pub fn find_installed_dirs(extensions_dir: &Path, extension_id: &str) -> Vec<PathBuf> {
+    let folder_name = folder_name_for(extension_id).unwrap_or_else(|_| extension_id.to_string());
+    let Ok(entries) = fs::read_dir(extensions_dir) else {
+        return Vec::new();
+    };
+    entries
+        .flatten()
+        .map(|e| e.path())
+        .filter(|dir| dir.is_dir())
+        .filter(|dir| {
+            let same_name = dir
+                .file_name()
+                .map(|n| n.to_string_lossy().eq_ignore_ascii_case(&folder_name))
+                .unwrap_or(false);
+            match read_manifest(dir) {
+                Some(info) => info.id == extension_id,
+                // A broken copy under our own name is ours to replace
+                None => same_name,
+            }
+        })
+        .collect()
+}
+
+/// Where an extension gets installed, and the existing copies that install replaces.
+/// A folder with the canonical name that holds a different extension is left alone
+/// and a numbered name is used instead.
+pub fn install_target(extensions_dir: &Path, extension_id: &str) -> Result<(PathBuf, Vec<PathBuf>), String> {
+    let folder_name = folder_name_for(extension_id)?;
+    let existing = find_installed_dirs(extensions_dir, extension_id);
+
+    let taken_by_other = |name: &str| {
+        fs::read_dir(extensions_dir)
+            .map(|entries| {
+                entries.flatten().any(|e| {
+                    e.file_name().to_string_lossy().eq_ignore_ascii_case(name) && !existing.contains(&e.path())
+                })
+            })
+            .unwrap_or(false)
+    };
+    let mut name = folder_name.clone();
+    let mut n = 2;
+    while taken_by_other(&name) {
+        name = format!("{}-{}", folder_name, n);
+        n += 1;
+    }
+    Ok((extensions_dir.join(name), existing))
+}
+
+/// Files smaller than this are exempt from the compression ratio check —
+/// small text files legitimately compress very well
+const RATIO_CHECK_MIN_BYTES: u64 = 1024 * 1024;
+
+fn megabytes(bytes: u64) -> String {
+    format!("{:.1} MB", bytes as f64 / (1024.0 * 1024.0))
+}
+
+/// Check an archive's declared sizes against the extraction limits before anything is written
+pub fn check_archive_limits<R: Read + Seek>(
+    archive: &mut ZipArchive<R>,
+    root: &str,
+    limits: &ExtractionLimits,
+) -> Result<(), String> {
+    if archive.len() as u64 > limits.max_entries {
+        return Err(format!(
+            "Archive has {} entries, more than the limit of {}",
+            archive.len(),
+            limits.max_entries
+        ));
+    }
+
+    let mut total: u64 = 0;
+    for i in 0..archive.len() {
+        let entry = archive.by_index(i).map_err(|e| format!("Cannot read ZXP entry: {}", e))?;
+        if skip_reason(entry.name(), root).is_some() {
+            continue;
+        }
+        let size = entry.size();
+        if size > limits.max_file_bytes {
+            return Err(format!(
+                "'{}' is {}, more than the per-file limit of {}",
+                entry.name(),
+                megabytes(size),
+                megabytes(limits.max_file_bytes)
+            ));
+        }
+        if size >= RATIO_CHECK_MIN_BYTES && size / entry.compressed_size().max(1) > limits.max_compression_ratio {
+            return Err(format!(
+                "'{}' expands more than {}:1 — possible zip bomb",
+                entry.name(),
+                limits.max_compression_ratio
+            ));
+        }
+        total = total.saturating_add(size);
+        if total > limits.max_total_bytes {
+            return Err(format!(
+                "Archive expands to more than the limit of {}",
+                megabytes(limits.max_total_bytes)
+            ));
+        }
+    }
+    Ok(())
+}
+
+/// Stream one entry to disk, stopping as soon as it produces more bytes than it
+/// declared or than the remaining budget allows
+pub fn copy_limited(entry: &mut zip::read::ZipFile, out: &mut fs::File, budget: u64) -> Result<u64, String> {
+    let allowed = entry.size().min(budget);
+    let name = entry.name().to_string();
+    let written = std::io::copy(&mut entry.take(allowed + 1), out)
+        .map_err(|e| format!("Cannot extract '{}': {}", name, e))?;
+    if written > allowed {
+        return Err(if allowed < budget {
+            format!("'{}' is larger than its declared size — the archive is corrupt or malicious", name)
+        } else {
+            "Archive exceeds the total size limit".to_string()
+        });
+    }
+    Ok(written)
+}
+
+/// Extract everything below `root` into `target_dir`, counting actual bytes in case
+/// the headers lie. Leaves a partial tree behind on error; callers remove it.
+pub fn extract_archive<R: Read + Seek>(
+    archive: &mut ZipArchive<R>,
+    root: &str,
+    target_dir: &Path,
+    limits: &ExtractionLimits,
+) -> Result<(), String> {
+    let mut extracted: u64 = 0;
+    let mut links: Vec<(PathBuf, String)> = Vec::new();
+    for i in 0..archive.len() {
+        let mut entry = match archive.by_index(i) {
+            Ok(e) => e,
+            Err(_) => continue,
+        };
+
+        if skip_reason(entry.name(), root).is_some() {
+            continue;
+        }
+
+        // Absolute paths and `..` would land outside the extension folder
+        let Some(rel_path) = entry
+            .enclosed_name()
+            .and_then(|p| p.strip_prefix(root).ok().map(extract::normalize_manifest_path))
+        else {
+            log::warn!("Skipping unsafe archive path '{}'", entry.name());
+            continue;
+        };
+        let out_path = target_dir.join(&rel_path);
+
+        if entry.is_symlink() {
+            // Created after everything else, see extract::create_links
+            let mut link_target = String::new();
+            if entry.by_ref().take(4096).read_to_string(&mut link_target).is_ok() {
+                links.push((rel_path, link_target));
+            }
+            continue;
+        }
+
+        // Any file that cannot be written aborts the install — a panel with
+        // missing files would only fail later inside the host app
+        let create_failed = |e: std::io::Error| format!("Cannot extract '{}': {}", entry.name(), e);
+        if entry.is_dir() {
+            fs::create_dir_all(&out_path).map_err(create_failed)?;
+        } else {
+            if let Some(parent) = out_path.parent() {
+                fs::create_dir_all(parent).map_err(create_failed)?;
+            }
+            let mut out_file = fs::File::create(&out_path).map_err(create_failed)?;
+            let budget = limits.max_total_bytes.saturating_sub(extracted);
+            extracted += copy_limited(&mut entry, &mut out_file, budget)?;
+            extract::apply_metadata(&out_file, &entry);
+        }
+    }
+
+    extract::create_links(target_dir, &links);
+    extract::clear_quarantine(target_dir);
+    Ok(())
+}
+
+/// A fresh folder in the app's temp folder to extract into before an install
+/// replaces what is there; deleted when dropped unless it was moved away
+pub struct StagingDir {
+    pub path: PathBuf,
+}
+
+impl StagingDir {
+    pub fn new() -> Result<StagingDir, String> {
+        let stamp = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .map(|d| d.as_nanos())
+            .unwrap_or(0);
+        let path = get_data_folder()
+            .join("tmp")
+            .join(format!("staging-{}-{}", std::process::id(), stamp));
+        fs::create_dir_all(&path).map_err(|e| format!("Cannot create staging folder: {}", e))?;
+        Ok(StagingDir { path })
+    }
+
+    /// Move the staged files to `target`, which must not exist
+    pub fn commit(self, target: &Path) -> Result<(), String> {
+        match trash::move_dir_direct(&self.path, target) {
+            Ok(()) => Ok(()),
+            // Everything reached the target; only the staging leftovers remain
+            Err(e) if trash::is_kept_copy(&e) => {
+                log::warn!("{}", e);
+                Ok(())
+            }
+            Err(e) => Err(format!("Cannot move files into {}: {}", target.display(), e)),
+        }
+    }
+}
+
+impl Drop for StagingDir {
+    fn drop(&mut self) {
+        let _ = fs::remove_dir_all(&self.path);
+    }
+}
+
+/// True when an installed extension lives outside the user extensions folder
+pub fn is_system_install(ext: &ExtensionInfo) -> bool {
+    match &ext.install_path {
+        Some(path) => !Path::new(path).starts_with(get_extensions_folder()),
+        None => false,
+    }
+}
+
+/// Install a ZXP file into the user extensions folder
 pub fn install_extension(path: &str) -> InstallResult {
-    // First, read extension info
-    let info = match get_extension_info_from_zxp(path) {
+    finish_install(path, install_into(path, &get_extensions_folder()))
+}
+
+/// Install a ZXP file for all users, asking for administrator rights when the
+/// system extensions folder is not writable
+pub fn install_extension_system(path: &str) -> InstallResult {
+    let Some(extensions_dir) = get_system_extensions_folder() else {
+        return InstallResult {
+            success: false,
+            message: "System-wide installs are not supported on this platform".to_string(),
+            extension: None,
+            payload: Vec::new(),
+        };
+    };
+    let result = if is_writable(&extensions_dir) {
+        install_into(path, &extensions_dir)
+    } else {
+        elevate::run_elevated(&PrivilegedOp::Install {
+            package: path.to_string(),
+        })
+    };
+    finish_install(path, result)
+}
+
+/// Whether files can be created in `dir` (creating it if needed) without elevation
+fn is_writable(dir: &Path) -> bool {
+    let probe = dir.join(".zxp-magic-write-test");
+    let writable = fs::create_dir_all(dir).is_ok() && fs::File::create(&probe).is_ok();
+    let _ = fs::remove_file(&probe);
+    writable
+}
+
+/// Record integrity data and add install details once an install succeeded.
+/// Kept out of `install_into` so an elevated install still records as the user.
+fn finish_install(path: &str, mut result: InstallResult) -> InstallResult {
+    if !result.success {
+        return result;
+    }
+    if let Some(info) = result.extension.as_mut() {
+        if let Some(target_dir) = info.install_path.clone().map(PathBuf::from) {
+            // Keep a hash manifest and the original package for verify/repair
+            if let Err(e) = integrity::record_install(&info.id, &info.version, &target_dir, Path::new(path)) {
+                log::warn!("Cannot record install of '{}': {}", info.id, e);
+            }
+            fill_install_details(info, &target_dir);
+
+            // A half-installed tool suite fails in confusing ways — say so up front
+            let missing = dependencies::missing_companions(&target_dir, info);
+            if !missing.is_empty() {
+                result.message = format!("{} Missing companions: {}", result.message, missing.join(", "));
+            }
+        }
+    }
+    result
+}
+
+/// Install a ZXP file into `extensions_dir`, replacing any installed copy there.
+/// Hybrid packages (with an `.mxi`) also get their host app files placed.
+pub fn install_into(path: &str, extensions_dir: &Path) -> InstallResult {
+    if hybrid::read_mxi(path).is_some() {
+        hybrid::install(path, extensions_dir)
+    } else {
+        install_panel(path, extensions_dir)
+    }
+}
+
+/// Extract the CEP panel of a ZXP file into `extensions_dir`
+pub fn install_panel(path: &str, extensions_dir: &Path) -> InstallResult {
+    // First, read extension info; the panel must be at the top of this package
+    let info = match zxp_info(path, None, false) {
         Ok(i) => i,
         Err(e) => {
             return InstallResult {
                 success: false,
                 message: format!("Invalid ZXP file: {}", e),
                 extension: None,
+                payload: Vec::new(),
+            };
+        }
+    };
+
+    let file = match fs::File::open(path) {
+        Ok(f) => f,
+        Err(e) => {
+            return InstallResult {
+                success: false,
+                message: format!("Cannot open ZXP file: {}", e),
+                extension: None,
+                payload: Vec::new(),
             };
         }
     };
 
-    let extensions_dir = get_extensions_folder();
-    let target_dir = extensions_dir.join(&info.id);
+    let mut archive = match ZipArchive::new(file) {
+        Ok(a) => a,
+        Err(e) => {
+            return InstallResult {
+                success: false,
+                message: format!("Cannot read ZXP archive: {}", e),
+                extension: None,
+                payload: Vec::new(),
+            };
+        }
+    };
 
-    // Remove existing installation if present
-    if target_dir.exists() {
-        if let Err(e) = fs::remove_dir_all(&target_dir) {
+    // Extract straight from the folder holding CSXS/, dropping any wrapper folders
+    let root = match extract::find_extension_root(&archive) {
+        Some((_, root)) => root,
+        None => {
             return InstallResult {
                 success: false,
-                message: format!("Cannot remove existing installation: {}", e),
+                message: "Invalid ZXP file: CSXS/manifest.xml not found".to_string(),
                 extension: None,
+                payload: Vec::new(),
             };
         }
-    }
+    };
 
-    // Create target directory
-    if let Err(e) = fs::create_dir_all(&target_dir) {
+    // Declared sizes are checked before the existing installation is touched
+    let limits = settings::load_settings().extraction_limits;
+    if let Err(e) = check_archive_limits(&mut archive, &root, &limits) {
         return InstallResult {
             success: false,
-            message: format!("Cannot create extension directory: {}", e),
+            message: format!("Refusing to install: {}", e),
             extension: None,
+            payload: Vec::new(),
         };
     }
 
-    // Extract ZXP contents
-    let file = match fs::File::open(path) {
-        Ok(f) => f,
+    let (target_dir, existing) = match install_target(extensions_dir, &info.id) {
+        Ok(t) => t,
         Err(e) => {
             return InstallResult {
                 success: false,
-                message: format!("Cannot open ZXP file: {}", e),
+                message: format!("Invalid extension id: {}", e),
                 extension: None,
+                payload: Vec::new(),
             };
         }
     };
 
-    let mut archive = match ZipArchive::new(file) {
-        Ok(a) => a,
+    // Extract into a staging folder first, so a package the streaming limits
+    // abort leaves the installed copy alone
+    let staging = match StagingDir::new() {
+        Ok(staging) => staging,
         Err(e) => {
             return InstallResult {
                 success: false,
-                message: format!("Cannot read ZXP archive: {}", e),
+                message: e,
                 extension: None,
+                payload: Vec::new(),
             };
         }
     };
-
-    for i in 0..archive.len() {
-        let mut entry = match archive.by_index(i) {
-            Ok(e) => e,
-            Err(_) => continue,
+    if let Err(e) = extract_archive(&mut archive, &root, &staging.path, &limits) {
+        return InstallResult {
+            success: false,
+            message: format!("Extraction aborted: {}", e),
+            extension: None,
+            payload: Vec::new(),
         };
+    }
 
-        // Skip META-INF (ZXP signature files)
-        if entry.name().starts_with("META-INF") {
-            continue;
+    // Remove existing installations, including copies under other folder names
+    for dir in &existing {
+        if dir != &target_dir {
+            log::info!("Replacing '{}' installed at {}", info.id, dir.display());
         }
-
-        let out_path = target_dir.join(entry.name());
-
-        if entry.name().ends_with('/') {
-            let _ = fs::create_dir_all(&out_path);
-        } else {
-            if let Some(parent) = out_path.parent() {
-                let _ = fs::create_dir_all(parent);
-            }
-            if let Ok(mut out_file) = fs::File::create(&out_path) {
-                let _ = std::io::copy(&mut entry, &mut out_file);
-            }
+        if let Err(e) = fs::remove_dir_all(dir) {
+            return InstallResult {
+                success: false,
+                message: format!("Cannot remove existing installation: {}", e),
+                extension: None,
+                payload: Vec::new(),
+            };
         }
     }
 
-    // Unwrap single nested wrapper folder if present
-    unwrap_nested_folder(&target_dir);
+    if let Err(e) = fs::create_dir_all(extensions_dir) {
+        return InstallResult {
+            success: false,
+            message: format!("Cannot create extensions folder: {}", e),
+            extension: None,
+            payload: Vec::new(),
+        };
+    }
+    if let Err(e) = staging.commit(&target_dir) {
+        return InstallResult {
+            success: false,
+            message: e,
+            extension: None,
+            payload: Vec::new(),
+        };
+    }
 
     // Re-parse manifest from the installed location to get proper icon paths
     let manifest_path = target_dir.join("CSXS").join("manifest.xml");
     let mut installed_info = if let Ok(content) = fs::read_to_string(&manifest_path) {
-        parse_manifest_xml(&content, Some(&target_dir)).unwrap_or_else(|_| info.clone())
+        match parse_manifest_xml(&content, Some(&target_dir)) {
+            Ok(mut parsed) => {
+                locale::localize_dir(&mut parsed, &target_dir, &locale::preferred_language());
+                security::scan_dir(&mut parsed.security, &target_dir);
+                parsed
+            }
+            Err(_) => info.clone(),
+        }
     } else {
         info.clone()
     };
@@ -324,66 +999,94 @@ pub fn install_extension(path: &str) -> InstallResult {
         success: true,
         message: format!("'{}' installed successfully!", installed_info.name),
         extension: Some(installed_info),
+        payload: Vec::new(),
     }
 }
 
-/// Try to remove a directory, with elevated (UAC) fallback on Windows
-fn remove_extension_dir(target: &Path) -> Result<(), String> {
-    match fs::remove_dir_all(target) {
-        Ok(()) => Ok(()),
-        Err(e) => {
-            #[cfg(target_os = "windows")]
-            {
-                let target_str = target.to_string_lossy().to_string();
-                let ps_script = format!(
-                    "Remove-Item -LiteralPath '{}' -Recurse -Force",
-                    target_str.replace("'", "''")
-                );
-                let status = std::process::Command::new("powershell")
-                    .args([
-                        "-NoProfile",
-                        "-Command",
-                        &format!(
-                            "Start-Process powershell -Verb RunAs -Wait -ArgumentList '-NoProfile -Command \"{}\"'",
-                            ps_script.replace("\"", "`\"")
-                        ),
-                    ])
-                    .status()
-                    .map_err(|e2| format!("Cannot elevate: {}", e2))?;
-
-                if status.success() && !target.exists() {
-                    return Ok(());
-                }
-                return Err(format!("Cannot remove extension (admin rights may be required): {}", e));
-            }
-            #[cfg(not(target_os = "windows"))]
-            Err(format!("Cannot remove extension: {}", e))
-        }
+/// Check that `path` is safe to uninstall: a real folder (not a symlink) directly
+/// inside a known CEP extensions root, holding the manifest of `extension_id`
+fn validate_uninstall_target(path: &Path, extension_id: &str) -> Result<(PathBuf, ExtensionInfo), String> {
+    let meta = fs::symlink_metadata(path).map_err(|e| format!("Cannot read {}: {}", path.display(), e))?;
+    if meta.file_type().is_symlink() {
+        return Err(format!("{} is a symlink — refusing to remove it", path.display()));
+    }
+    if !meta.is_dir() {
+        return Err(format!("{} is not a folder", path.display()));
     }
+
+    // Compare resolved paths so `..` or linked parents cannot sneak outside a root
+    let real = fs::canonicalize(path).map_err(|e| format!("Cannot resolve {}: {}", path.display(), e))?;
+    let in_root = get_all_extension_folders()
+        .iter()
+        .filter_map(|root| fs::canonicalize(root).ok())
+        .any(|root| real.parent() == Some(root.as_path()));
+    if !in_root {
+        return Err(format!("{} is not inside a CEP extensions folder", path.display()));
+    }
+
+    let info = read_manifest(&real).ok_or_else(|| format!("{} has no readable CEP manifest", path.display()))?;
+    if info.id != extension_id {
+        return Err(format!(
+            "{} holds '{}', not '{}' — refusing to remove it",
+            path.display(),
+            info.id,
+            extension_id
+        ));
+    }
+    Ok((real, info))
+}
+
+/// Validate an installed folder and move it to the trash
+fn trash_installed(path: &Path, extension_id: &str) -> Result<trash::TrashItem, String> {
+    let (real, mut info) = validate_uninstall_target(path, extension_id)?;
+    locale::localize_dir(&mut info, &real, &locale::preferred_language());
+    trash::move_to_trash(&real, &info)
 }
 
-/// Remove an installed extension — prefers install_path, falls back to id-based search
+/// Remove an installed extension into the trash — prefers install_path, falls back to id-based search
 pub fn uninstall_extension(extension_id: &str, install_path: Option<&str>) -> Result<(), String> {
-    // First try the exact install_path if provided
+    let item = remove_installed(extension_id, install_path)?;
+    integrity::forget_install(extension_id);
+    // Host app files go into the same trash item, so restoring it brings them back
+    if let Err(e) = hybrid::trash_payload(extension_id, &trash::payload_stash(&item)) {
+        log::warn!("{}", e);
+    }
+    Ok(())
+}
+
+/// Trash every copy of an extension; returns the trash item of the main one
+fn remove_installed(extension_id: &str, install_path: Option<&str>) -> Result<trash::TrashItem, String> {
+    // First try the exact install_path if provided, along with stray copies next to it
     if let Some(path) = install_path {
         let target = PathBuf::from(path);
         if target.exists() {
-            return remove_extension_dir(&target);
+            let item = trash_installed(&target, extension_id)?;
+            if let Some(folder) = target.parent() {
+                for copy in find_installed_dirs(folder, extension_id) {
+                    if let Err(e) = trash_installed(&copy, extension_id) {
+                        log::warn!("Leaving stray copy of '{}': {}", extension_id, e);
+                    }
+                }
+            }
+            return Ok(item);
         }
     }
 
-    // Fall back to id-based search across all CEP folders
+    // Fall back to id-based search across all CEP folders, whatever the folder is called
     for folder in get_all_extension_folders() {
-        let target = folder.join(extension_id);
-        if target.exists() {
-            return remove_extension_dir(&target);
+        let mut items = Vec::new();
+        for target in find_installed_dirs(&folder, extension_id) {
+            items.push(trash_installed(&target, extension_id)?);
+        }
+        if let Some(item) = items.into_iter().next() {
+            return Ok(item);
         }
     }
     Err(format!("Extension '{}' not found", extension_id))
 }
 
 /// All known CSXS versions (newest first)
-const CSXS_VERSIONS: &[&str] = &["12", "11", "10", "9", "8", "7"];
+pub const CSXS_VERSIONS: &[&str] = &["12", "11", "10", "9", "8", "7"];
 
 /// Read CEP PlayerDebugMode registry/plist setting
 pub fn get_debug_mode() -> bool {
@@ -480,11 +1183,74 @@ fn parse_manifest_xml(xml: &str, base_path: Option<&Path>) -> Result<ExtensionIn
     let mut host_list: Vec<HostApp> = Vec::new();
     let mut icon_path: Option<String> = None;
     let mut required_runtime_version: Option<String> = None;
+    let mut bundle_name = String::new();
+    let mut vendor = String::new();
+    let mut copyright = String::new();
+    let mut required_runtimes: Vec<RuntimeRequirement> = Vec::new();
+    let mut cef_parameters: Vec<String> = Vec::new();
+    let mut locales: Vec<String> = Vec::new();
+    let mut main_paths: Vec<String> = Vec::new();
+    let mut script_paths: Vec<String> = Vec::new();
+    let mut dependencies: Vec<ManifestDependency> = Vec::new();
+    let mut in_cef_command_line = false;
 
     // Extract with regex-like string operations
     for line in xml.lines() {
         let line = line.trim();
 
+        if line.contains("<CEFCommandLine") {
+            in_cef_command_line = !line.contains("/>");
+        }
+        if in_cef_command_line && line.contains("<Parameter>") {
+            let param = extract_text(line, "Parameter");
+            if !param.is_empty() && !cef_parameters.contains(&param) {
+                cef_parameters.push(param);
+            }
+        }
+        if line.contains("</CEFCommandLine>") {
+            in_cef_command_line = false;
+        }
+
+        if line.contains("<Locale ") {
+            if let Some(code) = extract_attr(line, "Code") {
+                if !locales.contains(&code) {
+                    locales.push(code);
+                }
+            }
+        }
+
+        if line.contains("<Dependency ") {
+            if let Some(dep_id) = extract_attr(line, "Id") {
+                if !dependencies.iter().any(|d| d.id == dep_id) {
+                    dependencies.push(ManifestDependency {
+                        id: dep_id,
+                        version: extract_attr(line, "Version"),
+                    });
+                }
+            }
+        }
+
+        if line.contains("<MainPath>") {
+            let main_path = extract_text(line, "MainPath");
+            if !main_path.is_empty() && !main_paths.contains(&main_path) {
+                main_paths.push(main_path);
+            }
+        }
+        if line.contains("<ScriptPath>") {
+            let script_path = extract_text(line, "ScriptPath");
+            if !script_path.is_empty() && !script_paths.contains(&script_path) {
+                script_paths.push(script_path);
+            }
+        }
+
+        if line.contains("<Vendor>") && vendor.is_empty() {
+            vendor = extract_text(line, "Vendor");
+        }
+
+        if line.contains("<Copyright>") && copyright.is_empty() {
+            copyright = extract_text(line, "Copyright");
+        }
+
         if line.contains("<Extension ") || line.starts_with("<Extension ") {
             if let Some(v) = extract_attr(line, "Id") {
                 if id.is_empty() {
@@ -500,6 +1266,9 @@ fn parse_manifest_xml(xml: &str, base_path: Option<&Path>) -> Result<ExtensionIn
 
         if line.contains("<ExtensionManifest") || line.contains("<ExtensionBundle") {
             if let Some(v) = extract_attr(line, "ExtensionBundleName") {
+                if bundle_name.is_empty() {
+                    bundle_name = v.clone();
+                }
                 if name.is_empty() {
                     name = v;
                 }
@@ -565,10 +1334,17 @@ fn parse_manifest_xml(xml: &str, base_path: Option<&Path>) -> Result<ExtensionIn
             author = extract_text(line, "Author");
         }
 
-        // Parse RequiredRuntime CSXS version
-        if line.contains("RequiredRuntime") {
+        // Parse RequiredRuntime entries (CSXS version doubles as CEP version fallback)
+        if line.contains("<RequiredRuntime ") {
             if let Some(v) = extract_attr(line, "Version") {
-                required_runtime_version = Some(v);
+                let runtime = extract_attr(line, "Name").unwrap_or_else(|| "CSXS".to_string());
+                if runtime.eq_ignore_ascii_case("CSXS") {
+                    required_runtime_version = Some(v.clone());
+                }
+                required_runtimes.push(RuntimeRequirement {
+                    name: runtime,
+                    version: v,
+                });
             }
         }
 
@@ -605,39 +1381,7 @@ fn parse_manifest_xml(xml: &str, base_path: Option<&Path>) -> Result<ExtensionIn
 
     // Fallback: derive a readable name from the extension ID
     if name.is_empty() && !id.is_empty() {
-        let generic = ["main", "panel", "extension", "index", "ui", "app", "core", "popup", "host"];
-        let parts: Vec<&str> = id.split('.').collect();
-
-        // Find the last non-generic, non-TLD segment
-        // e.g. "com.example.myCoolExt.main" → "myCoolExt"
-        let raw = parts
-            .iter()
-            .rev()
-            .find(|p| {
-                let lower = p.to_lowercase();
-                !generic.contains(&lower.as_str())
-                    && lower != "com"
-                    && lower != "net"
-                    && lower != "org"
-                    && lower != "io"
-                    && p.len() > 2
-            })
-            .copied()
-            .unwrap_or(parts.last().copied().unwrap_or(&id));
-
-        name = raw.replace('-', " ").replace('_', " ");
-        // Title case
-        name = name
-            .split_whitespace()
-            .map(|w| {
-                let mut c = w.chars();
-                match c.next() {
-                    None => String::new(),
-                    Some(f) => f.to_uppercase().collect::<String>() + c.as_str(),
-                }
-            })
-            .collect::<Vec<_>>()
-            .join(" ");
+        name = name_from_id(&id);
     }
 
     if id.is_empty() {
@@ -665,10 +1409,57 @@ fn parse_manifest_xml(xml: &str, base_path: Option<&Path>) -> Result<ExtensionIn
         cep_version,
         install_path: None,
         icon_path,
+        bundle_name,
+        vendor,
+        copyright,
+        required_runtimes,
+        node_enabled: cef_parameters.iter().any(|p| p == "--enable-nodejs"),
+        mixed_context: cef_parameters.iter().any(|p| p == "--mixed-context"),
+        security: SecurityReport::from_manifest(&cef_parameters, &main_paths, &script_paths),
+        cef_parameters,
+        locales,
+        dependencies,
+        ..Default::default()
     })
 }
 
-fn extract_attr(line: &str, attr: &str) -> Option<String> {
+/// Derive a readable name from an extension ID, e.g. "com.example.myCoolExt.main" → "MyCoolExt"
+pub fn name_from_id(id: &str) -> String {
+    let generic = ["main", "panel", "extension", "index", "ui", "app", "core", "popup", "host"];
+    let parts: Vec<&str> = id.split('.').collect();
+
+    // Find the last non-generic, non-TLD segment
+    // e.g. "com.example.myCoolExt.main" → "myCoolExt"
+    let raw = parts
+        .iter()
+        .rev()
+        .find(|p| {
+            let lower = p.to_lowercase();
+            !generic.contains(&lower.as_str())
+                && lower != "com"
+                && lower != "net"
+                && lower != "org"
+                && lower != "io"
+                && p.len() > 2
+        })
+        .copied()
+        .unwrap_or(parts.last().copied().unwrap_or(id));
+
+    // Title case
+    raw.replace(['-', '_'], " ")
+        .split_whitespace()
+        .map(|w| {
+            let mut c = w.chars();
+            match c.next() {
+                None => String::new(),
+                Some(f) => f.to_uppercase().collect::<String>() + c.as_str(),
+            }
+        })
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+pub fn extract_attr(line: &str, attr: &str) -> Option<String> {
     let patterns = [
         format!("{}=\"", attr),
         format!("{}='", attr),
@@ -688,7 +1479,7 @@ fn extract_attr(line: &str, attr: &str) -> Option<String> {
     None
 }
 
-fn extract_text(line: &str, tag: &str) -> String {
+pub fn extract_text(line: &str, tag: &str) -> String {
     let open = format!("<{}>", tag);
     let close = format!("</{}>", tag);
     if let (Some(s), Some(e)) = (line.find(&open), line.find(&close)) {
@@ -700,18 +1491,237 @@ fn extract_text(line: &str, tag: &str) -> String {
     String::new()
 }
 
-fn format_host_name(raw: &str) -> String {
+pub fn format_host_name(raw: &str) -> String {
     match raw.to_uppercase().as_str() {
         "PPRO" | "PREMIERE" => "Premiere Pro".to_string(),
         "AEFT" | "AFTEREFFECTS" => "After Effects".to_string(),
         "ILST" | "ILLUSTRATOR" => "Illustrator".to_string(),
-        "PHSP" | "PHOTOSHOP" => "Photoshop".to_string(),
+        "PHSP" | "PHXS" | "PHOTOSHOP" => "Photoshop".to_string(),
         "AUDE" | "AUDITION" => "Audition".to_string(),
         "DRWV" | "DREAMWEAVER" => "Dreamweaver".to_string(),
         "FLPR" | "ANIMATE" => "Animate".to_string(),
         "IDSN" | "INDESIGN" => "InDesign".to_string(),
         "RUSH" | "PREMIERERUSH" => "Premiere Rush".to_string(),
         "CHAR" | "CHARACTER" => "Character Animator".to_string(),
+        "KBRG" | "BRIDGE" => "Bridge".to_string(),
         _ => raw.to_string(),
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::{manifest, write_zip, Sandbox};
+
+    #[test]
+    fn unwritable_entry_aborts_the_install() {
+        let sandbox = Sandbox::new();
+        let zxp = sandbox.path("in/clash.zxp");
+        let manifest = manifest("com.test.clash", "1.0.0");
+        // "a" is a file, so "a/b.txt" cannot be created below it
+        write_zip(&zxp, &[("CSXS/manifest.xml", &manifest), ("a", "file"), ("a/b.txt", "nested")]);
+
+        let result = install_extension(&zxp.to_string_lossy());
+        assert!(!result.success);
+        assert!(result.message.contains("a/b.txt"), "{}", result.message);
+        assert!(fs::read_dir(get_extensions_folder()).map(|d| d.count() == 0).unwrap_or(true));
+        assert!(fs::read_dir(get_data_folder().join("tmp")).map(|d| d.count() == 0).unwrap_or(true));
+    }
+
+    #[test]
+    fn manifest_is_installed_under_its_canonical_name() {
+        let sandbox = Sandbox::new();
+        let zxp = sandbox.path("in/lowercase.zxp");
+        let manifest = manifest("com.test.lower", "1.0.0");
+        write_zip(&zxp, &[("Panel/csxs/", ""), ("Panel/csxs/Manifest.XML", &manifest), ("Panel/index.html", "")]);
+
+        let result = install_extension(&zxp.to_string_lossy());
+        assert!(result.success, "{}", result.message);
+        let dir = get_extensions_folder().join("com.test.lower");
+        assert!(dir.join("CSXS").join("manifest.xml").is_file());
+        assert_eq!(read_manifest(&dir).unwrap().id, "com.test.lower");
+    }
+
+    fn rules() -> FilterRules {
+        FilterRules {
+            builtin_id_prefixes: vec!["com.adobe.".to_string()],
+            builtin_system_only: true,
+            hidden_folders: vec!["/opt/hidden/extensions".to_string()],
+            hidden_vendors: vec!["Acme".to_string()],
+            always_show_ids: vec!["COM.ADOBE.KEEP".to_string()],
+        }
+    }
+
+    fn filtered(root: &Path, id: &str, author: &str) -> (bool, ExtensionInfo) {
+        let mut info = ExtensionInfo {
+            id: id.to_string(),
+            author: author.to_string(),
+            ..Default::default()
+        };
+        (apply_filter_rules(root, &mut info, &rules()), info)
+    }
+
+    #[test]
+    fn filter_rules_mark_builtin_and_hidden_extensions() {
+        let _sandbox = Sandbox::new();
+        let system = Path::new("/opt/system/extensions");
+
+        let (hidden, info) = filtered(system, "com.Adobe.ccx.start", "");
+        assert!(hidden && info.is_builtin);
+        assert_eq!(info.hidden_reason.as_deref(), Some("Built-in (com.adobe.*)"));
+        // Prefix matches only count in system folders when so configured
+        let (hidden, info) = filtered(&get_extensions_folder(), "com.adobe.ccx.start", "");
+        assert!(!hidden && !info.is_builtin);
+
+        let (hidden, info) = filtered(system, "com.adobe.keep", "");
+        assert!(!hidden && info.is_builtin);
+        let (hidden, info) = filtered(Path::new("/opt/hidden/extensions"), "com.studio.panel", "");
+        assert!(hidden);
+        assert_eq!(info.hidden_reason.as_deref(), Some("Hidden folder: /opt/hidden/extensions"));
+        let (hidden, _) = filtered(system, "com.studio.panel", "acme");
+        assert!(hidden);
+        let (hidden, info) = filtered(system, "com.studio.panel", "");
+        assert!(!hidden && info.hidden_reason.is_none());
+    }
+
+    #[test]
+    fn listing_applies_the_saved_rules() {
+        let sandbox = Sandbox::new();
+        for id in ["com.test.shown", "com.vendor.bundled"] {
+            let zxp = sandbox.path(&format!("in/{}.zxp", id));
+            crate::test_support::write_zxp(&zxp, id, "1.0.0", &[]);
+            assert!(install_extension(&zxp.to_string_lossy()).success);
+        }
+        let mut settings = settings::load_settings();
+        settings.filter_rules.builtin_id_prefixes = vec!["com.vendor.".to_string()];
+        settings.filter_rules.builtin_system_only = false;
+        settings::save_settings(&settings).unwrap();
+
+        let visible: Vec<String> = list_extensions_filtered(false, None).into_iter().map(|e| e.id).collect();
+        assert_eq!(visible, vec!["com.test.shown".to_string()]);
+        let all = list_extensions_filtered(true, None);
+        assert_eq!(all.len(), 2);
+        assert!(all.iter().any(|e| e.id == "com.vendor.bundled" && e.is_builtin));
+    }
+
+    const RICH_MANIFEST: &str = r#"<?xml version="1.0" encoding="UTF-8"?>
+<ExtensionManifest Version="7.0" ExtensionBundleId="com.studio.rich" ExtensionBundleVersion="2.0.0" ExtensionBundleName="Rich Bundle">
+  <Author>Studio</Author>
+  <Vendor>Studio GmbH</Vendor>
+  <Copyright>(c) 2026 Studio</Copyright>
+  <ExtensionList>
+    <Extension Id="com.studio.rich.panel" Version="2.0.0" />
+  </ExtensionList>
+  <ExecutionEnvironment>
+    <HostList>
+      <Host Name="PHXS" Version="[22.0,99.9]" />
+    </HostList>
+    <LocaleList>
+      <Locale Code="All" />
+    </LocaleList>
+    <RequiredRuntimeList>
+      <RequiredRuntime Name="CSXS" Version="11.0" />
+    </RequiredRuntimeList>
+  </ExecutionEnvironment>
+  <DispatchInfoList>
+    <Extension Id="com.studio.rich.panel">
+      <DispatchInfo>
+        <Resources>
+          <MainPath>./index.html</MainPath>
+          <CEFCommandLine>
+            <Parameter>--enable-nodejs</Parameter>
+            <Parameter>--mixed-context</Parameter>
+          </CEFCommandLine>
+        </Resources>
+      </DispatchInfo>
+    </Extension>
+  </DispatchInfoList>
+</ExtensionManifest>
+"#;
+
+    #[test]
+    fn manifest_metadata_is_parsed() {
+        let info = parse_manifest_xml(RICH_MANIFEST, None).unwrap();
+        assert_eq!(info.bundle_name, "Rich Bundle");
+        assert_eq!(info.vendor, "Studio GmbH");
+        assert_eq!(info.copyright, "(c) 2026 Studio");
+        assert_eq!(info.locales, vec!["All".to_string()]);
+        assert_eq!(
+            info.required_runtimes,
+            vec![RuntimeRequirement {
+                name: "CSXS".to_string(),
+                version: "11.0".to_string()
+            }]
+        );
+        assert_eq!(info.cef_parameters, vec!["--enable-nodejs".to_string(), "--mixed-context".to_string()]);
+        assert!(info.node_enabled && info.mixed_context);
+    }
+
+    #[test]
+    fn installed_extensions_carry_install_details() {
+        let sandbox = Sandbox::new();
+        let zxp = sandbox.path("in/details.zxp");
+        crate::test_support::write_zxp(&zxp, "com.test.details", "1.0.0", &[("index.html", "12345")]);
+        let result = install_extension(&zxp.to_string_lossy());
+        let info = result.extension.unwrap();
+        assert_eq!(info.file_count, 2);
+        let manifest_len = fs::metadata(get_extensions_folder().join("com.test.details/CSXS/manifest.xml")).unwrap().len();
+        assert_eq!(info.size_bytes, manifest_len + 5);
+        assert!(info.installed_at.is_some());
+        assert_eq!(info.package_sha256, Some(integrity::sha256_file(&zxp).unwrap()));
+    }
+
+    #[test]
+    fn mxi_fills_in_missing_vendor_details() {
+        let _sandbox = Sandbox::new();
+        let dir = get_extensions_folder().join("com.test.mxi");
+        fs::create_dir_all(dir.join("CSXS")).unwrap();
+        fs::write(dir.join("CSXS/manifest.xml"), manifest("com.test.mxi", "1.0.0")).unwrap();
+        fs::write(
+            dir.join("Tool.mxi"),
+            "<macromedia-extension>\n  <author name=\"Legacy Vendor\" />\n  <copyright>(c) Legacy</copyright>\n</macromedia-extension>\n",
+        )
+        .unwrap();
+
+        let info = read_extension_dir(&dir, "en_US").unwrap();
+        assert_eq!(info.vendor, "Legacy Vendor");
+        assert_eq!(info.copyright, "(c) Legacy");
+        // Not installed by us, so there is no package hash
+        assert_eq!(info.package_sha256, None);
+    }
+
+    #[test]
+    fn folder_names_are_valid_everywhere() {
+        assert_eq!(folder_name_for("com.vendor.panel").unwrap(), "com.vendor.panel");
+        assert_eq!(folder_name_for("com/vendor:panel?").unwrap(), "com_vendor_panel_");
+        assert_eq!(folder_name_for(" com.vendor.panel.. ").unwrap(), "com.vendor.panel");
+        assert_eq!(folder_name_for("con.vendor").unwrap(), "_con.vendor");
+        assert_eq!(folder_name_for(&"a".repeat(300)).unwrap().len(), MAX_FOLDER_NAME);
+        assert!(folder_name_for("..").is_err());
+        assert!(folder_name_for("/").is_err());
+    }
+
+    #[test]
+    fn install_replaces_copies_under_other_names() {
+        let sandbox = Sandbox::new();
+        let root = get_extensions_folder();
+        for folder in ["Vendor Panel", "COM.TEST.RENAMED"] {
+            fs::create_dir_all(root.join(folder).join("CSXS")).unwrap();
+            fs::write(root.join(folder).join("CSXS/manifest.xml"), manifest("com.test.renamed", "0.9.0")).unwrap();
+        }
+        // A folder with the canonical name that holds another extension stays
+        fs::create_dir_all(root.join("com.test.taken/CSXS")).unwrap();
+        fs::write(root.join("com.test.taken/CSXS/manifest.xml"), manifest("com.test.other", "1.0.0")).unwrap();
+
+        let zxp = sandbox.path("in/renamed.zxp");
+        crate::test_support::write_zxp(&zxp, "com.test.renamed", "1.0.0", &[]);
+        assert!(install_extension(&zxp.to_string_lossy()).success);
+        let copies = find_installed_dirs(&root, "com.test.renamed");
+        assert_eq!(copies.len(), 1);
+        assert_eq!(read_manifest(&copies[0]).unwrap().version, "1.0.0");
+
+        let (target, existing) = install_target(&root, "com.test.taken").unwrap();
+        assert_eq!(target, root.join("com.test.taken-2"));
+        assert!(existing.is_empty());
+    }
+}
@@ -0,0 +1,404 @@
+use crate::installer::{self, InstallResult};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::collections::BTreeMap;
+use std::fs;
+use std::io::Read;
+use std::path::{Path, PathBuf};
+use walkdir::WalkDir;
+
+/// Hash and size of a single installed file
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FileRecord {
+    pub sha256: String,
+    pub size: u64,
+}
+
+/// Snapshot of an extension's files taken right after installation
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct InstallManifest {
+    pub extension_id: String,
+    pub version: String,
+    pub install_path: String,
+    pub package_sha256: String,
+    /// Seconds since the Unix epoch
+    #[serde(default)]
+    pub installed_at: Option<u64>,
+    /// Relative path (forward slashes) → file record
+    pub files: BTreeMap<String, FileRecord>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct IntegrityReport {
+    pub extension_id: String,
+    pub install_path: String,
+    pub intact: bool,
+    pub missing: Vec<String>,
+    pub modified: Vec<String>,
+    pub extra: Vec<String>,
+    /// True when the original package is still in the cache
+    pub can_repair: bool,
+}
+
+fn manifests_folder() -> PathBuf {
+    installer::get_data_folder().join("manifests")
+}
+
+fn packages_folder() -> PathBuf {
+    installer::get_data_folder().join("packages")
+}
+
+fn manifest_path(extension_id: &str) -> Result<PathBuf, String> {
+    Ok(manifests_folder().join(format!("{}.json", installer::folder_name_for(extension_id)?)))
+}
+
+/// Path of the cached original package for an extension
+pub fn cached_package_path(extension_id: &str) -> Result<PathBuf, String> {
+    Ok(packages_folder().join(format!("{}.zxp", installer::folder_name_for(extension_id)?)))
+}
+
+/// SHA-256 of a file as lowercase hex
+pub fn sha256_file(path: &Path) -> Result<String, String> {
+    let mut file = fs::File::open(path).map_err(|e| format!("Cannot open {}: {}", path.display(), e))?;
+    let mut hasher = Sha256::new();
+    let mut buf = [0u8; 64 * 1024];
+    loop {
+        let n = file.read(&mut buf).map_err(|e| e.to_string())?;
+        if n == 0 {
+            break;
+        }
+        hasher.update(&buf[..n]);
+    }
+    Ok(hex::encode(hasher.finalize()))
+}
+
+/// Hash every file below `dir`, keyed by forward-slash relative path
+fn snapshot_files(dir: &Path) -> Result<BTreeMap<String, FileRecord>, String> {
+    let mut files = BTreeMap::new();
+    for entry in WalkDir::new(dir).into_iter().flatten() {
+        if !entry.file_type().is_file() {
+            continue;
+        }
+        let rel = relative_key(dir, entry.path());
+        let size = entry.metadata().map(|m| m.len()).unwrap_or(0);
+        let sha256 = sha256_file(entry.path())?;
+        files.insert(rel, FileRecord { sha256, size });
+    }
+    Ok(files)
+}
+
+fn relative_key(root: &Path, path: &Path) -> String {
+    path.strip_prefix(root)
+        .unwrap_or(path)
+        .components()
+        .map(|c| c.as_os_str().to_string_lossy().to_string())
+        .collect::<Vec<_>>()
+        .join("/")
+}
+
+/// Read the stored install manifest for an extension
+pub fn load_manifest(extension_id: &str) -> Option<InstallManifest> {
+    let content = fs::read_to_string(manifest_path(extension_id).ok()?).ok()?;
+    serde_json::from_str(&content).ok()
+}
+
+/// Cache the source package and write a hash manifest of the installed files
+pub fn record_install(
+    extension_id: &str,
+    version: &str,
+    install_dir: &Path,
+    package_path: &Path,
+) -> Result<(), String> {
+    fs::create_dir_all(manifests_folder()).map_err(|e| format!("Cannot create manifest folder: {}", e))?;
+    fs::create_dir_all(packages_folder()).map_err(|e| format!("Cannot create package cache: {}", e))?;
+
+    // Repairs install from the cache itself — don't copy a file onto itself
+    let cached = cached_package_path(extension_id)?;
+    if fs::canonicalize(package_path).ok() != fs::canonicalize(&cached).ok() {
+        fs::copy(package_path, &cached).map_err(|e| format!("Cannot cache package: {}", e))?;
+    }
+
+    let manifest = InstallManifest {
+        extension_id: extension_id.to_string(),
+        version: version.to_string(),
+        install_path: install_dir.to_string_lossy().to_string(),
+        package_sha256: sha256_file(&cached)?,
+        installed_at: std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .map(|d| d.as_secs())
+            .ok(),
+        files: snapshot_files(install_dir)?,
+    };
+    let json = serde_json::to_string_pretty(&manifest).map_err(|e| e.to_string())?;
+    fs::write(manifest_path(extension_id)?, json).map_err(|e| format!("Cannot write install manifest: {}", e))
+}
+
+/// Drop the install manifest and cached package of an uninstalled extension
+pub fn forget_install(extension_id: &str) {
+    for path in [manifest_path(extension_id), cached_package_path(extension_id)].into_iter().flatten() {
+        let _ = fs::remove_file(path);
+    }
+}
+
+/// File names of the install record and cached package inside a stash folder
+const STASHED_MANIFEST: &str = "install-manifest.json";
+const STASHED_PACKAGE: &str = "package.zxp";
+
+/// Move the install record and cached package of the copy at `install_dir`
+/// into `stash` (a trash item), so a restore can bring them back
+pub fn stash_install(extension_id: &str, install_dir: &Path, stash: &Path) -> Result<(), String> {
+    let Some(manifest) = load_manifest(extension_id) else {
+        return Ok(());
+    };
+    let recorded = fs::canonicalize(&manifest.install_path).ok();
+    if recorded.is_none() || recorded != fs::canonicalize(install_dir).ok() {
+        // The record belongs to another copy
+        return Ok(());
+    }
+    fs::create_dir_all(stash).map_err(|e| format!("Cannot create {}: {}", stash.display(), e))?;
+    for (from, name) in [
+        (manifest_path(extension_id)?, STASHED_MANIFEST),
+        (cached_package_path(extension_id)?, STASHED_PACKAGE),
+    ] {
+        if from.exists() {
+            fs::rename(&from, stash.join(name)).map_err(|e| format!("Cannot keep {}: {}", from.display(), e))?;
+        }
+    }
+    Ok(())
+}
+
+/// Put back what `stash_install` kept, unless the extension was recorded again since
+pub fn unstash_install(extension_id: &str, stash: &Path) -> Result<(), String> {
+    if !stash.join(STASHED_MANIFEST).is_file() || load_manifest(extension_id).is_some() {
+        return Ok(());
+    }
+    fs::create_dir_all(manifests_folder()).map_err(|e| format!("Cannot create manifest folder: {}", e))?;
+    fs::create_dir_all(packages_folder()).map_err(|e| format!("Cannot create package cache: {}", e))?;
+    for (name, to) in [
+        (STASHED_PACKAGE, cached_package_path(extension_id)?),
+        (STASHED_MANIFEST, manifest_path(extension_id)?),
+    ] {
+        let from = stash.join(name);
+        if from.exists() {
+            fs::rename(&from, &to).map_err(|e| format!("Cannot restore {}: {}", to.display(), e))?;
+        }
+    }
+    Ok(())
+}
+
+/// Compare an installed extension against the manifest recorded at install time
+pub fn verify_extension(extension_id: &str) -> Result<IntegrityReport, String> {
+    let manifest = load_manifest(extension_id).ok_or_else(|| {
+        format!("No install record for '{}' — it was not installed by ZXP Magic", extension_id)
+    })?;
+
+    let install_dir = PathBuf::from(&manifest.install_path);
+    if !install_dir.exists() {
+        return Err(format!("Extension folder no longer exists: {}", manifest.install_path));
+    }
+
+    let mut missing = Vec::new();
+    let mut modified = Vec::new();
+    for (rel, record) in &manifest.files {
+        let path = install_dir.join(rel);
+        if !path.is_file() {
+            missing.push(rel.clone());
+            continue;
+        }
+        // Size check first — skips hashing files that obviously changed
+        let size = fs::metadata(&path).map(|m| m.len()).unwrap_or(0);
+        if size != record.size || sha256_file(&path)? != record.sha256 {
+            modified.push(rel.clone());
+        }
+    }
+
+    let extra: Vec<String> = WalkDir::new(&install_dir)
+        .into_iter()
+        .flatten()
+        .filter(|e| e.file_type().is_file())
+        .map(|e| relative_key(&install_dir, e.path()))
+        // A `.debug` file is added on purpose for remote debugging
+        .filter(|rel| !manifest.files.contains_key(rel) && rel != ".debug")
+        .collect();
+
+    Ok(IntegrityReport {
+        extension_id: manifest.extension_id,
+        install_path: manifest.install_path,
+        intact: missing.is_empty() && modified.is_empty() && extra.is_empty(),
+        missing,
+        modified,
+        extra,
+        can_repair: cached_package_path(extension_id).map(|p| p.exists()).unwrap_or(false),
+    })
+}
+
+/// Restore an extension by reinstalling it from the cached original package
+pub fn repair_extension(extension_id: &str) -> InstallResult {
+    let cached = match cached_package_path(extension_id) {
+        Ok(cached) if cached.exists() => cached,
+        _ => {
+            return InstallResult {
+                success: false,
+                message: format!("No cached package for '{}' — reinstall it from the original ZXP", extension_id),
+                extension: None,
+                payload: Vec::new(),
+            };
+        }
+    };
+
+    if let Some(manifest) = load_manifest(extension_id) {
+        match sha256_file(&cached) {
+            Ok(hash) if hash == manifest.package_sha256 => {}
+            _ => {
+                return InstallResult {
+                    success: false,
+                    message: "Cached package is corrupt — reinstall it from the original ZXP".to_string(),
+                    extension: None,
+                    payload: Vec::new(),
+                };
+            }
+        }
+    }
+
+    let mut result = installer::install_extension(&cached.to_string_lossy());
+    if result.success {
+        if let Some(ext) = &result.extension {
+            result.message = format!("'{}' repaired successfully!", ext.name);
+        }
+    }
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::{write_zxp, Sandbox};
+
+    const ID: &str = "com.test.integrity";
+
+    fn install(sandbox: &Sandbox) -> PathBuf {
+        let zxp = sandbox.path("in/panel.zxp");
+        write_zxp(&zxp, ID, "1.0.0", &[("index.html", "<html></html>"), ("js/main.js", "main();")]);
+        let result = installer::install_extension(&zxp.to_string_lossy());
+        assert!(result.success, "{}", result.message);
+        PathBuf::from(load_manifest(ID).unwrap().install_path)
+    }
+
+    #[test]
+    fn intact_after_install() {
+        let sandbox = Sandbox::new();
+        install(&sandbox);
+        let report = verify_extension(ID).unwrap();
+        assert!(report.intact);
+        assert!(report.can_repair);
+    }
+
+    #[test]
+    fn reports_modified_missing_and_extra_files() {
+        let sandbox = Sandbox::new();
+        let dir = install(&sandbox);
+        fs::write(dir.join("index.html"), "<html>changed</html>").unwrap();
+        fs::remove_file(dir.join("js/main.js")).unwrap();
+        fs::write(dir.join("js/added.js"), "added();").unwrap();
+        fs::write(dir.join(".debug"), "<ExtensionList/>").unwrap();
+
+        let report = verify_extension(ID).unwrap();
+        assert!(!report.intact);
+        assert_eq!(report.modified, vec!["index.html"]);
+        assert_eq!(report.missing, vec!["js/main.js"]);
+        assert_eq!(report.extra, vec!["js/added.js"]);
+    }
+
+    #[test]
+    fn same_size_edit_is_modified() {
+        let sandbox = Sandbox::new();
+        let dir = install(&sandbox);
+        fs::write(dir.join("js/main.js"), "evil();").unwrap();
+        assert_eq!(verify_extension(ID).unwrap().modified, vec!["js/main.js"]);
+    }
+
+    #[test]
+    fn repair_restores_from_cached_package() {
+        let sandbox = Sandbox::new();
+        let dir = install(&sandbox);
+        // The source package is gone; only the cache can bring the files back
+        fs::remove_dir_all(sandbox.path("in")).unwrap();
+        fs::write(dir.join("index.html"), "tampered").unwrap();
+        fs::remove_file(dir.join("js/main.js")).unwrap();
+        fs::write(dir.join("js/added.js"), "added();").unwrap();
+
+        let result = repair_extension(ID);
+        assert!(result.success, "{}", result.message);
+        assert_eq!(fs::read_to_string(dir.join("index.html")).unwrap(), "<html></html>");
+        assert_eq!(fs::read_to_string(dir.join("js/main.js")).unwrap(), "main();");
+        assert!(!dir.join("js/added.js").exists());
+        assert!(verify_extension(ID).unwrap().intact);
+    }
+
+    #[test]
+    fn repair_refuses_corrupt_cache() {
+        let sandbox = Sandbox::new();
+        install(&sandbox);
+        fs::write(cached_package_path(ID).unwrap(), "not a zip").unwrap();
+        let result = repair_extension(ID);
+        assert!(!result.success);
+        assert!(result.message.contains("corrupt"));
+    }
+
+    #[test]
+    fn repair_without_cache_fails() {
+        let _sandbox = Sandbox::new();
+        let result = repair_extension(ID);
+        assert!(!result.success);
+        assert!(result.message.contains("No cached package"));
+    }
+
+    #[test]
+    fn verify_without_record_fails() {
+        let _sandbox = Sandbox::new();
+        assert!(verify_extension(ID).is_err());
+    }
+
+    #[test]
+    fn stash_and_unstash_round_trip() {
+        let sandbox = Sandbox::new();
+        let dir = install(&sandbox);
+        let stash = sandbox.path("trash/item/records");
+
+        stash_install(ID, &dir, &stash).unwrap();
+        assert!(load_manifest(ID).is_none());
+        assert!(!cached_package_path(ID).unwrap().exists());
+        assert!(stash.join(STASHED_MANIFEST).is_file());
+        assert!(stash.join(STASHED_PACKAGE).is_file());
+
+        unstash_install(ID, &stash).unwrap();
+        assert!(load_manifest(ID).is_some());
+        assert!(cached_package_path(ID).unwrap().is_file());
+        assert!(!stash.join(STASHED_MANIFEST).exists());
+    }
+
+    #[test]
+    fn stash_ignores_record_of_another_copy() {
+        let sandbox = Sandbox::new();
+        install(&sandbox);
+        let other = sandbox.path("elsewhere");
+        fs::create_dir_all(&other).unwrap();
+        let stash = sandbox.path("trash/item/records");
+
+        stash_install(ID, &other, &stash).unwrap();
+        assert!(load_manifest(ID).is_some());
+        assert!(!stash.exists());
+    }
+
+    #[test]
+    fn unstash_keeps_newer_record() {
+        let sandbox = Sandbox::new();
+        let dir = install(&sandbox);
+        let stash = sandbox.path("trash/item/records");
+        stash_install(ID, &dir, &stash).unwrap();
+        install(&sandbox);
+
+        unstash_install(ID, &stash).unwrap();
+        assert!(stash.join(STASHED_MANIFEST).is_file());
+    }
+}
@@ -1,20 +1,107 @@
+mod catalog;
+mod cli;
+mod debug;
+mod dependencies;
+mod diagnostics;
+mod elevate;
+mod extract;
+mod hybrid;
 mod installer;
+mod integrity;
+mod locale;
+mod plan;
+mod preview;
+mod profile;
+mod scan_cache;
+mod security;
+mod settings;
+mod sidecar;
+mod support;
+mod sync;
+#[cfg(test)]
+mod test_support;
+mod trash;
+mod updates;
+mod uxp;
+mod version;
+mod watcher;
 
+use catalog::CatalogEntry;
+use debug::{DebugConfig, DebugTarget};
+use dependencies::DependencyReport;
+use diagnostics::DiagnosticsReport;
 use installer::{ExtensionInfo, InstallResult};
+use integrity::IntegrityReport;
+use profile::{ImportPlan, ImportReport, Profile};
+use plan::ActionResult;
+use preview::InstallPreview;
+use settings::Settings;
+use support::{SupportBundle, SupportBundleOptions};
+use sync::SyncPlan;
+use trash::TrashItem;
+use updates::UpdateInfo;
+use uxp::UxpPlugin;
 
 #[tauri::command]
-fn list_extensions() -> Vec<ExtensionInfo> {
-    installer::list_extensions()
+fn list_extensions(show_hidden: Option<bool>, language: Option<String>) -> Vec<ExtensionInfo> {
+    match (show_hidden.unwrap_or(false), language) {
+        (false, None) => installer::list_extensions(),
+        (show_hidden, language) => installer::list_extensions_filtered(show_hidden, language.as_deref()),
+    }
+}
+
+#[tauri::command]
+fn list_extensions_cached(app: tauri::AppHandle) -> Vec<ExtensionInfo> {
+    scan_cache::list_extensions_cached(app)
+}
+
+#[tauri::command]
+fn get_extension_info_from_zxp(path: String, language: Option<String>) -> Result<ExtensionInfo, String> {
+    installer::get_extension_info_from_zxp_localized(&path, language.as_deref())
+}
+
+#[tauri::command]
+fn preview_install(path: String) -> Result<InstallPreview, String> {
+    preview::preview_install(&path)
+}
+
+#[tauri::command]
+fn install_extension(path: String, system: Option<bool>) -> InstallResult {
+    if system.unwrap_or(false) {
+        installer::install_extension_system(&path)
+    } else {
+        installer::install_extension(&path)
+    }
+}
+
+#[tauri::command]
+fn check_dependencies(install_path: String) -> Result<DependencyReport, String> {
+    dependencies::check_dependencies(&install_path)
+}
+
+#[tauri::command]
+fn install_companions(install_path: String) -> Result<DependencyReport, String> {
+    dependencies::install_companions(&install_path)
+}
+
+#[tauri::command]
+fn list_uxp_plugins() -> Vec<UxpPlugin> {
+    uxp::list_uxp_plugins()
 }
 
 #[tauri::command]
-fn get_extension_info_from_zxp(path: String) -> Result<ExtensionInfo, String> {
-    installer::get_extension_info_from_zxp(&path)
+fn install_uxp_plugin(path: String) -> Result<UxpPlugin, String> {
+    uxp::install_uxp_plugin(&path)
 }
 
 #[tauri::command]
-fn install_extension(path: String) -> InstallResult {
-    installer::install_extension(&path)
+fn uninstall_uxp_plugin(plugin_id: String) -> Result<(), String> {
+    uxp::uninstall_uxp_plugin(&plugin_id)
+}
+
+#[tauri::command]
+fn get_uxp_info_from_ccx(path: String) -> Result<UxpPlugin, String> {
+    uxp::get_plugin_info_from_ccx(&path)
 }
 
 #[tauri::command]
@@ -22,6 +109,21 @@ fn uninstall_extension(extension_id: String, install_path: Option<String>) -> Re
     installer::uninstall_extension(&extension_id, install_path.as_deref())
 }
 
+#[tauri::command]
+fn list_trash() -> Vec<TrashItem> {
+    trash::list_trash()
+}
+
+#[tauri::command]
+fn restore_from_trash(trash_id: String) -> Result<TrashItem, String> {
+    trash::restore_from_trash(&trash_id)
+}
+
+#[tauri::command]
+fn empty_trash() -> Result<(), String> {
+    trash::empty_trash()
+}
+
 #[tauri::command]
 fn get_extensions_folder() -> String {
     installer::get_extensions_folder()
@@ -49,6 +151,36 @@ fn open_extensions_folder() -> Result<(), String> {
     Ok(())
 }
 
+#[tauri::command]
+fn create_support_bundle(
+    app: tauri::AppHandle,
+    path: String,
+    options: Option<SupportBundleOptions>,
+) -> Result<SupportBundle, String> {
+    use tauri::Manager;
+    support::create_support_bundle(&path, &options.unwrap_or_default(), app.path().app_log_dir().ok())
+}
+
+#[tauri::command]
+fn diagnose_extension(install_path: String) -> Result<DiagnosticsReport, String> {
+    diagnostics::diagnose_extension(&install_path)
+}
+
+#[tauri::command]
+fn get_debug_config(install_path: String) -> Result<DebugConfig, String> {
+    debug::get_debug_config(&install_path)
+}
+
+#[tauri::command]
+fn write_debug_file(install_path: String, extensions: Option<Vec<DebugTarget>>) -> Result<DebugConfig, String> {
+    debug::write_debug_file(&install_path, extensions)
+}
+
+#[tauri::command]
+fn remove_debug_file(install_path: String) -> Result<(), String> {
+    debug::remove_debug_file(&install_path)
+}
+
 #[tauri::command]
 fn get_debug_mode() -> bool {
     installer::get_debug_mode()
@@ -59,6 +191,82 @@ fn set_debug_mode(enabled: bool) -> Result<(), String> {
     installer::set_debug_mode(enabled)
 }
 
+#[tauri::command]
+fn verify_extension(extension_id: String) -> Result<IntegrityReport, String> {
+    integrity::verify_extension(&extension_id)
+}
+
+#[tauri::command]
+fn repair_extension(extension_id: String) -> InstallResult {
+    integrity::repair_extension(&extension_id)
+}
+
+#[tauri::command]
+fn get_settings() -> Settings {
+    settings::load_settings()
+}
+
+#[tauri::command]
+fn save_settings(settings: Settings) -> Result<(), String> {
+    settings::save_settings(&settings)
+}
+
+#[tauri::command]
+fn browse_catalog(source: Option<String>) -> Result<Vec<CatalogEntry>, String> {
+    catalog::browse_catalog(source.as_deref())
+}
+
+#[tauri::command]
+fn install_from_catalog(extension_id: String, source: Option<String>) -> InstallResult {
+    catalog::install_from_catalog(&extension_id, source.as_deref())
+}
+
+#[tauri::command]
+fn check_extension_updates() -> Vec<UpdateInfo> {
+    updates::check_updates()
+}
+
+#[tauri::command]
+fn update_extension(extension_id: String) -> InstallResult {
+    updates::update_extension(&extension_id)
+}
+
+#[tauri::command]
+fn update_all_extensions() -> Vec<InstallResult> {
+    updates::update_all()
+}
+
+#[tauri::command]
+fn export_profile(path: String, include_packages: bool) -> Result<Profile, String> {
+    profile::export_profile(&path, include_packages)
+}
+
+#[tauri::command]
+fn plan_profile_import(path: String, remove_unlisted: bool) -> Result<ImportPlan, String> {
+    profile::plan_profile_import(&path, remove_unlisted)
+}
+
+#[tauri::command]
+fn apply_profile_import(path: String, remove_unlisted: bool) -> Result<ImportReport, String> {
+    profile::apply_profile_import(&path, remove_unlisted)
+}
+
+#[tauri::command]
+fn plan_sync(lockfile: String, remove_unlisted: Option<bool>) -> Result<SyncPlan, String> {
+    sync::plan_sync(&lockfile, remove_unlisted)
+}
+
+#[tauri::command]
+fn apply_sync(lockfile: String, remove_unlisted: Option<bool>) -> Result<Vec<ActionResult>, String> {
+    sync::apply_sync(&lockfile, remove_unlisted)
+}
+
+/// Run a CLI subcommand if one was given; returns the exit code
+pub fn run_cli() -> Option<i32> {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    cli::run(&args)
+}
+
 #[cfg_attr(mobile, tauri::mobile_entry_point)]
 pub fn run() {
     tauri::Builder::default()
@@ -68,15 +276,49 @@ pub fn run() {
         .plugin(tauri_plugin_fs::init())
         .plugin(tauri_plugin_updater::Builder::new().build())
         .plugin(tauri_plugin_process::init())
+        .setup(|app| {
+            watcher::start(app.handle().clone());
+            Ok(())
+        })
         .invoke_handler(tauri::generate_handler![
             list_extensions,
+            list_extensions_cached,
             get_extension_info_from_zxp,
+            preview_install,
             install_extension,
+            check_dependencies,
+            install_companions,
             uninstall_extension,
+            list_uxp_plugins,
+            get_uxp_info_from_ccx,
+            install_uxp_plugin,
+            uninstall_uxp_plugin,
+            list_trash,
+            restore_from_trash,
+            empty_trash,
             get_extensions_folder,
             open_extensions_folder,
             get_debug_mode,
             set_debug_mode,
+            diagnose_extension,
+            create_support_bundle,
+            get_debug_config,
+            write_debug_file,
+            remove_debug_file,
+            verify_extension,
+            repair_extension,
+            get_settings,
+            save_settings,
+            browse_catalog,
+            install_from_catalog,
+            check_extension_updates,
+            update_extension,
+            update_all_extensions,
+            export_profile,
+            plan_profile_import,
+            apply_profile_import,
+            plan_sync,
+            apply_sync,
         ])
         .run(tauri::generate_context!())
         .expect("error while running tauri application");
@@ -0,0 +1,273 @@
+use crate::installer::{self, ExtensionInfo};
+use crate::settings;
+use std::collections::HashMap;
+use std::fs;
+use std::path::Path;
+use std::sync::OnceLock;
+
+/// Used when neither the settings nor the system name a language
+const FALLBACK_LANGUAGE: &str = "en_US";
+
+/// Normalize a language tag to CEP's folder form: `de-DE` / `de_DE.UTF-8` → `de_DE`
+pub fn normalize(code: &str) -> String {
+    let code = code.split(['.', '@']).next().unwrap_or(code).replace('-', "_");
+    match code.split_once('_') {
+        Some((lang, region)) => format!("{}_{}", lang.to_lowercase(), region.to_uppercase()),
+        None => code.to_lowercase(),
+    }
+}
+
+/// The POSIX locale variables
+fn env_language() -> Option<String> {
+    ["LC_ALL", "LC_MESSAGES", "LANG"]
+        .iter()
+        .filter_map(|var| std::env::var(var).ok())
+        .find(|v| !v.is_empty() && v != "C" && v != "POSIX")
+}
+
+/// The user's locale as the OS reports it: the Windows user locale, the macOS
+/// `AppleLocale` preference, or the POSIX locale variables elsewhere
+fn system_language() -> Option<String> {
+    #[cfg(target_os = "windows")]
+    {
+        use winreg::enums::HKEY_CURRENT_USER;
+        use winreg::RegKey;
+        // What GetUserDefaultLocaleName returns, e.g. "de-DE"
+        RegKey::predef(HKEY_CURRENT_USER)
+            .open_subkey("Control Panel\\International")
+            .and_then(|key| key.get_value::<String, _>("LocaleName"))
+            .ok()
+            .filter(|v| !v.is_empty())
+    }
+    #[cfg(target_os = "macos")]
+    {
+        // e.g. "de_DE" or "en_GB@rg=dezzzz"; apps launched from Finder get no LANG
+        std::process::Command::new("defaults")
+            .args(["read", "-g", "AppleLocale"])
+            .output()
+            .ok()
+            .filter(|output| output.status.success())
+            .map(|output| String::from_utf8_lossy(&output.stdout).trim().to_string())
+            .filter(|v| !v.is_empty())
+    }
+    #[cfg(not(any(target_os = "windows", target_os = "macos")))]
+    {
+        env_language()
+    }
+}
+
+/// Display language: the settings override, then the system locale, then en_US
+pub fn preferred_language() -> String {
+    if let Some(language) = settings::load_settings().language.filter(|l| !l.is_empty()) {
+        return normalize(&language);
+    }
+    // Asking the OS may start a process — once per run is enough
+    static SYSTEM: OnceLock<String> = OnceLock::new();
+    SYSTEM
+        .get_or_init(|| {
+            system_language()
+                .or_else(env_language)
+                .map(|v| normalize(&v))
+                .unwrap_or_else(|| FALLBACK_LANGUAGE.to_string())
+        })
+        .clone()
+}
+
+/// Parse a Java-style `.properties` file (`key=value`, `#`/`!` comments, `\uXXXX` escapes)
+pub fn parse_properties(content: &str) -> HashMap<String, String> {
+    let mut strings = HashMap::new();
+    let mut logical = String::new();
+    for line in content.trim_start_matches('\u{feff}').lines() {
+        let line = line.trim_start();
+        if logical.is_empty() && (line.is_empty() || line.starts_with('#') || line.starts_with('!')) {
+            continue;
+        }
+        // A trailing backslash continues the value on the next line
+        if let Some(continued) = line.strip_suffix('\\').filter(|l| !l.ends_with('\\')) {
+            logical.push_str(continued);
+            continue;
+        }
+        logical.push_str(line);
+
+        let split = logical.find(['=', ':']).unwrap_or(logical.len());
+        let key = logical[..split].trim();
+        let value = logical.get(split + 1..).unwrap_or("").trim_start();
+        if !key.is_empty() {
+            strings.insert(key.to_string(), unescape(value));
+        }
+        logical.clear();
+    }
+    strings
+}
+
+fn unescape(value: &str) -> String {
+    let mut out = String::with_capacity(value.len());
+    let mut chars = value.chars();
+    while let Some(c) = chars.next() {
+        if c != '\\' {
+            out.push(c);
+            continue;
+        }
+        match chars.next() {
+            Some('n') => out.push('\n'),
+            Some('t') => out.push('\t'),
+            Some('u') => {
+                let hex: String = chars.by_ref().take(4).collect();
+                match u32::from_str_radix(&hex, 16).ok().and_then(char::from_u32) {
+                    Some(ch) => out.push(ch),
+                    None => out.push_str(&hex),
+                }
+            }
+            Some(other) => out.push(other),
+            None => {}
+        }
+    }
+    out
+}
+
+/// Pick the `locale/<code>` folder CEP would load for a language: an exact match,
+/// then the same language in another region, then the manifest's first declared locale
+fn pick_folder(language: &str, available: &[String], declared: &[String]) -> Option<String> {
+    let language = normalize(language);
+    let base = language.split('_').next().unwrap_or(&language).to_string();
+    let find = |wanted: &dyn Fn(&str) -> bool| available.iter().find(|f| wanted(&normalize(f))).cloned();
+
+    find(&|f| f == language)
+        .or_else(|| find(&|f| f.split('_').next() == Some(base.as_str())))
+        .or_else(|| {
+            // An extension restricted to other locales still shows its own strings
+            declared
+                .iter()
+                .filter(|code| !code.eq_ignore_ascii_case("All"))
+                .find_map(|code| find(&|f| f == normalize(code)))
+        })
+}
+
+/// Resolve `%key` placeholders in an ExtensionInfo. `read` returns the content of a
+/// path relative to the extension root (e.g. `locale/de_DE/messages.properties`).
+pub fn localize(
+    info: &mut ExtensionInfo,
+    language: &str,
+    available: &[String],
+    read: impl Fn(&str) -> Option<String>,
+) {
+    let fields = [&info.name, &info.description, &info.bundle_name, &info.author];
+    if !fields.iter().any(|f| f.starts_with('%')) {
+        return;
+    }
+
+    // Default strings first, then the chosen locale on top of them
+    let mut strings = read("locale/messages.properties")
+        .map(|c| parse_properties(&c))
+        .unwrap_or_default();
+    if let Some(folder) = pick_folder(language, available, &info.locales) {
+        if let Some(content) = read(&format!("locale/{}/messages.properties", folder)) {
+            strings.extend(parse_properties(&content));
+        }
+    }
+
+    let resolve = |value: &mut String| {
+        if let Some(key) = value.strip_prefix('%') {
+            match strings.get(key) {
+                Some(text) => *value = text.clone(),
+                None => value.clear(),
+            }
+        }
+    };
+    resolve(&mut info.name);
+    resolve(&mut info.description);
+    resolve(&mut info.bundle_name);
+    resolve(&mut info.author);
+
+    if info.name.is_empty() {
+        info.name = if info.bundle_name.is_empty() {
+            installer::name_from_id(&info.id)
+        } else {
+            info.bundle_name.clone()
+        };
+    }
+}
+
+/// Locale subfolders of an installed extension
+pub fn dir_locales(dir: &Path) -> Vec<String> {
+    fs::read_dir(dir.join("locale"))
+        .map(|entries| {
+            entries
+                .flatten()
+                .filter(|e| e.path().is_dir())
+                .map(|e| e.file_name().to_string_lossy().to_string())
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+/// Localize an installed extension from its own locale folder
+pub fn localize_dir(info: &mut ExtensionInfo, dir: &Path, language: &str) {
+    let available = dir_locales(dir);
+    localize(info, language, &available, |rel| fs::read_to_string(dir.join(rel)).ok());
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn normalize_forms() {
+        assert_eq!(normalize("de-DE"), "de_DE");
+        assert_eq!(normalize("de_de.UTF-8"), "de_DE");
+        assert_eq!(normalize("en_GB@rg=dezzzz"), "en_GB");
+        assert_eq!(normalize("FR"), "fr");
+    }
+
+    #[test]
+    fn parse_properties_handles_comments_escapes_and_continuations() {
+        let strings = parse_properties(
+            "\u{feff}# comment\n! also a comment\nname=Mein Panel\ndesc : Zeile eins \\\n  und zwei\nquote=\\u00e4\\tb\nempty=\n",
+        );
+        assert_eq!(strings["name"], "Mein Panel");
+        assert_eq!(strings["desc"], "Zeile eins und zwei");
+        assert_eq!(strings["quote"], "ä\tb");
+        assert_eq!(strings["empty"], "");
+        assert_eq!(strings.len(), 4);
+    }
+
+    #[test]
+    fn pick_folder_prefers_exact_then_language_then_declared() {
+        let available = vec!["de_DE".to_string(), "fr_FR".to_string(), "ja_JP".to_string()];
+        assert_eq!(pick_folder("de-DE", &available, &[]), Some("de_DE".to_string()));
+        assert_eq!(pick_folder("fr_CA", &available, &[]), Some("fr_FR".to_string()));
+        let declared = vec!["All".to_string(), "ja_JP".to_string()];
+        assert_eq!(pick_folder("es_ES", &available, &declared), Some("ja_JP".to_string()));
+        assert_eq!(pick_folder("es_ES", &available, &[]), None);
+    }
+
+    #[test]
+    fn localize_layers_the_locale_over_the_defaults() {
+        let mut info = ExtensionInfo {
+            id: "com.vendor.panel".to_string(),
+            name: "%name".to_string(),
+            description: "%desc".to_string(),
+            author: "%missing".to_string(),
+            ..Default::default()
+        };
+        let files: HashMap<&str, &str> = HashMap::from([
+            ("locale/messages.properties", "name=Panel\ndesc=Default description"),
+            ("locale/de_DE/messages.properties", "name=Tafel"),
+        ]);
+        localize(&mut info, "de_DE", &["de_DE".to_string()], |rel| files.get(rel).map(|c| c.to_string()));
+        assert_eq!(info.name, "Tafel");
+        assert_eq!(info.description, "Default description");
+        assert_eq!(info.author, "");
+    }
+
+    #[test]
+    fn unresolved_name_falls_back_to_the_id() {
+        let mut info = ExtensionInfo {
+            id: "com.vendor.color-picker".to_string(),
+            name: "%name".to_string(),
+            ..Default::default()
+        };
+        localize(&mut info, "en_US", &[], |_| None);
+        assert_eq!(info.name, "Color Picker");
+    }
+}
@@ -1,6 +1,10 @@
 // Prevents additional console window on Windows in release, DO NOT REMOVE!!
+// CLI subcommands attach to the parent shell's console instead (see cli::run).
 #![cfg_attr(not(debug_assertions), windows_subsystem = "windows")]
 
 fn main() {
+  if let Some(code) = app_lib::run_cli() {
+    std::process::exit(code);
+  }
   app_lib::run();
 }
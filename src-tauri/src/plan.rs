@@ -0,0 +1,182 @@
+use crate::catalog::{self, CatalogPackage};
+use crate::installer::{self, InstallResult};
+use crate::settings::{self, ExtractionLimits};
+use crate::version;
+use serde::{Deserialize, Serialize};
+use std::fs;
+use zip::ZipArchive;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PlanAction {
+    Install,
+    Upgrade,
+    Downgrade,
+    Remove,
+    Unchanged,
+}
+
+/// Where the package for an install/upgrade comes from
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum PackageSource {
+    /// A ZXP on disk (cached original, update folder, …)
+    File { path: String },
+    /// A catalog/feed package, verified against its SHA-256
+    Remote { package: CatalogPackage },
+    /// A ZXP stored inside another archive (e.g. a bundled profile)
+    Archive { archive: String, entry: String },
+}
+
+/// One step of reconciling this machine with a desired set of extensions
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PlanItem {
+    pub action: PlanAction,
+    pub extension_id: String,
+    pub name: String,
+    pub installed_version: Option<String>,
+    pub target_version: Option<String>,
+    pub install_path: Option<String>,
+    /// None for removals, or when no package could be found
+    pub source: Option<PackageSource>,
+    /// Install into the machine-wide extensions folder
+    #[serde(default)]
+    pub system: bool,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ActionResult {
+    pub extension_id: String,
+    pub action: PlanAction,
+    pub success: bool,
+    pub message: String,
+}
+
+/// Pick install/upgrade/downgrade/unchanged for a wanted version
+pub fn action_for(installed_version: Option<&str>, target_version: &str) -> PlanAction {
+    match installed_version {
+        None => PlanAction::Install,
+        Some(v) => match version::compare_versions(target_version, v) {
+            std::cmp::Ordering::Greater => PlanAction::Upgrade,
+            std::cmp::Ordering::Less => PlanAction::Downgrade,
+            std::cmp::Ordering::Equal => PlanAction::Unchanged,
+        },
+    }
+}
+
+/// Copy a ZXP out of an archive into the download folder
+fn extract_archive_entry(
+    archive_path: &str,
+    entry_name: &str,
+    limits: &ExtractionLimits,
+) -> Result<std::path::PathBuf, String> {
+    let file = fs::File::open(archive_path).map_err(|e| format!("Cannot open {}: {}", archive_path, e))?;
+    let mut archive = ZipArchive::new(file).map_err(|e| format!("Cannot read {}: {}", archive_path, e))?;
+    let mut entry = archive
+        .by_name(entry_name)
+        .map_err(|_| format!("{} is missing from {}", entry_name, archive_path))?;
+
+    let downloads = installer::get_data_folder().join("downloads");
+    fs::create_dir_all(&downloads).map_err(|e| format!("Cannot create download folder: {}", e))?;
+    let file_name = entry_name.rsplit('/').next().unwrap_or(entry_name);
+    let target = downloads.join(file_name);
+
+    let budget = limits.max_file_bytes.min(limits.max_total_bytes);
+    if entry.size() > budget {
+        return Err(format!("{} is larger than the extraction limit", entry_name));
+    }
+    let mut out = fs::File::create(&target).map_err(|e| format!("Cannot save package: {}", e))?;
+    if let Err(e) = installer::copy_limited(&mut entry, &mut out, budget) {
+        drop(out);
+        let _ = fs::remove_file(&target);
+        return Err(e);
+    }
+    Ok(target)
+}
+
+fn install_from_source(source: &PackageSource, system: bool) -> InstallResult {
+    let install: fn(&str) -> InstallResult = if system {
+        installer::install_extension_system
+    } else {
+        installer::install_extension
+    };
+    match source {
+        PackageSource::File { path } => install(path),
+        PackageSource::Remote { package } => catalog::install_package_with(package, install),
+        PackageSource::Archive { archive, entry } => {
+            match extract_archive_entry(archive, entry, &settings::load_settings().extraction_limits) {
+                Ok(path) => {
+                    let result = install(&path.to_string_lossy());
+                    let _ = fs::remove_file(&path);
+                    result
+                }
+                Err(e) => InstallResult {
+                    success: false,
+                    message: e,
+                    extension: None,
+                    payload: Vec::new(),
+                },
+            }
+        }
+    }
+}
+
+/// Run a plan, continuing past failures so one broken package doesn't block the rest
+pub fn execute(items: &[PlanItem]) -> Vec<ActionResult> {
+    items
+        .iter()
+        .filter(|item| item.action != PlanAction::Unchanged)
+        .map(|item| {
+            let (success, message) = match (item.action, &item.source) {
+                (PlanAction::Remove, _) => {
+                    match installer::uninstall_extension(&item.extension_id, item.install_path.as_deref()) {
+                        Ok(()) => (true, format!("'{}' removed", item.name)),
+                        Err(e) => (false, e),
+                    }
+                }
+                (_, Some(source)) => {
+                    let result = install_from_source(source, item.system);
+                    (result.success, result.message)
+                }
+                (_, None) => (false, format!("No package available for '{}'", item.extension_id)),
+            };
+            ActionResult {
+                extension_id: item.extension_id.clone(),
+                action: item.action,
+                success,
+                message,
+            }
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::{write_zip, Sandbox};
+
+    #[test]
+    fn archive_entry_is_extracted_within_limits() {
+        let sandbox = Sandbox::new();
+        let archive = sandbox.path("bundle.zip");
+        write_zip(&archive, &[("packages/panel.zxp", "zxp bytes")]);
+        let archive = archive.to_string_lossy();
+        let limits = ExtractionLimits::default();
+        let path = extract_archive_entry(&archive, "packages/panel.zxp", &limits).unwrap();
+        assert_eq!(fs::read_to_string(&path).unwrap(), "zxp bytes");
+        assert!(extract_archive_entry(&archive, "missing.zxp", &limits).is_err());
+    }
+
+    #[test]
+    fn oversized_archive_entry_is_refused() {
+        let sandbox = Sandbox::new();
+        let archive = sandbox.path("bundle.zip");
+        write_zip(&archive, &[("panel.zxp", "0123456789")]);
+        let limits = ExtractionLimits {
+            max_file_bytes: 4,
+            ..ExtractionLimits::default()
+        };
+        assert!(extract_archive_entry(&archive.to_string_lossy(), "panel.zxp", &limits).is_err());
+        assert!(!installer::get_data_folder().join("downloads/panel.zxp").exists());
+    }
+}
@@ -0,0 +1,175 @@
+use crate::extract;
+use crate::installer::{self, ExtensionInfo};
+use crate::settings;
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::Path;
+use walkdir::WalkDir;
+use zip::ZipArchive;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ArchiveEntry {
+    pub path: String,
+    pub is_dir: bool,
+    pub compressed_size: u64,
+    pub size: u64,
+    /// Why the entry would not be extracted, if it wouldn't
+    pub skipped: Option<String>,
+}
+
+/// Everything a ZXP would put on disk, computed without extracting it
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct InstallPreview {
+    pub extension: ExtensionInfo,
+    pub entries: Vec<ArchiveEntry>,
+    /// Files that would be extracted
+    pub file_count: u64,
+    /// Uncompressed size of the files that would be extracted
+    pub install_size: u64,
+    pub compressed_size: u64,
+    /// Wrapper folders around the extension that are dropped on install
+    pub wrapper_folder: Option<String>,
+    pub target_path: String,
+    /// Existing copies that would be replaced, possibly under other folder names
+    pub replaces: Vec<String>,
+    /// Size of the installation this one would replace
+    pub existing_size: Option<u64>,
+    /// Free space on the target volume, if it could be determined
+    pub free_space: Option<u64>,
+    /// Whether the install fits, counting the space freed by replacing an existing copy
+    pub enough_space: Option<bool>,
+    /// Extraction limit the archive breaks; installing it would be refused
+    pub limit_error: Option<String>,
+}
+
+fn dir_size(dir: &Path) -> u64 {
+    WalkDir::new(dir)
+        .into_iter()
+        .flatten()
+        .filter(|e| e.file_type().is_file())
+        .filter_map(|e| e.metadata().ok())
+        .map(|m| m.len())
+        .sum()
+}
+
+/// Free space on the volume holding `path`, walking up to the first folder that exists
+fn free_space(path: &Path) -> Option<u64> {
+    let existing = path.ancestors().find(|p| p.exists())?;
+    fs4::available_space(existing).ok()
+}
+
+/// List a ZXP's contents and estimate what installing it would take
+pub fn preview_install(path: &str) -> Result<InstallPreview, String> {
+    let extension = installer::get_extension_info_from_zxp(path)?;
+    let file = fs::File::open(path).map_err(|e| format!("Cannot open file: {}", e))?;
+    let mut archive = ZipArchive::new(file).map_err(|e| format!("Not a valid ZXP: {}", e))?;
+
+    let root = extract::find_extension_root(&archive)
+        .map(|(_, root)| root)
+        .unwrap_or_default();
+
+    let mut entries = Vec::with_capacity(archive.len());
+    for i in 0..archive.len() {
+        let entry = archive.by_index(i).map_err(|e| format!("Cannot read ZXP entry: {}", e))?;
+        entries.push(ArchiveEntry {
+            path: entry.name().to_string(),
+            is_dir: entry.is_dir(),
+            compressed_size: entry.compressed_size(),
+            size: entry.size(),
+            skipped: installer::skip_reason(entry.name(), &root).map(str::to_string),
+        });
+    }
+
+    let limits = settings::load_settings().extraction_limits;
+    let limit_error = installer::check_archive_limits(&mut archive, &root, &limits).err();
+
+    let extracted = entries.iter().filter(|e| e.skipped.is_none() && !e.is_dir);
+    let file_count = extracted.clone().count() as u64;
+    let install_size: u64 = extracted.map(|e| e.size).sum();
+    let compressed_size = entries.iter().map(|e| e.compressed_size).sum();
+
+    let (target, existing) = installer::install_target(&installer::get_extensions_folder(), &extension.id)?;
+    let existing_size = (!existing.is_empty()).then(|| existing.iter().map(|dir| dir_size(dir)).sum());
+    let free_space = free_space(&target);
+    let enough_space = free_space.map(|free| free + existing_size.unwrap_or(0) >= install_size);
+
+    Ok(InstallPreview {
+        wrapper_folder: (!root.is_empty()).then(|| root.trim_end_matches('/').to_string()),
+        extension,
+        entries,
+        file_count,
+        install_size,
+        compressed_size,
+        target_path: target.to_string_lossy().to_string(),
+        replaces: existing.iter().map(|dir| dir.to_string_lossy().to_string()).collect(),
+        existing_size,
+        free_space,
+        enough_space,
+        limit_error,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::{manifest, write_zip, Sandbox};
+
+    #[test]
+    fn preview_lists_what_would_be_extracted() {
+        let sandbox = Sandbox::new();
+        let zxp = sandbox.path("in/wrapped.zxp");
+        let manifest = manifest("com.test.preview", "1.0.0");
+        write_zip(
+            &zxp,
+            &[
+                ("Wrapper/", ""),
+                ("Wrapper/CSXS/manifest.xml", &manifest),
+                ("Wrapper/index.html", "12345"),
+                ("Wrapper/.DS_Store", "junk"),
+                ("META-INF/signatures.xml", "sig"),
+            ],
+        );
+
+        let preview = preview_install(&zxp.to_string_lossy()).unwrap();
+        assert_eq!(preview.extension.id, "com.test.preview");
+        assert_eq!(preview.wrapper_folder.as_deref(), Some("Wrapper"));
+        assert_eq!(preview.file_count, 2);
+        assert_eq!(preview.install_size, manifest.len() as u64 + 5);
+        let skipped = |path: &str| preview.entries.iter().find(|e| e.path == path).unwrap().skipped.clone();
+        assert_eq!(skipped("Wrapper/").as_deref(), Some("Wrapper folder"));
+        assert_eq!(skipped("Wrapper/.DS_Store").as_deref(), Some("Operating system junk"));
+        assert_eq!(skipped("META-INF/signatures.xml").as_deref(), Some("ZXP signature files"));
+        assert_eq!(skipped("Wrapper/index.html"), None);
+        assert!(preview.replaces.is_empty() && preview.existing_size.is_none());
+        assert!(preview.limit_error.is_none());
+        // Nothing was written
+        assert!(!installer::get_extensions_folder().join("com.test.preview").exists());
+    }
+
+    #[test]
+    fn preview_counts_the_copy_it_replaces() {
+        let sandbox = Sandbox::new();
+        let zxp = sandbox.path("in/panel.zxp");
+        crate::test_support::write_zxp(&zxp, "com.test.preview", "1.0.0", &[("index.html", "12345")]);
+        assert!(installer::install_extension(&zxp.to_string_lossy()).success);
+
+        let preview = preview_install(&zxp.to_string_lossy()).unwrap();
+        let installed = installer::get_extensions_folder().join("com.test.preview");
+        assert_eq!(preview.replaces, vec![installed.to_string_lossy().to_string()]);
+        assert_eq!(preview.existing_size, Some(dir_size(&installed)));
+        assert_eq!(preview.target_path, installed.to_string_lossy());
+    }
+
+    #[test]
+    fn preview_reports_broken_limits() {
+        let sandbox = Sandbox::new();
+        let mut settings = settings::load_settings();
+        settings.extraction_limits.max_entries = 1;
+        settings::save_settings(&settings).unwrap();
+        let zxp = sandbox.path("in/panel.zxp");
+        crate::test_support::write_zxp(&zxp, "com.test.preview", "1.0.0", &[("a.js", ""), ("b.js", "")]);
+
+        let preview = preview_install(&zxp.to_string_lossy()).unwrap();
+        assert!(preview.limit_error.is_some());
+    }
+}
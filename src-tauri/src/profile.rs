@@ -0,0 +1,448 @@
+use crate::catalog::{self, CatalogEntry};
+use crate::debug::{self, DebugTarget};
+use crate::installer::{self, ExtensionInfo};
+use crate::integrity;
+use crate::plan::{self, ActionResult, PackageSource, PlanAction, PlanItem};
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+use std::fs;
+use std::io::{Read, Write};
+use std::path::Path;
+use walkdir::WalkDir;
+use zip::write::SimpleFileOptions;
+use zip::{CompressionMethod, ZipArchive, ZipWriter};
+
+/// Bump when the profile.json layout changes incompatibly
+const PROFILE_FORMAT: u32 = 1;
+const PROFILE_ENTRY: &str = "profile.json";
+const PACKAGES_DIR: &str = "packages/";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum InstallScope {
+    /// Per-user CEP extensions folder
+    User,
+    /// Machine-wide CEP extensions folder
+    System,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ProfileExtension {
+    pub id: String,
+    pub name: String,
+    pub version: String,
+    pub scope: InstallScope,
+    /// True when the profile file carries a ZXP for this extension
+    pub bundled: bool,
+    /// Ports of the extension's `.debug` file; empty when it has none, absent
+    /// in profiles from before debug files were exported (left alone on import)
+    #[serde(default)]
+    pub debug: Option<Vec<DebugTarget>>,
+}
+
+/// Snapshot of a workstation's extension setup, stored as `profile.json`
+/// inside a ZIP (optionally next to `packages/<id>.zxp` payloads)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Profile {
+    pub format: u32,
+    /// Seconds since the Unix epoch
+    pub created_at: u64,
+    pub debug_mode: bool,
+    pub extensions: Vec<ProfileExtension>,
+}
+
+/// A `.debug` file that importing would write, or delete when `extensions` is empty
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DebugFileChange {
+    pub extension_id: String,
+    pub extensions: Vec<DebugTarget>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ImportPlan {
+    pub profile: Profile,
+    pub items: Vec<PlanItem>,
+    /// New PlayerDebugMode value, if it differs from this machine
+    pub debug_mode_change: Option<bool>,
+    pub debug_file_changes: Vec<DebugFileChange>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ImportReport {
+    pub results: Vec<ActionResult>,
+    pub debug_mode_changed: bool,
+    /// Extensions whose `.debug` file was written or removed
+    pub debug_files_changed: Vec<String>,
+    pub debug_file_errors: Vec<String>,
+}
+
+fn scope_of(ext: &ExtensionInfo) -> InstallScope {
+    if installer::is_system_install(ext) {
+        InstallScope::System
+    } else {
+        InstallScope::User
+    }
+}
+
+/// Ports of an installed extension's `.debug` file, empty if it has none
+fn debug_targets(ext: &ExtensionInfo) -> Vec<DebugTarget> {
+    ext.install_path
+        .as_deref()
+        .and_then(|dir| debug::get_debug_config(dir).ok())
+        .filter(|config| config.exists)
+        .map(|config| config.extensions)
+        .unwrap_or_default()
+}
+
+fn package_entry(extension_id: &str) -> Result<String, String> {
+    Ok(format!("{}{}.zxp", PACKAGES_DIR, installer::folder_name_for(extension_id)?))
+}
+
+/// Re-pack an installed extension folder as a ZXP (used when no original is cached)
+fn pack_directory(dir: &Path) -> Result<Vec<u8>, String> {
+    let mut writer = ZipWriter::new(std::io::Cursor::new(Vec::new()));
+    let options = SimpleFileOptions::default().compression_method(CompressionMethod::Deflated);
+
+    for entry in WalkDir::new(dir).into_iter().flatten() {
+        let rel = entry.path().strip_prefix(dir).unwrap_or(entry.path());
+        if rel.as_os_str().is_empty() {
+            continue;
+        }
+        let name = rel
+            .components()
+            .map(|c| c.as_os_str().to_string_lossy().to_string())
+            .collect::<Vec<_>>()
+            .join("/");
+        if entry.file_type().is_dir() {
+            writer.add_directory(name, options).map_err(|e| e.to_string())?;
+        } else if entry.file_type().is_file() {
+            writer.start_file(name, options).map_err(|e| e.to_string())?;
+            let bytes = fs::read(entry.path()).map_err(|e| format!("Cannot read {}: {}", entry.path().display(), e))?;
+            writer.write_all(&bytes).map_err(|e| e.to_string())?;
+        }
+    }
+
+    writer
+        .finish()
+        .map(|cursor| cursor.into_inner())
+        .map_err(|e| e.to_string())
+}
+
+/// Original package if cached with a matching version, else a re-pack of the installed folder
+fn package_bytes(ext: &ExtensionInfo) -> Result<Vec<u8>, String> {
+    let cached = integrity::cached_package_path(&ext.id)?;
+    let cache_matches = integrity::load_manifest(&ext.id)
+        .map(|m| m.version == ext.version)
+        .unwrap_or(false);
+    if cache_matches && cached.exists() {
+        return fs::read(&cached).map_err(|e| format!("Cannot read cached package: {}", e));
+    }
+    match &ext.install_path {
+        Some(dir) => pack_directory(Path::new(dir)),
+        None => Err(format!("'{}' has no install folder", ext.id)),
+    }
+}
+
+/// Write the current machine state to a profile file
+pub fn export_profile(path: &str, include_packages: bool) -> Result<Profile, String> {
+    let installed = installer::list_extensions();
+    let file = fs::File::create(path).map_err(|e| format!("Cannot create profile: {}", e))?;
+    let mut writer = ZipWriter::new(file);
+    // ZXPs are already compressed
+    let stored = SimpleFileOptions::default().compression_method(CompressionMethod::Stored);
+
+    let mut extensions = Vec::new();
+    for ext in &installed {
+        let mut bundled = false;
+        if include_packages {
+            match package_entry(&ext.id).and_then(|entry| Ok((entry, package_bytes(ext)?))) {
+                Ok((entry, bytes)) => {
+                    writer.start_file(entry, stored).map_err(|e| e.to_string())?;
+                    writer.write_all(&bytes).map_err(|e| e.to_string())?;
+                    bundled = true;
+                }
+                Err(e) => log::warn!("Cannot bundle '{}': {}", ext.id, e),
+            }
+        }
+        extensions.push(ProfileExtension {
+            id: ext.id.clone(),
+            name: ext.name.clone(),
+            version: ext.version.clone(),
+            scope: scope_of(ext),
+            bundled,
+            debug: Some(debug_targets(ext)),
+        });
+    }
+
+    let profile = Profile {
+        format: PROFILE_FORMAT,
+        created_at: std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .map(|d| d.as_secs())
+            .unwrap_or(0),
+        debug_mode: installer::get_debug_mode(),
+        extensions,
+    };
+
+    let json = serde_json::to_string_pretty(&profile).map_err(|e| e.to_string())?;
+    writer
+        .start_file(PROFILE_ENTRY, SimpleFileOptions::default())
+        .map_err(|e| e.to_string())?;
+    writer.write_all(json.as_bytes()).map_err(|e| e.to_string())?;
+    writer.finish().map_err(|e| format!("Cannot write profile: {}", e))?;
+    Ok(profile)
+}
+
+/// Read profile.json and the list of bundled package entries
+fn read_profile(path: &str) -> Result<(Profile, HashSet<String>), String> {
+    let file = fs::File::open(path).map_err(|e| format!("Cannot open profile: {}", e))?;
+    let mut archive = ZipArchive::new(file).map_err(|e| format!("Not a valid profile: {}", e))?;
+
+    let bundled: HashSet<String> = archive
+        .file_names()
+        .filter(|n| n.starts_with(PACKAGES_DIR))
+        .map(|n| n.to_string())
+        .collect();
+
+    let mut content = String::new();
+    archive
+        .by_name(PROFILE_ENTRY)
+        .map_err(|_| format!("{} not found in profile", PROFILE_ENTRY))?
+        .read_to_string(&mut content)
+        .map_err(|e| e.to_string())?;
+    let profile: Profile = serde_json::from_str(&content).map_err(|e| format!("Invalid profile: {}", e))?;
+    if profile.format > PROFILE_FORMAT {
+        return Err("Profile was created by a newer version of ZXP Magic".to_string());
+    }
+    Ok((profile, bundled))
+}
+
+/// Bundled payload, then a cached original of that version, then the catalog
+fn find_source(
+    profile_path: &str,
+    wanted: &ProfileExtension,
+    bundled: &HashSet<String>,
+    catalog: &[CatalogEntry],
+) -> Option<PackageSource> {
+    let entry = package_entry(&wanted.id).ok()?;
+    if bundled.contains(&entry) {
+        return Some(PackageSource::Archive {
+            archive: profile_path.to_string(),
+            entry,
+        });
+    }
+
+    let cached = integrity::cached_package_path(&wanted.id).ok()?;
+    if cached.exists() && integrity::load_manifest(&wanted.id).map(|m| m.version == wanted.version).unwrap_or(false) {
+        return Some(PackageSource::File {
+            path: cached.to_string_lossy().to_string(),
+        });
+    }
+
+    catalog
+        .iter()
+        .find(|c| c.package.id == wanted.id && c.package.version == wanted.version)
+        .map(|c| PackageSource::Remote {
+            package: c.package.clone(),
+        })
+}
+
+/// Compare a profile with this machine and list what importing it would change
+pub fn plan_profile_import(path: &str, remove_unlisted: bool) -> Result<ImportPlan, String> {
+    let (profile, bundled) = read_profile(path)?;
+    let installed = installer::list_extensions();
+    let catalog = catalog::browse_catalog(None).unwrap_or_default();
+
+    let mut items: Vec<PlanItem> = profile
+        .extensions
+        .iter()
+        .map(|wanted| {
+            let current = installed.iter().find(|e| e.id == wanted.id);
+            let action = plan::action_for(current.map(|e| e.version.as_str()), &wanted.version);
+            PlanItem {
+                action,
+                extension_id: wanted.id.clone(),
+                name: wanted.name.clone(),
+                installed_version: current.map(|e| e.version.clone()),
+                target_version: Some(wanted.version.clone()),
+                install_path: current.and_then(|e| e.install_path.clone()),
+                source: match action {
+                    PlanAction::Unchanged => None,
+                    _ => find_source(path, wanted, &bundled, &catalog),
+                },
+                system: wanted.scope == InstallScope::System,
+            }
+        })
+        .collect();
+
+    if remove_unlisted {
+        let wanted: HashSet<&str> = profile.extensions.iter().map(|e| e.id.as_str()).collect();
+        for ext in installed.iter().filter(|e| !wanted.contains(e.id.as_str())) {
+            items.push(PlanItem {
+                action: PlanAction::Remove,
+                extension_id: ext.id.clone(),
+                name: ext.name.clone(),
+                installed_version: Some(ext.version.clone()),
+                target_version: None,
+                install_path: ext.install_path.clone(),
+                source: None,
+                system: false,
+            });
+        }
+    }
+
+    let debug_mode_change = if profile.debug_mode != installer::get_debug_mode() {
+        Some(profile.debug_mode)
+    } else {
+        None
+    };
+
+    let debug_file_changes = profile
+        .extensions
+        .iter()
+        .filter_map(|wanted| {
+            let extensions = wanted.debug.clone()?;
+            let current = installed
+                .iter()
+                .find(|e| e.id == wanted.id)
+                .map(debug_targets)
+                .unwrap_or_default();
+            (extensions != current).then(|| DebugFileChange {
+                extension_id: wanted.id.clone(),
+                extensions,
+            })
+        })
+        .collect();
+
+    Ok(ImportPlan {
+        profile,
+        items,
+        debug_mode_change,
+        debug_file_changes,
+    })
+}
+
+/// Reconcile this machine with a profile
+pub fn apply_profile_import(path: &str, remove_unlisted: bool) -> Result<ImportReport, String> {
+    let plan = plan_profile_import(path, remove_unlisted)?;
+    let results = plan::execute(&plan.items);
+
+    let debug_mode_changed = match plan.debug_mode_change {
+        Some(enabled) => installer::set_debug_mode(enabled).is_ok(),
+        None => false,
+    };
+
+    // After the installs, so newly installed extensions get their ports too
+    let mut debug_files_changed = Vec::new();
+    let mut debug_file_errors = Vec::new();
+    if !plan.debug_file_changes.is_empty() {
+        let installed = installer::list_extensions();
+        for change in plan.debug_file_changes {
+            let Some(dir) = installed
+                .iter()
+                .find(|e| e.id == change.extension_id)
+                .and_then(|e| e.install_path.clone())
+            else {
+                debug_file_errors.push(format!("'{}' is not installed", change.extension_id));
+                continue;
+            };
+            let written = if change.extensions.is_empty() {
+                debug::remove_debug_file(&dir)
+            } else {
+                debug::write_debug_file(&dir, Some(change.extensions)).map(|_| ())
+            };
+            match written {
+                Ok(()) => debug_files_changed.push(change.extension_id),
+                Err(e) => debug_file_errors.push(format!("{}: {}", change.extension_id, e)),
+            }
+        }
+    }
+
+    Ok(ImportReport {
+        results,
+        debug_mode_changed,
+        debug_files_changed,
+        debug_file_errors,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::{write_zip, write_zxp, Sandbox};
+
+    const ID: &str = "com.test.profile";
+
+    fn install(sandbox: &Sandbox) -> String {
+        let zxp = sandbox.path("in/panel.zxp");
+        write_zxp(&zxp, ID, "1.0.0", &[("index.html", "<html></html>")]);
+        assert!(installer::install_extension(&zxp.to_string_lossy()).success);
+        installer::list_extensions()[0].install_path.clone().unwrap()
+    }
+
+    #[test]
+    fn export_records_debug_ports() {
+        let sandbox = Sandbox::new();
+        let dir = install(&sandbox);
+        let written = debug::write_debug_file(&dir, None).unwrap();
+
+        let path = sandbox.path("profile.zip").to_string_lossy().to_string();
+        let profile = export_profile(&path, false).unwrap();
+        assert_eq!(profile.extensions[0].debug.as_ref(), Some(&written.extensions));
+        assert!(!profile.extensions[0].bundled);
+    }
+
+    #[test]
+    fn import_restores_and_removes_debug_files() {
+        let sandbox = Sandbox::new();
+        let dir = install(&sandbox);
+        debug::write_debug_file(&dir, None).unwrap();
+        let with_debug = sandbox.path("with.zip").to_string_lossy().to_string();
+        export_profile(&with_debug, false).unwrap();
+        debug::remove_debug_file(&dir).unwrap();
+        let without_debug = sandbox.path("without.zip").to_string_lossy().to_string();
+        export_profile(&without_debug, false).unwrap();
+
+        let plan = plan_profile_import(&with_debug, false).unwrap();
+        assert_eq!(plan.items[0].action, PlanAction::Unchanged);
+        assert_eq!(plan.debug_file_changes.len(), 1);
+        let report = apply_profile_import(&with_debug, false).unwrap();
+        assert_eq!(report.debug_files_changed, vec![ID]);
+        assert!(Path::new(&dir).join(".debug").is_file());
+        assert!(plan_profile_import(&with_debug, false).unwrap().debug_file_changes.is_empty());
+
+        let plan = plan_profile_import(&without_debug, false).unwrap();
+        assert!(plan.debug_file_changes[0].extensions.is_empty());
+        apply_profile_import(&without_debug, false).unwrap();
+        assert!(!Path::new(&dir).join(".debug").exists());
+    }
+
+    #[test]
+    fn older_profiles_leave_debug_files_alone() {
+        let sandbox = Sandbox::new();
+        let dir = install(&sandbox);
+        debug::write_debug_file(&dir, None).unwrap();
+        let json = format!(
+            r#"{{"format":1,"created_at":0,"debug_mode":false,"extensions":[{{"id":"{}","name":"Test","version":"1.0.0","scope":"user","bundled":false}}]}}"#,
+            ID
+        );
+        let path = sandbox.path("old.zip");
+        write_zip(&path, &[(PROFILE_ENTRY, &json)]);
+
+        let plan = plan_profile_import(&path.to_string_lossy(), false).unwrap();
+        assert!(plan.profile.extensions[0].debug.is_none());
+        assert!(plan.debug_file_changes.is_empty());
+    }
+
+    #[test]
+    fn profile_scope_follows_install_folder() {
+        let sandbox = Sandbox::new();
+        install(&sandbox);
+        let path = sandbox.path("profile.zip").to_string_lossy().to_string();
+        let profile = export_profile(&path, true).unwrap();
+        assert_eq!(profile.extensions[0].scope, InstallScope::User);
+        assert!(profile.extensions[0].bundled);
+        let plan = plan_profile_import(&path, false).unwrap();
+        assert!(!plan.items[0].system);
+    }
+}
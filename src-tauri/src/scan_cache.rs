@@ -0,0 +1,259 @@
+use crate::installer::{self, ExtensionInfo};
+use crate::integrity;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::{Mutex, OnceLock};
+use std::time::UNIX_EPOCH;
+use tauri::{AppHandle, Emitter};
+
+/// Cheap fingerprint of an extension directory: a few stats plus a hash of the
+/// manifest. Any install, removal or manifest edit changes at least one of
+/// these. Edits deeper in the tree (size, file count, script scan) are picked
+/// up by `refresh_details` in the background revalidate pass.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+struct Fingerprint {
+    dir_mtime: u64,
+    csxs_mtime: u64,
+    manifest_mtime: u64,
+    manifest_len: u64,
+    #[serde(default)]
+    manifest_sha256: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct CachedDir {
+    fingerprint: Fingerprint,
+    /// None if the directory is not a valid extension
+    info: Option<ExtensionInfo>,
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct ScanIndex {
+    /// Display language the cached entries were localized for
+    #[serde(default)]
+    language: String,
+    dirs: HashMap<PathBuf, CachedDir>,
+    /// Result of the last full listing, served while a rescan runs
+    last_listing: Option<Vec<ExtensionInfo>>,
+}
+
+fn index_path() -> PathBuf {
+    installer::get_data_folder().join("scan-index.json")
+}
+
+fn index() -> &'static Mutex<ScanIndex> {
+    static INDEX: OnceLock<Mutex<ScanIndex>> = OnceLock::new();
+    INDEX.get_or_init(|| {
+        let loaded = fs::read_to_string(index_path())
+            .ok()
+            .and_then(|content| serde_json::from_str(&content).ok())
+            .unwrap_or_default();
+        Mutex::new(loaded)
+    })
+}
+
+fn save_index(index: &ScanIndex) {
+    let _ = fs::create_dir_all(installer::get_data_folder());
+    match serde_json::to_string(index) {
+        Ok(json) => {
+            if let Err(e) = fs::write(index_path(), json) {
+                log::warn!("Cannot save scan index: {}", e);
+            }
+        }
+        Err(e) => log::warn!("Cannot serialize scan index: {}", e),
+    }
+}
+
+fn mtime_nanos(path: &Path) -> Option<(u64, u64)> {
+    let meta = fs::metadata(path).ok()?;
+    let mtime = meta
+        .modified()
+        .ok()?
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_nanos() as u64)
+        .unwrap_or(0);
+    Some((mtime, meta.len()))
+}
+
+fn fingerprint(dir: &Path) -> Fingerprint {
+    let csxs = dir.join("CSXS");
+    let manifest = csxs.join("manifest.xml");
+    let (manifest_mtime, manifest_len) = mtime_nanos(&manifest).unwrap_or((0, 0));
+    Fingerprint {
+        dir_mtime: mtime_nanos(dir).map(|(m, _)| m).unwrap_or(0),
+        csxs_mtime: mtime_nanos(&csxs).map(|(m, _)| m).unwrap_or(0),
+        manifest_mtime,
+        manifest_len,
+        manifest_sha256: integrity::sha256_file(&manifest).unwrap_or_default(),
+    }
+}
+
+/// Parse directories on a few worker threads — manifest reads dominate on network profiles
+fn parse_parallel(dirs: Vec<(PathBuf, Fingerprint)>, language: &str) -> Vec<(PathBuf, CachedDir)> {
+    let parse = |(dir, fingerprint): &(PathBuf, Fingerprint)| {
+        let info = installer::read_extension_dir(dir, language);
+        (dir.clone(), CachedDir { fingerprint: fingerprint.clone(), info })
+    };
+    if dirs.len() < 2 {
+        return dirs.iter().map(parse).collect();
+    }
+
+    let workers = std::thread::available_parallelism()
+        .map(|n| n.get())
+        .unwrap_or(4)
+        .min(8);
+    let chunk_size = dirs.len().div_ceil(workers);
+    std::thread::scope(|scope| {
+        let handles: Vec<_> = dirs
+            .chunks(chunk_size)
+            .map(|chunk| scope.spawn(move || chunk.iter().map(parse).collect::<Vec<_>>()))
+            .collect();
+        handles
+            .into_iter()
+            .flat_map(|h| h.join().unwrap_or_default())
+            .collect()
+    })
+}
+
+/// Read extension directories, re-parsing only those whose fingerprint changed
+/// (or all of them when the display language changed)
+pub fn read_dirs(dirs: &[PathBuf], language: &str) -> Vec<Option<ExtensionInfo>> {
+    let stale: Vec<(PathBuf, Fingerprint)> = {
+        let mut index = index().lock().unwrap_or_else(|e| e.into_inner());
+        if index.language != language {
+            index.dirs.clear();
+            index.language = language.to_string();
+        }
+        dirs.iter()
+            .map(|d| (d.clone(), fingerprint(d)))
+            .filter(|(d, fp)| index.dirs.get(d).map(|c| &c.fingerprint != fp).unwrap_or(true))
+            .collect()
+    };
+    let parsed = parse_parallel(stale, language);
+
+    let mut index = index().lock().unwrap_or_else(|e| e.into_inner());
+    index.dirs.extend(parsed);
+    // Forget directories that vanished from the roots that were scanned
+    let roots: Vec<&Path> = dirs.iter().filter_map(|d| d.parent()).collect();
+    index
+        .dirs
+        .retain(|d, _| dirs.contains(d) || !d.parent().map(|p| roots.contains(&p)).unwrap_or(false));
+
+    dirs.iter()
+        .map(|d| index.dirs.get(d).and_then(|c| c.info.clone()))
+        .collect()
+}
+
+/// Re-read every cached directory in full. The fingerprint only covers the
+/// manifest, so this is what notices edits deeper down.
+fn refresh_details() {
+    let (dirs, language) = {
+        let index = index().lock().unwrap_or_else(|e| e.into_inner());
+        let dirs: Vec<PathBuf> = index.dirs.keys().cloned().collect();
+        (dirs, index.language.clone())
+    };
+    let stale = dirs.into_iter().map(|d| {
+        let fingerprint = fingerprint(&d);
+        (d, fingerprint)
+    });
+    let parsed = parse_parallel(stale.collect(), &language);
+
+    let mut index = index().lock().unwrap_or_else(|e| e.into_inner());
+    if index.language == language {
+        index.dirs.extend(parsed);
+    }
+}
+
+/// Remember a full listing so the next launch can show it immediately
+pub fn store_listing(listing: &[ExtensionInfo]) {
+    let mut index = index().lock().unwrap_or_else(|e| e.into_inner());
+    index.last_listing = Some(listing.to_vec());
+    save_index(&index);
+}
+
+/// Stale-while-revalidate listing: return the last known result right away and
+/// rescan in the background, emitting `extensions-refreshed` if anything changed
+pub fn list_extensions_cached(app: AppHandle) -> Vec<ExtensionInfo> {
+    let cached = index()
+        .lock()
+        .unwrap_or_else(|e| e.into_inner())
+        .last_listing
+        .clone();
+
+    match cached {
+        Some(stale) => {
+            let previous = stale.clone();
+            std::thread::spawn(move || {
+                refresh_details();
+                let fresh = installer::list_extensions();
+                if fresh != previous {
+                    if let Err(e) = app.emit("extensions-refreshed", fresh) {
+                        log::warn!("Cannot emit extensions-refreshed: {}", e);
+                    }
+                }
+            });
+            stale
+        }
+        None => installer::list_extensions(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::{manifest, Sandbox};
+
+    fn panel(sandbox: &Sandbox, id: &str) -> PathBuf {
+        let dir = sandbox.path("extensions").join(id);
+        fs::create_dir_all(dir.join("CSXS")).unwrap();
+        fs::create_dir_all(dir.join("js")).unwrap();
+        fs::write(dir.join("CSXS/manifest.xml"), manifest(id, "1.0.0")).unwrap();
+        fs::write(dir.join("js/main.js"), "main();").unwrap();
+        dir
+    }
+
+    fn read_one(dir: &Path) -> ExtensionInfo {
+        read_dirs(&[dir.to_path_buf()], "en_US").remove(0).unwrap()
+    }
+
+    #[test]
+    fn deep_edits_wait_for_refresh() {
+        let sandbox = Sandbox::new();
+        let dir = panel(&sandbox, "com.test.deep");
+        assert_eq!(read_one(&dir).file_count, 2);
+
+        // Neither the top folder nor the manifest changes
+        fs::write(dir.join("js/more.js"), "more();").unwrap();
+        assert_eq!(read_one(&dir).file_count, 2);
+
+        refresh_details();
+        assert_eq!(read_one(&dir).file_count, 3);
+    }
+
+    #[test]
+    fn same_size_manifest_edit_is_noticed() {
+        let sandbox = Sandbox::new();
+        let dir = panel(&sandbox, "com.test.edit");
+        assert_eq!(read_one(&dir).version, "1.0.0");
+
+        let path = dir.join("CSXS/manifest.xml");
+        let modified = fs::metadata(&path).unwrap().modified().unwrap();
+        fs::write(&path, manifest("com.test.edit", "2.0.0")).unwrap();
+        fs::File::options().write(true).open(&path).unwrap().set_modified(modified).unwrap();
+        assert_eq!(read_one(&dir).version, "2.0.0");
+    }
+
+    #[test]
+    fn new_and_removed_dirs_are_noticed() {
+        let sandbox = Sandbox::new();
+        let dir = panel(&sandbox, "com.test.gone");
+        let dirs = [dir.clone()];
+        assert!(read_dirs(&dirs, "en_US")[0].is_some());
+        fs::remove_dir_all(&dir).unwrap();
+        assert!(read_dirs(&dirs, "en_US")[0].is_none());
+        panel(&sandbox, "com.test.gone");
+        assert!(read_dirs(&dirs, "en_US")[0].is_some());
+    }
+}
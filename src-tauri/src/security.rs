@@ -0,0 +1,240 @@
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::Path;
+use walkdir::WalkDir;
+
+/// Bundled scripts larger than this are skipped (minified bundles rarely get there)
+const MAX_SCAN_BYTES: u64 = 8 * 1024 * 1024;
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RiskLevel {
+    #[default]
+    Low,
+    Medium,
+    High,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SecurityFinding {
+    pub level: RiskLevel,
+    pub message: String,
+}
+
+/// What a panel is allowed to do, from its manifest and a scan of its bundled scripts
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct SecurityReport {
+    /// Highest level among the findings
+    pub level: RiskLevel,
+    pub findings: Vec<SecurityFinding>,
+    /// ExtendScript files loaded into the host app
+    pub script_paths: Vec<String>,
+    /// `<MainPath>` values that load a remote page
+    pub remote_urls: Vec<String>,
+    /// Bundled files that reference networking APIs
+    pub network_files: Vec<String>,
+    /// Bundled files that spawn processes or shell commands
+    pub process_files: Vec<String>,
+}
+
+/// CEF switches worth calling out, with why
+const CEF_RISKS: &[(&str, RiskLevel, &str)] = &[
+    ("--enable-nodejs", RiskLevel::High, "Node.js enabled: full file system and process access"),
+    ("--mixed-context", RiskLevel::Medium, "Mixed context: page scripts share the Node.js context"),
+    ("--disable-web-security", RiskLevel::High, "Web security disabled: same-origin policy is off"),
+    ("--allow-running-insecure-content", RiskLevel::Medium, "Allows insecure (http) content on secure pages"),
+    ("--allow-file-access-from-files", RiskLevel::Medium, "Local pages may read other local files"),
+    ("--allow-file-access", RiskLevel::Medium, "Local pages may read other local files"),
+    ("--remote-debugging-port", RiskLevel::Medium, "Opens a remote debugging port"),
+];
+
+const NETWORK_PATTERNS: &[&str] = &[
+    "require('http')",
+    "require(\"http\")",
+    "require('https')",
+    "require(\"https\")",
+    "require('net')",
+    "require(\"net\")",
+    "require('dgram')",
+    "require(\"dgram\")",
+    "XMLHttpRequest",
+    "fetch(",
+    "new WebSocket(",
+    // ExtendScript's raw TCP socket
+    "new Socket",
+];
+
+const PROCESS_PATTERNS: &[&str] = &[
+    "child_process",
+    "cep.process.createProcess",
+    "system.callSystem(",
+];
+
+fn is_script(name: &str) -> bool {
+    let lower = name.to_lowercase();
+    [".js", ".mjs", ".cjs", ".jsx", ".html", ".htm"]
+        .iter()
+        .any(|ext| lower.ends_with(ext))
+}
+
+fn is_remote(path: &str) -> bool {
+    let lower = path.to_lowercase();
+    lower.starts_with("http://") || lower.starts_with("https://")
+}
+
+impl SecurityReport {
+    /// Findings that follow from the manifest alone
+    pub fn from_manifest(cef_parameters: &[String], main_paths: &[String], script_paths: &[String]) -> Self {
+        let mut report = SecurityReport {
+            script_paths: script_paths.to_vec(),
+            remote_urls: main_paths.iter().filter(|p| is_remote(p)).cloned().collect(),
+            ..Default::default()
+        };
+
+        for (switch, level, message) in CEF_RISKS {
+            // Switches may carry a value, e.g. --remote-debugging-port=8088
+            if cef_parameters.iter().any(|p| p == switch || p.starts_with(&format!("{}=", switch))) {
+                report.add(*level, message.to_string());
+            }
+        }
+        let node_enabled = cef_parameters.iter().any(|p| p == "--enable-nodejs");
+        for url in report.remote_urls.clone() {
+            // Remote code with Node.js access can do anything the user can
+            let level = if node_enabled { RiskLevel::High } else { RiskLevel::Medium };
+            report.add(level, format!("Loads its UI from a remote URL: {}", url));
+        }
+        for script in script_paths {
+            report.add(RiskLevel::Low, format!("Runs ExtendScript in the host app: {}", script));
+        }
+        report
+    }
+
+    fn add(&mut self, level: RiskLevel, message: String) {
+        self.level = self.level.max(level);
+        self.findings.push(SecurityFinding { level, message });
+    }
+
+    /// Look for network and process APIs in one bundled file
+    pub fn scan_source(&mut self, rel_path: &str, content: &str) {
+        if !is_script(rel_path) {
+            return;
+        }
+        if NETWORK_PATTERNS.iter().any(|p| content.contains(p)) && !self.network_files.iter().any(|f| f == rel_path) {
+            self.network_files.push(rel_path.to_string());
+        }
+        if PROCESS_PATTERNS.iter().any(|p| content.contains(p)) && !self.process_files.iter().any(|f| f == rel_path) {
+            self.process_files.push(rel_path.to_string());
+        }
+    }
+
+    /// Turn the scanned files into findings; call once after all `scan_source` calls
+    pub fn finish_scan(&mut self) {
+        // Archive and directory order differ — keep reports comparable
+        self.network_files.sort();
+        self.process_files.sort();
+        if !self.process_files.is_empty() {
+            let message = format!("Spawns processes or shell commands ({})", summarize(&self.process_files));
+            self.add(RiskLevel::High, message);
+        }
+        if !self.network_files.is_empty() {
+            let message = format!("Uses network APIs ({})", summarize(&self.network_files));
+            self.add(RiskLevel::Medium, message);
+        }
+    }
+}
+
+fn summarize(files: &[String]) -> String {
+    match files.len() {
+        1 => files[0].clone(),
+        n => format!("{} and {} more", files[0], n - 1),
+    }
+}
+
+/// Scan the bundled scripts of an installed extension
+pub fn scan_dir(report: &mut SecurityReport, dir: &Path) {
+    for entry in WalkDir::new(dir).into_iter().flatten() {
+        let name = entry.file_name().to_string_lossy();
+        if !entry.file_type().is_file() || !is_script(&name) {
+            continue;
+        }
+        if entry.metadata().map(|m| m.len() > MAX_SCAN_BYTES).unwrap_or(true) {
+            continue;
+        }
+        if let Ok(bytes) = fs::read(entry.path()) {
+            let rel = entry
+                .path()
+                .strip_prefix(dir)
+                .unwrap_or(entry.path())
+                .to_string_lossy()
+                .replace('\\', "/");
+            report.scan_source(&rel, &String::from_utf8_lossy(&bytes));
+        }
+    }
+    report.finish_scan();
+}
+
+/// Whether an archive entry should be read for `scan_source`
+pub fn should_scan(name: &str, size: u64) -> bool {
+    is_script(name) && size <= MAX_SCAN_BYTES
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn strings(values: &[&str]) -> Vec<String> {
+        values.iter().map(|v| v.to_string()).collect()
+    }
+
+    #[test]
+    fn manifest_switches_and_remote_pages_raise_the_level() {
+        let report = SecurityReport::from_manifest(
+            &strings(&["--mixed-context", "--remote-debugging-port=8088"]),
+            &strings(&["./index.html", "HTTPS://example.com/panel"]),
+            &strings(&["./host.jsx"]),
+        );
+        assert_eq!(report.level, RiskLevel::Medium);
+        assert_eq!(report.remote_urls, strings(&["HTTPS://example.com/panel"]));
+        assert_eq!(report.findings.len(), 4);
+
+        // Remote code with Node.js is the worst case
+        let report = SecurityReport::from_manifest(&strings(&["--enable-nodejs"]), &strings(&["https://x.test"]), &[]);
+        assert_eq!(report.level, RiskLevel::High);
+        assert!(report.findings.iter().all(|f| f.level == RiskLevel::High));
+
+        let report = SecurityReport::from_manifest(&strings(&["--enable-nodejs-later"]), &strings(&["./index.html"]), &[]);
+        assert_eq!(report.level, RiskLevel::Low);
+        assert!(report.findings.is_empty());
+    }
+
+    #[test]
+    fn scan_finds_network_and_process_apis_in_scripts_only() {
+        let mut report = SecurityReport::default();
+        report.scan_source("js/b.js", "const https = require('https');");
+        report.scan_source("js/a.js", "fetch('/api')");
+        report.scan_source("js/a.js", "fetch('/again')");
+        report.scan_source("host/run.jsx", "system.callSystem('ls')");
+        report.scan_source("notes.txt", "require('child_process')");
+        report.finish_scan();
+
+        assert_eq!(report.network_files, strings(&["js/a.js", "js/b.js"]));
+        assert_eq!(report.process_files, strings(&["host/run.jsx"]));
+        assert_eq!(report.level, RiskLevel::High);
+        assert!(report.findings.iter().any(|f| f.message == "Uses network APIs (js/a.js and 1 more)"));
+    }
+
+    #[test]
+    fn scan_dir_uses_forward_slash_paths() {
+        let dir = std::env::temp_dir().join(format!("zxp-magic-security-{}", std::process::id()));
+        fs::create_dir_all(dir.join("js")).unwrap();
+        fs::write(dir.join("js/main.js"), "new WebSocket(url)").unwrap();
+        fs::write(dir.join("readme.md"), "fetch(").unwrap();
+
+        let mut report = SecurityReport::default();
+        scan_dir(&mut report, &dir);
+        let _ = fs::remove_dir_all(&dir);
+        assert_eq!(report.network_files, strings(&["js/main.js"]));
+        assert!(should_scan("a.HTML", MAX_SCAN_BYTES));
+        assert!(!should_scan("a.js", MAX_SCAN_BYTES + 1));
+    }
+}
@@ -0,0 +1,105 @@
+use crate::installer;
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::PathBuf;
+
+/// Persistent app settings, stored as JSON in the data folder
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Settings {
+    /// Repository index locations (local path, file share or http(s) URL)
+    pub catalog_sources: Vec<String>,
+    /// Folders of ZXP files checked for newer versions of installed extensions
+    pub update_folders: Vec<String>,
+    pub filter_rules: FilterRules,
+    /// Language for localized extension names (e.g. "ja_JP"); None follows the system
+    pub language: Option<String>,
+    pub extraction_limits: ExtractionLimits,
+    /// Days uninstalled extensions stay restorable; 0 keeps them until the trash is emptied
+    pub trash_retention_days: u64,
+}
+
+impl Default for Settings {
+    fn default() -> Self {
+        Settings {
+            catalog_sources: Vec::new(),
+            update_folders: Vec::new(),
+            filter_rules: FilterRules::default(),
+            language: None,
+            extraction_limits: ExtractionLimits::default(),
+            trash_retention_days: 30,
+        }
+    }
+}
+
+/// Which installed extensions are treated as built-in or hidden from the list
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct FilterRules {
+    /// Id prefixes of vendor-bundled extensions (case-insensitive)
+    pub builtin_id_prefixes: Vec<String>,
+    /// Only treat prefix matches as built-in when they live in a system-level folder
+    pub builtin_system_only: bool,
+    /// Extension roots whose contents are always hidden
+    pub hidden_folders: Vec<String>,
+    /// Authors whose extensions are hidden (case-insensitive)
+    pub hidden_vendors: Vec<String>,
+    /// Ids that are always shown, even when a rule above matches
+    pub always_show_ids: Vec<String>,
+}
+
+impl Default for FilterRules {
+    fn default() -> Self {
+        FilterRules {
+            // Adobe's own panels live in the system folders under com.adobe.
+            builtin_id_prefixes: vec!["com.adobe.".to_string()],
+            builtin_system_only: true,
+            hidden_folders: Vec::new(),
+            hidden_vendors: Vec::new(),
+            always_show_ids: Vec::new(),
+        }
+    }
+}
+
+/// Guards against zip bombs and corrupt archives when extracting packages
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ExtractionLimits {
+    /// Uncompressed bytes across the whole archive
+    pub max_total_bytes: u64,
+    pub max_entries: u64,
+    pub max_file_bytes: u64,
+    /// Highest uncompressed:compressed ratio allowed for a single file
+    pub max_compression_ratio: u64,
+}
+
+impl Default for ExtractionLimits {
+    fn default() -> Self {
+        // Generous for real panels, which rarely exceed a few hundred MB
+        ExtractionLimits {
+            max_total_bytes: 4 * 1024 * 1024 * 1024,
+            max_entries: 100_000,
+            max_file_bytes: 2 * 1024 * 1024 * 1024,
+            max_compression_ratio: 200,
+        }
+    }
+}
+
+fn settings_path() -> PathBuf {
+    installer::get_data_folder().join("settings.json")
+}
+
+/// Load settings, falling back to defaults if the file is missing or unreadable
+pub fn load_settings() -> Settings {
+    fs::read_to_string(settings_path())
+        .ok()
+        .and_then(|content| serde_json::from_str(&content).ok())
+        .unwrap_or_default()
+}
+
+pub fn save_settings(settings: &Settings) -> Result<(), String> {
+    fs::create_dir_all(installer::get_data_folder())
+        .map_err(|e| format!("Cannot create data folder: {}", e))?;
+    let json = serde_json::to_string_pretty(settings).map_err(|e| e.to_string())?;
+    fs::write(settings_path(), json).map_err(|e| format!("Cannot save settings: {}", e))
+}
@@ -0,0 +1,79 @@
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::Path;
+
+/// File name of the optional ZXP Magic descriptor shipped in an extension's root
+pub const SIDECAR_FILE: &str = "zxp-magic.json";
+
+/// Vendor-provided extras that CEP's manifest has no place for, e.g.
+///
+/// ```json
+/// {
+///   "update_feed": "https://vendor.example/panels/feed.json",
+///   "dependencies": [
+///     { "kind": "host_plugin", "file": "VendorFX.aex", "hosts": ["AEFT"], "bundled": "companions/VendorFX.aex" },
+///     { "kind": "startup_script", "file": "vendor_startup.jsx", "hosts": ["PHXS"] },
+///     { "kind": "font", "file": "VendorIcons.otf", "optional": true },
+///     { "kind": "extension", "id": "com.vendor.core", "version": ">=2.0" }
+///   ]
+/// }
+/// ```
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Sidecar {
+    /// Catalog-format index the vendor publishes new versions to
+    pub update_feed: Option<String>,
+    /// Companion items the panel needs outside its own folder
+    pub dependencies: Vec<Companion>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CompanionKind {
+    /// Native host plug-in (`.aex`, `.prm`, `.8bf`, `.aip`…)
+    HostPlugin,
+    /// ExtendScript run when the host starts
+    StartupScript,
+    Font,
+    /// Another CEP extension
+    Extension,
+}
+
+/// Something a panel needs that lives outside its CEP folder
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Companion {
+    pub kind: CompanionKind,
+    /// Display name; defaults to the file name or extension id
+    #[serde(default)]
+    pub name: Option<String>,
+    /// File name to look for (plug-ins, startup scripts, fonts)
+    #[serde(default)]
+    pub file: Option<String>,
+    /// Extension id (extension companions)
+    #[serde(default)]
+    pub id: Option<String>,
+    /// Version constraint for extension companions, e.g. ">=2.0"
+    #[serde(default)]
+    pub version: Option<String>,
+    /// Host codes or names it belongs to (AEFT, PPRO, PHXS…); empty means any
+    #[serde(default)]
+    pub hosts: Vec<String>,
+    /// Relative path of a copy shipped inside the extension that can be installed
+    #[serde(default)]
+    pub bundled: Option<String>,
+    /// Nice to have; the panel works without it
+    #[serde(default)]
+    pub optional: bool,
+}
+
+/// Read the sidecar from an extension folder, if it ships one
+pub fn load_sidecar(extension_dir: &Path) -> Option<Sidecar> {
+    let content = fs::read_to_string(extension_dir.join(SIDECAR_FILE)).ok()?;
+    match serde_json::from_str(&content) {
+        Ok(sidecar) => Some(sidecar),
+        Err(e) => {
+            log::warn!("Ignoring invalid {} in {}: {}", SIDECAR_FILE, extension_dir.display(), e);
+            None
+        }
+    }
+}
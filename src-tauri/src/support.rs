@@ -0,0 +1,354 @@
+use crate::dependencies;
+use crate::installer;
+use crate::uxp;
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::io::{Read, Seek, SeekFrom, Write};
+use std::path::{Path, PathBuf};
+use std::time::SystemTime;
+use zip::write::SimpleFileOptions;
+use zip::{CompressionMethod, ZipWriter};
+
+/// What goes into a support bundle
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct SupportBundleOptions {
+    /// Replace the home folder, user name and computer name in every file
+    pub redact: bool,
+    /// Budget for CEP logs, newest first; larger files keep only their end
+    pub cep_log_limit_mb: u64,
+    /// Same for this app's own logs
+    pub app_log_limit_mb: u64,
+}
+
+impl Default for SupportBundleOptions {
+    fn default() -> Self {
+        SupportBundleOptions {
+            redact: false,
+            cep_log_limit_mb: 5,
+            app_log_limit_mb: 2,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SupportBundle {
+    pub path: String,
+    /// Entry names written to the ZIP
+    pub entries: Vec<String>,
+    pub redacted: bool,
+}
+
+/// PlayerDebugMode and LogLevel of one CSXS version
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CsxsSettings {
+    pub version: String,
+    pub player_debug_mode: Option<String>,
+    pub log_level: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct SystemInfo {
+    app_version: String,
+    os: String,
+    os_version: Option<String>,
+    arch: String,
+    created_at: u64,
+    debug_mode: bool,
+    extension_folders: Vec<String>,
+    data_folder: String,
+}
+
+/// Replaces the user's identity in bundle text
+struct Redactor {
+    /// (needle, replacement), longest first
+    rules: Vec<(String, String)>,
+}
+
+impl Redactor {
+    fn new(enabled: bool) -> Self {
+        let mut rules = Vec::new();
+        if enabled {
+            let home = std::env::var("HOME").or_else(|_| std::env::var("USERPROFILE")).unwrap_or_default();
+            if home.len() > 1 {
+                rules.push((home.clone(), "~".to_string()));
+                rules.push((home.replace('\\', "/"), "~".to_string()));
+                rules.push((home.replace('\\', "\\\\"), "~".to_string()));
+            }
+            // Short names would match ordinary words, so those are only replaced as path segments
+            for (var, placeholder) in [("USER", "<user>"), ("USERNAME", "<user>"), ("COMPUTERNAME", "<computer>"), ("HOSTNAME", "<computer>")] {
+                let Ok(value) = std::env::var(var) else {
+                    continue;
+                };
+                if value.is_empty() {
+                    continue;
+                }
+                for sep in ["/", "\\", "\\\\"] {
+                    rules.push((format!("{sep}{}{sep}", value), format!("{sep}{}{sep}", placeholder)));
+                }
+                if value.len() >= 4 {
+                    rules.push((value, placeholder.to_string()));
+                }
+            }
+        }
+        rules.sort_by_key(|(needle, _)| std::cmp::Reverse(needle.len()));
+        Redactor { rules }
+    }
+
+    fn apply(&self, text: &str) -> String {
+        self.rules
+            .iter()
+            .fold(text.to_string(), |text, (needle, replacement)| text.replace(needle, replacement))
+    }
+}
+
+struct BundleWriter {
+    zip: ZipWriter<fs::File>,
+    redactor: Redactor,
+    entries: Vec<String>,
+}
+
+impl BundleWriter {
+    fn add(&mut self, name: &str, content: &str) -> Result<(), String> {
+        let name = self.redactor.apply(name);
+        let options = SimpleFileOptions::default().compression_method(CompressionMethod::Deflated);
+        self.zip.start_file(name.as_str(), options).map_err(|e| e.to_string())?;
+        self.zip
+            .write_all(self.redactor.apply(content).as_bytes())
+            .map_err(|e| format!("Cannot write {}: {}", name, e))?;
+        self.entries.push(name);
+        Ok(())
+    }
+
+    fn add_json<T: Serialize>(&mut self, name: &str, value: &T) -> Result<(), String> {
+        let json = serde_json::to_string_pretty(value).map_err(|e| e.to_string())?;
+        self.add(name, &json)
+    }
+}
+
+/// PlayerDebugMode/LogLevel for every known CSXS version
+pub fn csxs_settings() -> Vec<CsxsSettings> {
+    installer::CSXS_VERSIONS
+        .iter()
+        .map(|version| {
+            #[cfg(target_os = "windows")]
+            let (player_debug_mode, log_level) = {
+                use winreg::enums::HKEY_CURRENT_USER;
+                use winreg::RegKey;
+                let key = RegKey::predef(HKEY_CURRENT_USER)
+                    .open_subkey(format!("SOFTWARE\\Adobe\\CSXS.{}", version))
+                    .ok();
+                let read = |name: &str| key.as_ref().and_then(|k| k.get_value::<String, _>(name).ok());
+                (read("PlayerDebugMode"), read("LogLevel"))
+            };
+            #[cfg(target_os = "macos")]
+            let (player_debug_mode, log_level) = {
+                let home = std::env::var("HOME").unwrap_or_default();
+                let plist = format!("{}/Library/Preferences/com.adobe.CSXS.{}.plist", home, version);
+                let read = |name: &str| {
+                    std::process::Command::new("defaults")
+                        .args(["read", &plist, name])
+                        .output()
+                        .ok()
+                        .filter(|output| output.status.success())
+                        .map(|output| String::from_utf8_lossy(&output.stdout).trim().to_string())
+                };
+                (read("PlayerDebugMode"), read("LogLevel"))
+            };
+            #[cfg(not(any(target_os = "windows", target_os = "macos")))]
+            let (player_debug_mode, log_level) = (None, None);
+
+            CsxsSettings {
+                version: version.to_string(),
+                player_debug_mode,
+                log_level,
+            }
+        })
+        .collect()
+}
+
+fn os_version() -> Option<String> {
+    #[cfg(target_os = "windows")]
+    let output = std::process::Command::new("cmd").args(["/C", "ver"]).output();
+    #[cfg(target_os = "macos")]
+    let output = std::process::Command::new("sw_vers").arg("-productVersion").output();
+    #[cfg(not(any(target_os = "windows", target_os = "macos")))]
+    let output = std::process::Command::new("uname").arg("-r").output();
+    output
+        .ok()
+        .map(|o| String::from_utf8_lossy(&o.stdout).trim().to_string())
+        .filter(|v| !v.is_empty())
+}
+
+/// CEP writes its logs to %TEMP% on Windows and ~/Library/Logs/CSXS on macOS
+fn cep_logs() -> Vec<PathBuf> {
+    #[cfg(target_os = "windows")]
+    let (folder, prefix) = (std::env::temp_dir(), "CEP");
+    #[cfg(target_os = "macos")]
+    let (folder, prefix) = (
+        PathBuf::from(std::env::var("HOME").unwrap_or_default()).join("Library/Logs/CSXS"),
+        "",
+    );
+    #[cfg(not(any(target_os = "windows", target_os = "macos")))]
+    let (folder, prefix) = (PathBuf::new(), "CEP");
+    log_files(&folder, prefix)
+}
+
+/// `.log` files in `folder` starting with `prefix`, newest first
+fn log_files(folder: &Path, prefix: &str) -> Vec<PathBuf> {
+    let Ok(entries) = fs::read_dir(folder) else {
+        return Vec::new();
+    };
+    let mut logs: Vec<(SystemTime, PathBuf)> = entries
+        .flatten()
+        .map(|e| e.path())
+        .filter(|p| p.is_file())
+        .filter(|p| {
+            let name = p.file_name().unwrap_or_default().to_string_lossy().to_string();
+            name.starts_with(prefix) && name.to_lowercase().ends_with(".log")
+        })
+        .map(|p| (fs::metadata(&p).and_then(|m| m.modified()).unwrap_or(SystemTime::UNIX_EPOCH), p))
+        .collect();
+    logs.sort_by_key(|(modified, _)| std::cmp::Reverse(*modified));
+    logs.into_iter().map(|(_, p)| p).collect()
+}
+
+/// The last `max_bytes` of a log file
+fn read_tail(path: &Path, max_bytes: u64) -> Result<String, String> {
+    let mut file = fs::File::open(path).map_err(|e| format!("Cannot open {}: {}", path.display(), e))?;
+    let len = file.metadata().map(|m| m.len()).unwrap_or(0);
+    let start = len.saturating_sub(max_bytes);
+    file.seek(SeekFrom::Start(start)).map_err(|e| e.to_string())?;
+    let mut bytes = Vec::new();
+    file.read_to_end(&mut bytes).map_err(|e| e.to_string())?;
+    let text = String::from_utf8_lossy(&bytes).to_string();
+    Ok(if start > 0 {
+        format!("[… {} earlier bytes omitted]\n{}", start, text)
+    } else {
+        text
+    })
+}
+
+/// Add logs newest first until `limit_mb` is used up
+fn add_logs(writer: &mut BundleWriter, folder: &str, logs: &[PathBuf], limit_mb: u64) -> Result<(), String> {
+    let mut budget = limit_mb * 1024 * 1024;
+    for log in logs {
+        if budget == 0 {
+            break;
+        }
+        let size = fs::metadata(log).map(|m| m.len()).unwrap_or(0).min(budget);
+        match read_tail(log, size) {
+            Ok(text) => {
+                let name = log.file_name().unwrap_or_default().to_string_lossy().to_string();
+                writer.add(&format!("{}/{}", folder, name), &text)?;
+                budget -= size;
+            }
+            Err(e) => log::warn!("Skipping log: {}", e),
+        }
+    }
+    Ok(())
+}
+
+/// Collect everything a vendor asks for when a panel misbehaves into one ZIP
+pub fn create_support_bundle(
+    path: &str,
+    options: &SupportBundleOptions,
+    app_log_dir: Option<PathBuf>,
+) -> Result<SupportBundle, String> {
+    let file = fs::File::create(path).map_err(|e| format!("Cannot create support bundle: {}", e))?;
+    let mut writer = BundleWriter {
+        zip: ZipWriter::new(file),
+        redactor: Redactor::new(options.redact),
+        entries: Vec::new(),
+    };
+
+    let system = SystemInfo {
+        app_version: env!("CARGO_PKG_VERSION").to_string(),
+        os: std::env::consts::OS.to_string(),
+        os_version: os_version(),
+        arch: std::env::consts::ARCH.to_string(),
+        created_at: SystemTime::now()
+            .duration_since(SystemTime::UNIX_EPOCH)
+            .map(|d| d.as_secs())
+            .unwrap_or(0),
+        debug_mode: installer::get_debug_mode(),
+        extension_folders: installer::get_all_extension_folders()
+            .iter()
+            .map(|f| f.to_string_lossy().to_string())
+            .collect(),
+        data_folder: installer::get_data_folder().to_string_lossy().to_string(),
+    };
+    writer.add_json("system.json", &system)?;
+    writer.add_json("csxs.json", &csxs_settings())?;
+
+    let products: Vec<String> = dependencies::PRODUCTS.iter().map(|p| p.to_string()).collect();
+    let hosts: Vec<String> = dependencies::app_dirs(&products)
+        .iter()
+        .map(|dir| dir.file_name().unwrap_or_default().to_string_lossy().to_string())
+        .collect();
+    writer.add_json("hosts.json", &hosts)?;
+
+    let extensions = installer::list_extensions_filtered(true, None);
+    writer.add_json("extensions.json", &extensions)?;
+    writer.add_json("uxp_plugins.json", &uxp::list_uxp_plugins())?;
+    for (index, ext) in extensions.iter().enumerate() {
+        let Some(dir) = &ext.install_path else {
+            continue;
+        };
+        let dir = Path::new(dir);
+        // Folder names are unique per root, not across roots
+        let folder = format!("{:02}-{}", index, dir.file_name().unwrap_or_default().to_string_lossy());
+        for file in [PathBuf::from("CSXS").join("manifest.xml"), PathBuf::from(".debug")] {
+            if let Ok(content) = fs::read_to_string(dir.join(&file)) {
+                let name = file.to_string_lossy().replace('\\', "/");
+                writer.add(&format!("manifests/{}/{}", folder, name), &content)?;
+            }
+        }
+    }
+
+    add_logs(&mut writer, "cep_logs", &cep_logs(), options.cep_log_limit_mb)?;
+    if let Some(dir) = app_log_dir {
+        add_logs(&mut writer, "app_logs", &log_files(&dir, ""), options.app_log_limit_mb)?;
+    }
+
+    writer.zip.finish().map_err(|e| format!("Cannot write support bundle: {}", e))?;
+    log::info!("Wrote support bundle with {} files to {}", writer.entries.len(), path);
+    Ok(SupportBundle {
+        path: path.to_string(),
+        entries: writer.entries,
+        redacted: options.redact,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn redactor(rules: &[(&str, &str)]) -> Redactor {
+        Redactor {
+            rules: rules.iter().map(|(n, r)| (n.to_string(), r.to_string())).collect(),
+        }
+    }
+
+    #[test]
+    fn apply_replaces_every_occurrence() {
+        let redactor = redactor(&[("/Users/jane", "~"), ("/jane/", "/<user>/")]);
+        assert_eq!(
+            redactor.apply("/Users/jane/Library/Logs and /Users/jane/Desktop"),
+            "~/Library/Logs and ~/Desktop"
+        );
+        assert_eq!(redactor.apply("/Volumes/jane/x"), "/Volumes/<user>/x");
+    }
+
+    #[test]
+    fn apply_leaves_ordinary_words() {
+        let redactor = redactor(&[("/jane/", "/<user>/")]);
+        assert_eq!(redactor.apply("jane wrote this"), "jane wrote this");
+    }
+
+    #[test]
+    fn disabled_redactor_changes_nothing() {
+        let text = "/Users/someone/Library/Logs/CSXS/CEP12-PHXS.log";
+        assert_eq!(Redactor::new(false).apply(text), text);
+    }
+}
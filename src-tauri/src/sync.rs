@@ -0,0 +1,329 @@
+use crate::catalog::{self, CatalogPackage};
+use crate::installer::{self, ExtensionInfo};
+use crate::integrity;
+use crate::plan::{self, ActionResult, PackageSource, PlanAction, PlanItem};
+use crate::settings;
+use crate::version;
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+use std::fs;
+use std::path::Path;
+
+/// Desired extension state, written as TOML or JSON, e.g.
+///
+/// ```toml
+/// remove_unlisted = true
+///
+/// [[extension]]
+/// id = "com.studio.tool"
+/// version = "^1.4"
+/// source = "\\\\fileserver\\panels\\index.json"
+/// ```
+///
+/// `source` may be a catalog index or a single ZXP (path or URL, with
+/// `sha256` required for URLs). Without one, configured catalogs are searched.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Lockfile {
+    /// Uninstall extensions that are not listed (Adobe's own are always kept)
+    #[serde(default)]
+    pub remove_unlisted: bool,
+    #[serde(default, rename = "extension", alias = "extensions")]
+    pub extensions: Vec<LockedExtension>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LockedExtension {
+    pub id: String,
+    /// Version constraint, see `version::satisfies`
+    #[serde(default = "any_version")]
+    pub version: String,
+    #[serde(default)]
+    pub source: Option<String>,
+    #[serde(default)]
+    pub sha256: Option<String>,
+}
+
+fn any_version() -> String {
+    "*".to_string()
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SyncPlan {
+    pub items: Vec<PlanItem>,
+    /// Entries that need a change but have no package satisfying their constraint
+    pub unresolved: Vec<String>,
+}
+
+impl SyncPlan {
+    /// True when applying would not change anything
+    pub fn is_noop(&self) -> bool {
+        self.items.iter().all(|i| i.action == PlanAction::Unchanged)
+    }
+}
+
+/// Parse a lockfile; `.json` files are read as JSON, everything else as TOML
+pub fn load_lockfile(path: &str) -> Result<Lockfile, String> {
+    let content = fs::read_to_string(path).map_err(|e| format!("Cannot read lockfile: {}", e))?;
+    let is_json = Path::new(path)
+        .extension()
+        .map(|e| e.eq_ignore_ascii_case("json"))
+        .unwrap_or(false);
+    let lockfile: Lockfile = if is_json {
+        serde_json::from_str(&content).map_err(|e| format!("Invalid lockfile: {}", e))?
+    } else {
+        toml::from_str(&content).map_err(|e| format!("Invalid lockfile: {}", e))?
+    };
+
+    for entry in &lockfile.extensions {
+        // Surface constraint typos before anything is planned
+        version::satisfies("0", &entry.version).map_err(|e| format!("{}: {}", entry.id, e))?;
+    }
+    Ok(lockfile)
+}
+
+fn is_package_file(location: &str) -> bool {
+    let lower = location.to_lowercase();
+    lower.ends_with(".zxp") || lower.ends_with(".zxpinstall")
+}
+
+/// Every package version the entry could be installed from
+fn candidates(entry: &LockedExtension) -> Vec<PackageSource> {
+    match &entry.source {
+        Some(location) if is_package_file(location) => {
+            let is_local = !location.starts_with("http://") && !location.starts_with("https://");
+            if is_local {
+                return vec![PackageSource::File { path: location.clone() }];
+            }
+            let Some(sha256) = &entry.sha256 else {
+                log::warn!("{}: sha256 is required for remote packages", entry.id);
+                return Vec::new();
+            };
+            vec![PackageSource::Remote {
+                package: CatalogPackage {
+                    id: entry.id.clone(),
+                    name: entry.id.clone(),
+                    // Unknown until downloaded — trusted to match the constraint
+                    version: String::new(),
+                    description: String::new(),
+                    url: location.clone(),
+                    sha256: sha256.clone(),
+                    hosts: Vec::new(),
+                    changelog: String::new(),
+                },
+            }]
+        }
+        Some(index) => catalog_candidates(&entry.id, std::slice::from_ref(index)),
+        None => catalog_candidates(&entry.id, &settings::load_settings().catalog_sources),
+    }
+}
+
+fn catalog_candidates(extension_id: &str, locations: &[String]) -> Vec<PackageSource> {
+    locations
+        .iter()
+        .filter_map(|location| match catalog::load_catalog(location) {
+            Ok(index) => Some(index.packages),
+            Err(e) => {
+                log::warn!("Skipping catalog {}: {}", location, e);
+                None
+            }
+        })
+        .flatten()
+        .filter(|p| p.id == extension_id)
+        .map(|package| PackageSource::Remote { package })
+        .collect()
+}
+
+/// Version a candidate would install, reading local ZXPs when needed
+fn candidate_version(source: &PackageSource) -> Option<String> {
+    match source {
+        PackageSource::File { path } => installer::get_extension_info_from_zxp(path).ok().map(|i| i.version),
+        PackageSource::Remote { package } if !package.version.is_empty() => Some(package.version.clone()),
+        _ => None,
+    }
+}
+
+/// Newest candidate satisfying the constraint; direct ZXP URLs are taken as-is
+fn best_candidate(entry: &LockedExtension) -> Option<(PackageSource, Option<String>)> {
+    let mut best: Option<(PackageSource, Option<String>)> = None;
+    for source in candidates(entry) {
+        let found = candidate_version(&source);
+        match &found {
+            Some(v) if !version::satisfies(v, &entry.version).unwrap_or(false) => continue,
+            Some(v) => {
+                let better = match &best {
+                    Some((_, Some(b))) => version::is_newer(v, b),
+                    _ => true,
+                };
+                if better {
+                    best = Some((source, found));
+                }
+            }
+            None => {
+                if best.is_none() {
+                    best = Some((source, None));
+                }
+            }
+        }
+    }
+
+    // Fall back to the cached original if it satisfies the constraint
+    if best.is_none() {
+        if let (Ok(cached), Some(manifest)) = (integrity::cached_package_path(&entry.id), integrity::load_manifest(&entry.id)) {
+            if cached.exists() && version::satisfies(&manifest.version, &entry.version).unwrap_or(false) {
+                best = Some((
+                    PackageSource::File { path: cached.to_string_lossy().to_string() },
+                    Some(manifest.version),
+                ));
+            }
+        }
+    }
+    best
+}
+
+fn plan_entry(entry: &LockedExtension, installed: Option<&ExtensionInfo>, unresolved: &mut Vec<String>) -> PlanItem {
+    let in_range = installed
+        .map(|e| version::satisfies(&e.version, &entry.version).unwrap_or(false))
+        .unwrap_or(false);
+
+    let (action, source, target_version) = if in_range {
+        (PlanAction::Unchanged, None, installed.map(|e| e.version.clone()))
+    } else {
+        match best_candidate(entry) {
+            Some((source, target)) => {
+                let action = match &target {
+                    Some(t) => plan::action_for(installed.map(|e| e.version.as_str()), t),
+                    None if installed.is_some() => PlanAction::Upgrade,
+                    None => PlanAction::Install,
+                };
+                (action, Some(source), target)
+            }
+            None => {
+                unresolved.push(format!("{} ({})", entry.id, entry.version));
+                let action = if installed.is_some() { PlanAction::Upgrade } else { PlanAction::Install };
+                (action, None, None)
+            }
+        }
+    };
+
+    PlanItem {
+        action,
+        extension_id: entry.id.clone(),
+        name: installed.map(|e| e.name.clone()).unwrap_or_else(|| entry.id.clone()),
+        installed_version: installed.map(|e| e.version.clone()),
+        target_version,
+        install_path: installed.and_then(|e| e.install_path.clone()),
+        source,
+        system: false,
+    }
+}
+
+/// Work out what `apply_sync` would do; `remove_unlisted` overrides the lockfile's value
+pub fn plan_sync(path: &str, remove_unlisted: Option<bool>) -> Result<SyncPlan, String> {
+    let lockfile = load_lockfile(path)?;
+    let installed = installer::list_extensions();
+    let mut unresolved = Vec::new();
+
+    let mut items: Vec<PlanItem> = lockfile
+        .extensions
+        .iter()
+        .map(|entry| plan_entry(entry, installed.iter().find(|e| e.id == entry.id), &mut unresolved))
+        .collect();
+
+    if remove_unlisted.unwrap_or(lockfile.remove_unlisted) {
+        let listed: HashSet<&str> = lockfile.extensions.iter().map(|e| e.id.as_str()).collect();
+        for ext in installed
+            .iter()
+            .filter(|e| !listed.contains(e.id.as_str()) && !installer::is_adobe_builtin(&e.id))
+        {
+            items.push(PlanItem {
+                action: PlanAction::Remove,
+                extension_id: ext.id.clone(),
+                name: ext.name.clone(),
+                installed_version: Some(ext.version.clone()),
+                target_version: None,
+                install_path: ext.install_path.clone(),
+                source: None,
+                system: false,
+            });
+        }
+    }
+
+    Ok(SyncPlan { items, unresolved })
+}
+
+/// Bring this machine in line with a lockfile
+pub fn apply_sync(path: &str, remove_unlisted: Option<bool>) -> Result<Vec<ActionResult>, String> {
+    let plan = plan_sync(path, remove_unlisted)?;
+    Ok(plan::execute(&plan.items))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::{write_zxp, Sandbox};
+
+    #[test]
+    fn lockfiles_parse_as_toml_or_json() {
+        let sandbox = Sandbox::new();
+        let toml = sandbox.path("panels.toml");
+        fs::write(&toml, "remove_unlisted = true\n\n[[extension]]\nid = \"com.test.a\"\nversion = \"^1.4\"\n").unwrap();
+        let lockfile = load_lockfile(&toml.to_string_lossy()).unwrap();
+        assert!(lockfile.remove_unlisted);
+        assert_eq!(lockfile.extensions[0].version, "^1.4");
+
+        let json = sandbox.path("panels.JSON");
+        fs::write(&json, r#"{"extensions":[{"id":"com.test.a"}]}"#).unwrap();
+        let lockfile = load_lockfile(&json.to_string_lossy()).unwrap();
+        assert!(!lockfile.remove_unlisted);
+        assert_eq!(lockfile.extensions[0].version, "*");
+
+        fs::write(&toml, "[[extension]]\nid = \"com.test.a\"\nversion = \">=\"\n").unwrap();
+        let error = load_lockfile(&toml.to_string_lossy()).unwrap_err();
+        assert!(error.starts_with("com.test.a: "), "{}", error);
+    }
+
+    #[test]
+    fn sync_picks_the_newest_matching_package_and_removes_unlisted() {
+        let sandbox = Sandbox::new();
+        for (id, version) in [("com.test.tool", "1.0.0"), ("com.test.extra", "1.0.0")] {
+            let zxp = sandbox.path(&format!("in/{}.zxp", id));
+            write_zxp(&zxp, id, version, &[]);
+            assert!(installer::install_extension(&zxp.to_string_lossy()).success);
+        }
+        let mut packages = Vec::new();
+        for version in ["2.0.0", "2.1.0", "3.0.0"] {
+            let zxp = sandbox.path(&format!("repo/tool-{}.zxp", version));
+            write_zxp(&zxp, "com.test.tool", version, &[]);
+            packages.push(serde_json::json!({ "id": "com.test.tool", "version": version,
+                "url": format!("tool-{}.zxp", version), "sha256": integrity::sha256_file(&zxp).unwrap() }));
+        }
+        let index = sandbox.path("repo/index.json");
+        fs::write(&index, serde_json::json!({ "packages": packages }).to_string()).unwrap();
+        let lockfile = sandbox.path("panels.toml");
+        fs::write(
+            &lockfile,
+            format!(
+                "[[extension]]\nid = \"com.test.tool\"\nversion = \"^2\"\nsource = {:?}\n\n[[extension]]\nid = \"com.test.absent\"\nsource = {:?}\n",
+                index.to_string_lossy(),
+                index.to_string_lossy()
+            ),
+        )
+        .unwrap();
+        let path = lockfile.to_string_lossy().to_string();
+
+        let plan = plan_sync(&path, Some(true)).unwrap();
+        let action = |id: &str| plan.items.iter().find(|i| i.extension_id == id).map(|i| i.action);
+        assert_eq!(action("com.test.tool"), Some(PlanAction::Upgrade));
+        assert_eq!(plan.items[0].target_version.as_deref(), Some("2.1.0"));
+        assert_eq!(action("com.test.extra"), Some(PlanAction::Remove));
+        assert_eq!(plan.unresolved, vec!["com.test.absent (*)".to_string()]);
+        // The lockfile's own setting keeps unlisted extensions
+        assert_eq!(plan_sync(&path, None).unwrap().items.len(), 2);
+
+        let results = apply_sync(&path, Some(true)).unwrap();
+        let tool = results.iter().find(|r| r.extension_id == "com.test.tool").unwrap();
+        assert!(tool.success, "{}", tool.message);
+        let installed: Vec<(String, String)> = installer::list_extensions().into_iter().map(|e| (e.id, e.version)).collect();
+        assert_eq!(installed, vec![("com.test.tool".to_string(), "2.1.0".to_string())]);
+    }
+}
@@ -0,0 +1,98 @@
+//! Helpers shared by the unit tests
+
+use crate::installer;
+use std::fs;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::sync::{Mutex, MutexGuard};
+use zip::write::SimpleFileOptions;
+use zip::ZipWriter;
+
+/// Only one sandbox at a time — the folders it swaps in are process-wide
+static LOCK: Mutex<()> = Mutex::new(());
+
+/// A temporary home: while it lives, the extensions and data folders point
+/// inside it instead of at the user's real ones
+pub struct Sandbox {
+    pub root: PathBuf,
+    _lock: MutexGuard<'static, ()>,
+}
+
+impl Sandbox {
+    pub fn new() -> Sandbox {
+        let lock = LOCK.lock().unwrap_or_else(|e| e.into_inner());
+        let stamp = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .map(|d| d.as_nanos())
+            .unwrap_or(0);
+        let root = std::env::temp_dir().join(format!("zxp-magic-test-{}-{}", std::process::id(), stamp));
+        fs::create_dir_all(&root).unwrap();
+        // Resolve /tmp-style links once so paths compare equal to canonicalized ones
+        let root = fs::canonicalize(&root).unwrap();
+        *installer::TEST_HOME.lock().unwrap_or_else(|e| e.into_inner()) = Some(root.clone());
+        Sandbox { root, _lock: lock }
+    }
+
+    pub fn path(&self, rel: &str) -> PathBuf {
+        self.root.join(rel)
+    }
+}
+
+impl Drop for Sandbox {
+    fn drop(&mut self) {
+        *installer::TEST_HOME.lock().unwrap_or_else(|e| e.into_inner()) = None;
+        let _ = fs::remove_dir_all(&self.root);
+    }
+}
+
+/// Write a ZIP with the given (name, content) entries; names ending in '/' are folders
+pub fn write_zip(path: &Path, entries: &[(&str, &str)]) {
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent).unwrap();
+    }
+    let mut writer = ZipWriter::new(fs::File::create(path).unwrap());
+    for (name, content) in entries {
+        if name.ends_with('/') {
+            writer.add_directory(*name, SimpleFileOptions::default()).unwrap();
+        } else {
+            writer.start_file(*name, SimpleFileOptions::default()).unwrap();
+            writer.write_all(content.as_bytes()).unwrap();
+        }
+    }
+    writer.finish().unwrap();
+}
+
+/// A minimal CEP manifest for one panel
+pub fn manifest(id: &str, version: &str) -> String {
+    format!(
+        r#"<?xml version="1.0" encoding="UTF-8"?>
+<ExtensionManifest Version="7.0" ExtensionBundleId="{id}" ExtensionBundleVersion="{version}" ExtensionBundleName="Test">
+  <ExtensionList>
+    <Extension Id="{id}.panel" Version="{version}" />
+  </ExtensionList>
+  <ExecutionEnvironment>
+    <HostList>
+      <Host Name="PHXS" Version="[22.0,99.9]" />
+    </HostList>
+  </ExecutionEnvironment>
+  <DispatchInfoList>
+    <Extension Id="{id}.panel">
+      <DispatchInfo>
+        <Resources>
+          <MainPath>./index.html</MainPath>
+        </Resources>
+      </DispatchInfo>
+    </Extension>
+  </DispatchInfoList>
+</ExtensionManifest>
+"#
+    )
+}
+
+/// Write a ZXP holding a panel with `manifest(id, version)` and the given extra files
+pub fn write_zxp(path: &Path, id: &str, version: &str, files: &[(&str, &str)]) {
+    let manifest = manifest(id, version);
+    let mut entries = vec![("CSXS/manifest.xml", manifest.as_str())];
+    entries.extend_from_slice(files);
+    write_zip(path, &entries);
+}
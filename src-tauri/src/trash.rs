@@ -0,0 +1,375 @@
+use crate::elevate::{self, PrivilegedOp};
+use crate::hybrid;
+use crate::installer::{self, ExtensionInfo};
+use crate::integrity;
+use crate::settings;
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::{Component, Path, PathBuf};
+use walkdir::WalkDir;
+
+const INFO_FILE: &str = "trash-info.json";
+/// Subfolder of a trash item holding the extension's files
+const CONTENT_DIR: &str = "extension";
+/// Subfolder of a trash item holding its integrity record and cached package
+const RECORDS_DIR: &str = "records";
+/// Subfolder of a trash item holding the host app files of a hybrid package
+const PAYLOAD_DIR: &str = "payload";
+
+/// An uninstalled extension kept so it can be restored
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TrashItem {
+    /// Folder name inside the trash
+    pub id: String,
+    pub extension_id: String,
+    pub name: String,
+    pub version: String,
+    pub original_path: String,
+    /// Seconds since the Unix epoch
+    pub trashed_at: u64,
+}
+
+pub fn trash_folder() -> PathBuf {
+    installer::get_data_folder().join("trash")
+}
+
+fn now() -> u64 {
+    std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0)
+}
+
+/// Copy a folder tree without following symlinks
+pub fn copy_dir(from: &Path, to: &Path) -> std::io::Result<()> {
+    for entry in WalkDir::new(from) {
+        let entry = entry.map_err(std::io::Error::other)?;
+        let dest = to.join(entry.path().strip_prefix(from).unwrap_or(entry.path()));
+        let file_type = entry.file_type();
+        if file_type.is_dir() {
+            fs::create_dir_all(&dest)?;
+        } else if file_type.is_symlink() {
+            #[cfg(unix)]
+            std::os::unix::fs::symlink(fs::read_link(entry.path())?, &dest)?;
+        } else {
+            fs::copy(entry.path(), &dest)?;
+        }
+    }
+    Ok(())
+}
+
+/// A cross-volume move copied everything but could not remove the original.
+/// The copy is complete and is kept — the original may be partly deleted.
+#[derive(Debug)]
+pub struct KeptCopy {
+    pub to: PathBuf,
+    pub source: std::io::Error,
+}
+
+impl std::fmt::Display for KeptCopy {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "cannot remove the original ({}), a complete copy was kept at {}", self.source, self.to.display())
+    }
+}
+
+impl std::error::Error for KeptCopy {}
+
+/// True when a move failed after its copy finished (see `KeptCopy`)
+pub fn is_kept_copy(e: &std::io::Error) -> bool {
+    e.get_ref().is_some_and(|inner| inner.is::<KeptCopy>())
+}
+
+/// Move a folder with the current user's rights, copying across volumes
+pub fn move_dir_direct(from: &Path, to: &Path) -> std::io::Result<()> {
+    match fs::rename(from, to) {
+        Ok(()) => return Ok(()),
+        Err(e) if e.kind() == std::io::ErrorKind::PermissionDenied => return Err(e),
+        Err(e) => log::info!("Cannot rename {} ({}), copying instead", from.display(), e),
+    }
+    copy_then_remove(from, to, |dir| fs::remove_dir_all(dir))
+}
+
+/// Copy `from` to `to`, then remove `from`. A failed copy is cleaned up; once
+/// the copy is complete it is never deleted, since the original may already be
+/// half gone when `remove` fails.
+fn copy_then_remove(from: &Path, to: &Path, remove: impl Fn(&Path) -> std::io::Result<()>) -> std::io::Result<()> {
+    if let Err(e) = copy_dir(from, to) {
+        let _ = fs::remove_dir_all(to);
+        return Err(e);
+    }
+    remove(from).map_err(|source| std::io::Error::other(KeptCopy { to: to.to_path_buf(), source }))
+}
+
+/// Move a folder, going through the elevated helper when it lives somewhere
+/// the user cannot write (the system extensions folder)
+fn move_dir(from: &Path, to: &Path) -> Result<(), String> {
+    match move_dir_direct(from, to) {
+        Ok(()) => Ok(()),
+        Err(e) if e.kind() == std::io::ErrorKind::PermissionDenied => {
+            let result = elevate::run_elevated(&PrivilegedOp::MoveDir {
+                from: from.to_string_lossy().to_string(),
+                to: to.to_string_lossy().to_string(),
+            });
+            if result.success {
+                Ok(())
+            } else {
+                Err(format!("Cannot move {}: {}", from.display(), result.message))
+            }
+        }
+        Err(e) => Err(format!("Cannot move {}: {}", from.display(), e)),
+    }
+}
+
+/// Delete a trash item, elevating for files that came out of the system folder
+fn remove_item(dir: &Path) -> Result<(), String> {
+    match fs::remove_dir_all(dir) {
+        Ok(()) => Ok(()),
+        Err(e) if e.kind() == std::io::ErrorKind::PermissionDenied => {
+            let result = elevate::run_elevated(&PrivilegedOp::RemoveDir {
+                path: dir.to_string_lossy().to_string(),
+            });
+            if result.success {
+                Ok(())
+            } else {
+                Err(format!("Cannot remove {}: {}", dir.display(), result.message))
+            }
+        }
+        Err(e) => Err(format!("Cannot remove {}: {}", dir.display(), e)),
+    }
+}
+
+/// Move an installed extension folder into the trash instead of deleting it
+pub fn move_to_trash(dir: &Path, info: &ExtensionInfo) -> Result<TrashItem, String> {
+    purge_expired();
+
+    let trashed_at = now();
+    let base = format!(
+        "{}-{}",
+        installer::folder_name_for(&info.id).unwrap_or_else(|_| "extension".to_string()),
+        trashed_at
+    );
+    let mut id = base.clone();
+    let mut n = 2;
+    while trash_folder().join(&id).exists() {
+        id = format!("{}-{}", base, n);
+        n += 1;
+    }
+
+    let item_dir = trash_folder().join(&id);
+    fs::create_dir_all(&item_dir).map_err(|e| format!("Cannot create trash folder: {}", e))?;
+    let item = TrashItem {
+        id,
+        extension_id: info.id.clone(),
+        name: info.name.clone(),
+        version: info.version.clone(),
+        original_path: dir.to_string_lossy().to_string(),
+        trashed_at,
+    };
+    let json = serde_json::to_string_pretty(&item).map_err(|e| e.to_string())?;
+    fs::write(item_dir.join(INFO_FILE), json).map_err(|e| format!("Cannot write trash record: {}", e))?;
+
+    // Keep the integrity record with the folder so verify/repair work after a restore
+    if let Err(e) = integrity::stash_install(&info.id, dir, &item_dir.join(RECORDS_DIR)) {
+        log::warn!("{}", e);
+    }
+    if let Err(e) = move_dir(dir, &item_dir.join(CONTENT_DIR)) {
+        if item_dir.join(CONTENT_DIR).exists() {
+            // The files were copied but the original could not be fully removed —
+            // the trash holds the only complete copy, so keep the whole item
+            return Err(format!("{} — a copy was kept in the trash", e));
+        }
+        let _ = integrity::unstash_install(&info.id, &item_dir.join(RECORDS_DIR));
+        let _ = fs::remove_dir_all(&item_dir);
+        return Err(e);
+    }
+    Ok(item)
+}
+
+/// Where `hybrid::trash_payload` keeps the host app files of a trash item
+pub fn payload_stash(item: &TrashItem) -> PathBuf {
+    trash_folder().join(&item.id).join(PAYLOAD_DIR)
+}
+
+fn load_item(item_dir: &Path) -> Option<TrashItem> {
+    let content = fs::read_to_string(item_dir.join(INFO_FILE)).ok()?;
+    serde_json::from_str(&content).ok()
+}
+
+/// Trashed extensions, newest first
+pub fn list_trash() -> Vec<TrashItem> {
+    let mut items: Vec<TrashItem> = fs::read_dir(trash_folder())
+        .map(|entries| entries.flatten().filter_map(|e| load_item(&e.path())).collect())
+        .unwrap_or_default();
+    items.sort_by_key(|i| std::cmp::Reverse(i.trashed_at));
+    items
+}
+
+fn item_dir(trash_id: &str) -> Result<PathBuf, String> {
+    // Ids are plain folder names — never let one point elsewhere
+    if trash_id.is_empty() || trash_id.contains(['/', '\\']) || trash_id.starts_with('.') {
+        return Err(format!("Invalid trash item '{}'", trash_id));
+    }
+    Ok(trash_folder().join(trash_id))
+}
+
+/// The folder a trash item goes back to. The record is user-writable, so only
+/// a plain folder directly inside a known extensions folder is accepted.
+fn restore_target(item: &TrashItem) -> Result<PathBuf, String> {
+    let original = PathBuf::from(&item.original_path);
+    let invalid = || format!("{} is not inside a CEP extensions folder", item.original_path);
+    let (Some(parent), Some(Component::Normal(_))) = (original.parent(), original.components().next_back()) else {
+        return Err(invalid());
+    };
+    if !original.is_absolute() || original.components().any(|c| c == Component::ParentDir) {
+        return Err(invalid());
+    }
+    let mut roots = installer::get_all_extension_folders();
+    roots.extend(installer::get_system_extensions_folder());
+    let canonical_parent = fs::canonicalize(parent).ok();
+    let in_root = roots
+        .iter()
+        .any(|root| root == parent || (canonical_parent.is_some() && fs::canonicalize(root).ok() == canonical_parent));
+    if !in_root || elevate::has_symlink_component(&original) {
+        return Err(invalid());
+    }
+    Ok(original)
+}
+
+/// Move a trashed extension back to where it was installed
+pub fn restore_from_trash(trash_id: &str) -> Result<TrashItem, String> {
+    let dir = item_dir(trash_id)?;
+    let item = load_item(&dir).ok_or_else(|| format!("Trash item '{}' not found", trash_id))?;
+    let original = restore_target(&item)?;
+    if original.exists() {
+        return Err(format!(
+            "{} already exists — uninstall the current copy first",
+            item.original_path
+        ));
+    }
+    if let Some(parent) = original.parent() {
+        fs::create_dir_all(parent).map_err(|e| format!("Cannot create {}: {}", parent.display(), e))?;
+    }
+    // Host app files first: if they cannot go back, the item stays whole for another try
+    hybrid::restore_payload(&item.extension_id, &dir.join(PAYLOAD_DIR))?;
+    move_dir(&dir.join(CONTENT_DIR), &original)?;
+    if let Err(e) = integrity::unstash_install(&item.extension_id, &dir.join(RECORDS_DIR)) {
+        log::warn!("{}", e);
+    }
+    let _ = fs::remove_dir_all(&dir);
+    Ok(item)
+}
+
+/// Permanently delete everything in the trash
+pub fn empty_trash() -> Result<(), String> {
+    let Ok(entries) = fs::read_dir(trash_folder()) else {
+        return Ok(());
+    };
+    for entry in entries.flatten() {
+        remove_item(&entry.path()).map_err(|e| format!("Cannot empty trash: {}", e))?;
+    }
+    Ok(())
+}
+
+/// Delete trash items older than the configured retention period
+fn purge_expired() {
+    let days = settings::load_settings().trash_retention_days;
+    if days == 0 {
+        return;
+    }
+    let cutoff = now().saturating_sub(days * 24 * 60 * 60);
+    for item in list_trash().into_iter().filter(|i| i.trashed_at < cutoff) {
+        if let Ok(dir) = item_dir(&item.id) {
+            if let Err(e) = remove_item(&dir) {
+                log::warn!("{}", e);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::Sandbox;
+
+    fn sample(sandbox: &Sandbox) -> PathBuf {
+        let from = sandbox.path("from");
+        fs::create_dir_all(from.join("js")).unwrap();
+        fs::write(from.join("index.html"), "<html></html>").unwrap();
+        fs::write(from.join("js/main.js"), "main();").unwrap();
+        from
+    }
+
+    #[test]
+    fn copy_then_remove_moves_the_tree() {
+        let sandbox = Sandbox::new();
+        let from = sample(&sandbox);
+        let to = sandbox.path("to");
+        copy_then_remove(&from, &to, |dir| fs::remove_dir_all(dir)).unwrap();
+        assert!(!from.exists());
+        assert_eq!(fs::read_to_string(to.join("js/main.js")).unwrap(), "main();");
+    }
+
+    #[test]
+    fn failed_removal_keeps_the_copy() {
+        let sandbox = Sandbox::new();
+        let from = sample(&sandbox);
+        let to = sandbox.path("to");
+        let err = copy_then_remove(&from, &to, |dir| {
+            // Half the original is gone when removal gives up
+            fs::remove_file(dir.join("index.html"))?;
+            Err(std::io::Error::other("busy"))
+        })
+        .unwrap_err();
+        assert!(is_kept_copy(&err));
+        assert!(err.to_string().contains("copy was kept"));
+        assert_eq!(fs::read_to_string(to.join("index.html")).unwrap(), "<html></html>");
+        assert_eq!(fs::read_to_string(to.join("js/main.js")).unwrap(), "main();");
+    }
+
+    #[test]
+    fn failed_copy_cleans_up() {
+        let sandbox = Sandbox::new();
+        let to = sandbox.path("to");
+        let err = copy_then_remove(&sandbox.path("missing"), &to, |dir| fs::remove_dir_all(dir)).unwrap_err();
+        assert!(!is_kept_copy(&err));
+        assert!(!to.exists());
+    }
+
+    #[test]
+    fn uninstall_and_restore_round_trip() {
+        let sandbox = Sandbox::new();
+        let zxp = sandbox.path("in/panel.zxp");
+        crate::test_support::write_zxp(&zxp, "com.test.trash", "1.0.0", &[("index.html", "<html></html>")]);
+        assert!(installer::install_extension(&zxp.to_string_lossy()).success);
+        let record = integrity::load_manifest("com.test.trash").unwrap();
+
+        installer::uninstall_extension("com.test.trash", Some(&record.install_path)).unwrap();
+        assert!(!Path::new(&record.install_path).exists());
+        let items = list_trash();
+        assert_eq!(items.len(), 1);
+
+        restore_from_trash(&items[0].id).unwrap();
+        assert!(Path::new(&record.install_path).join("index.html").is_file());
+        assert!(integrity::verify_extension("com.test.trash").unwrap().intact);
+        assert!(list_trash().is_empty());
+    }
+
+    #[test]
+    fn restore_rejects_target_outside_extension_folders() {
+        let sandbox = Sandbox::new();
+        let item_dir = trash_folder().join("evil-1");
+        fs::create_dir_all(item_dir.join(CONTENT_DIR)).unwrap();
+        let item = TrashItem {
+            id: "evil-1".to_string(),
+            extension_id: "com.test.evil".to_string(),
+            name: "Evil".to_string(),
+            version: "1.0.0".to_string(),
+            original_path: sandbox.path("elsewhere/evil").to_string_lossy().to_string(),
+            trashed_at: now(),
+        };
+        fs::write(item_dir.join(INFO_FILE), serde_json::to_string(&item).unwrap()).unwrap();
+        assert!(restore_from_trash("evil-1").is_err());
+        assert!(restore_from_trash("../evil-1").is_err());
+        assert!(!sandbox.path("elsewhere/evil").exists());
+    }
+}
@@ -0,0 +1,263 @@
+use crate::catalog::{self, CatalogPackage, CatalogStatus};
+use crate::installer::{self, ExtensionInfo, InstallResult};
+use crate::integrity;
+use crate::settings;
+use crate::sidecar;
+use crate::version;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fs;
+use std::path::Path;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum UpdateSourceKind {
+    /// A configured catalog index
+    Catalog,
+    /// The vendor feed declared in the extension's sidecar file
+    VendorFeed,
+    /// A configured folder of ZXP files
+    Folder,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct UpdateInfo {
+    pub extension_id: String,
+    pub name: String,
+    pub installed_version: String,
+    pub available_version: String,
+    pub changelog: String,
+    pub source_kind: UpdateSourceKind,
+    /// Index, feed or folder the update was found in
+    pub source: String,
+    pub package: CatalogPackage,
+    /// The installed copy is in the system extensions folder; the update goes there too
+    #[serde(default)]
+    pub system: bool,
+}
+
+/// Newest candidate seen so far per extension id
+type Candidates = HashMap<String, (CatalogPackage, UpdateSourceKind, String)>;
+
+fn offer(candidates: &mut Candidates, package: CatalogPackage, kind: UpdateSourceKind, source: &str) {
+    let newer = candidates
+        .get(&package.id)
+        .map(|(p, _, _)| version::is_newer(&package.version, &p.version))
+        .unwrap_or(true);
+    if newer {
+        candidates.insert(package.id.clone(), (package, kind, source.to_string()));
+    }
+}
+
+fn collect_from_catalogs(candidates: &mut Candidates) {
+    if settings::load_settings().catalog_sources.is_empty() {
+        return;
+    }
+    match catalog::browse_catalog(None) {
+        Ok(entries) => {
+            for entry in entries {
+                if entry.status == CatalogStatus::Updatable {
+                    offer(candidates, entry.package, UpdateSourceKind::Catalog, &entry.source);
+                }
+            }
+        }
+        Err(e) => log::warn!("Catalog update check failed: {}", e),
+    }
+}
+
+fn collect_from_feeds(candidates: &mut Candidates, installed: &[ExtensionInfo]) {
+    for ext in installed {
+        let Some(dir) = &ext.install_path else { continue };
+        let Some(feed) = sidecar::load_sidecar(Path::new(dir)).and_then(|s| s.update_feed) else {
+            continue;
+        };
+        match catalog::load_catalog(&feed) {
+            Ok(index) => {
+                for package in index.packages.into_iter().filter(|p| p.id == ext.id) {
+                    offer(candidates, package, UpdateSourceKind::VendorFeed, &feed);
+                }
+            }
+            Err(e) => log::warn!("Update feed for '{}' failed: {}", ext.id, e),
+        }
+    }
+}
+
+fn collect_from_folders(candidates: &mut Candidates) {
+    for folder in settings::load_settings().update_folders {
+        let Ok(entries) = fs::read_dir(&folder) else {
+            log::warn!("Cannot read update folder {}", folder);
+            continue;
+        };
+        for entry in entries.flatten() {
+            let path = entry.path();
+            let is_package = path
+                .extension()
+                .map(|e| e.eq_ignore_ascii_case("zxp") || e.eq_ignore_ascii_case("zxpinstall"))
+                .unwrap_or(false);
+            if !is_package {
+                continue;
+            }
+            let location = path.to_string_lossy().to_string();
+            let Ok(info) = installer::get_extension_info_from_zxp(&location) else { continue };
+            let Ok(sha256) = integrity::sha256_file(&path) else { continue };
+            let package = CatalogPackage {
+                id: info.id,
+                name: info.name,
+                version: info.version,
+                description: info.description,
+                url: location,
+                sha256,
+                hosts: info.host_list,
+                changelog: String::new(),
+            };
+            offer(candidates, package, UpdateSourceKind::Folder, &folder);
+        }
+    }
+}
+
+/// Compare installed extensions against every configured update source
+pub fn check_updates() -> Vec<UpdateInfo> {
+    let installed = installer::list_extensions();
+    let mut candidates = Candidates::new();
+    collect_from_catalogs(&mut candidates);
+    collect_from_feeds(&mut candidates, &installed);
+    collect_from_folders(&mut candidates);
+
+    let mut updates: Vec<UpdateInfo> = installed
+        .into_iter()
+        .filter_map(|ext| {
+            let (package, source_kind, source) = candidates.remove(&ext.id)?;
+            if !version::is_newer(&package.version, &ext.version) {
+                return None;
+            }
+            let system = installer::is_system_install(&ext);
+            Some(UpdateInfo {
+                extension_id: ext.id,
+                name: ext.name,
+                installed_version: ext.version,
+                available_version: package.version.clone(),
+                changelog: package.changelog.clone(),
+                source_kind,
+                source,
+                package,
+                system,
+            })
+        })
+        .collect();
+
+    updates.sort_by_key(|u| u.name.to_lowercase());
+    updates
+}
+
+/// Install an update into the same scope as the copy it replaces
+fn install_update(update: &UpdateInfo) -> InstallResult {
+    let install: fn(&str) -> InstallResult = if update.system {
+        installer::install_extension_system
+    } else {
+        installer::install_extension
+    };
+    catalog::install_package_with(&update.package, install)
+}
+
+/// Install the newest available version of one extension
+pub fn update_extension(extension_id: &str) -> InstallResult {
+    match check_updates().into_iter().find(|u| u.extension_id == extension_id) {
+        Some(update) => install_update(&update),
+        None => InstallResult {
+            success: false,
+            message: format!("No update available for '{}'", extension_id),
+            extension: None,
+            payload: Vec::new(),
+        },
+    }
+}
+
+/// Install every available update, one result per extension
+pub fn update_all() -> Vec<InstallResult> {
+    check_updates()
+        .iter()
+        .map(install_update)
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::{write_zxp, Sandbox};
+
+    fn install(sandbox: &Sandbox, version: &str) {
+        let zxp = sandbox.path(&format!("in/{}.zxp", version));
+        write_zxp(&zxp, "com.test.update", version, &[("index.html", version)]);
+        assert!(installer::install_extension(&zxp.to_string_lossy()).success);
+    }
+
+    fn offer_folder(sandbox: &Sandbox, version: &str) {
+        let folder = sandbox.path("updates");
+        write_zxp(&folder.join("panel.zxp"), "com.test.update", version, &[("index.html", version)]);
+        let mut settings = settings::load_settings();
+        settings.update_folders = vec![folder.to_string_lossy().to_string()];
+        settings::save_settings(&settings).unwrap();
+    }
+
+    #[test]
+    fn finds_newer_version_in_update_folder() {
+        let sandbox = Sandbox::new();
+        install(&sandbox, "1.0.0");
+        offer_folder(&sandbox, "1.1.0");
+
+        let updates = check_updates();
+        assert_eq!(updates.len(), 1);
+        assert_eq!(updates[0].installed_version, "1.0.0");
+        assert_eq!(updates[0].available_version, "1.1.0");
+        assert_eq!(updates[0].source_kind, UpdateSourceKind::Folder);
+        assert!(!updates[0].system);
+    }
+
+    #[test]
+    fn ignores_same_or_older_versions() {
+        let sandbox = Sandbox::new();
+        install(&sandbox, "1.1.0");
+        offer_folder(&sandbox, "1.0.0");
+        assert!(check_updates().is_empty());
+        assert!(!update_extension("com.test.update").success);
+    }
+
+    #[test]
+    fn update_stays_in_the_user_folder() {
+        let sandbox = Sandbox::new();
+        install(&sandbox, "1.0.0");
+        offer_folder(&sandbox, "1.1.0");
+
+        let result = update_extension("com.test.update");
+        assert!(result.success, "{}", result.message);
+        let installed = installer::list_extensions();
+        assert_eq!(installed.len(), 1);
+        assert_eq!(installed[0].version, "1.1.0");
+        assert!(!installer::is_system_install(&installed[0]));
+    }
+
+    #[test]
+    fn system_update_goes_through_the_system_installer() {
+        let sandbox = Sandbox::new();
+        install(&sandbox, "1.0.0");
+        offer_folder(&sandbox, "1.1.0");
+        let mut update = check_updates().remove(0);
+        update.system = true;
+
+        // The sandbox has no system folder, so only the user-folder installer could succeed
+        let result = install_update(&update);
+        assert!(!result.success);
+        assert_eq!(installer::list_extensions()[0].version, "1.0.0");
+    }
+
+    #[test]
+    fn scope_follows_the_install_folder() {
+        let _sandbox = Sandbox::new();
+        let mut ext = ExtensionInfo::default();
+        assert!(!installer::is_system_install(&ext));
+        ext.install_path = Some(installer::get_extensions_folder().join("com.a").to_string_lossy().to_string());
+        assert!(!installer::is_system_install(&ext));
+        ext.install_path = Some("/Library/Application Support/Adobe/CEP/extensions/com.a".to_string());
+        assert!(installer::is_system_install(&ext));
+    }
+}
@@ -0,0 +1,622 @@
+use crate::installer;
+use crate::settings;
+use crate::trash;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use std::collections::HashMap;
+use std::fs;
+use std::io::{Read, Seek};
+use std::path::{Path, PathBuf};
+use zip::ZipArchive;
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct UxpHost {
+    /// Host code from the manifest, e.g. "PS"
+    pub app: String,
+    /// Display name, e.g. "Photoshop"
+    pub name: String,
+    pub min_version: Option<String>,
+}
+
+/// A UXP plugin, installed or inside a `.ccx` package
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct UxpPlugin {
+    pub id: String,
+    pub name: String,
+    pub version: String,
+    pub hosts: Vec<UxpHost>,
+    /// `manifestVersion` (4 or 5 for current plugins)
+    pub manifest_version: Option<u64>,
+    pub install_path: Option<String>,
+    /// Enabled state from the hosts' PluginsInfo files, if registered there
+    pub enabled: Option<bool>,
+    /// Hosts whose PluginsInfo lists the plugin
+    pub registered_in: Vec<String>,
+    /// Installed for all users rather than the current one
+    pub system: bool,
+}
+
+/// Display name for a UXP host code
+fn host_name(app: &str) -> String {
+    match app.to_uppercase().as_str() {
+        "PS" => "Photoshop".to_string(),
+        "ID" => "InDesign".to_string(),
+        "AI" => "Illustrator".to_string(),
+        "XD" => "XD".to_string(),
+        "PPRO" | "PREMIEREPRO" => "Premiere Pro".to_string(),
+        _ => installer::format_host_name(app),
+    }
+}
+
+/// UXP's per-user and all-users data folders
+fn uxp_roots() -> Vec<(PathBuf, bool)> {
+    #[cfg(test)]
+    if let Some(home) = installer::test_home() {
+        return vec![(home.join("uxp"), false), (home.join("uxp-system"), true)];
+    }
+    #[cfg(target_os = "windows")]
+    {
+        let mut roots = Vec::new();
+        if let Ok(appdata) = std::env::var("APPDATA") {
+            roots.push((PathBuf::from(appdata).join("Adobe\\UXP"), false));
+        }
+        let common = std::env::var("CommonProgramFiles").unwrap_or_else(|_| "C:\\Program Files\\Common Files".to_string());
+        roots.push((PathBuf::from(common).join("Adobe\\UXP"), true));
+        roots
+    }
+    #[cfg(target_os = "macos")]
+    {
+        let home = std::env::var("HOME").unwrap_or_else(|_| "~".to_string());
+        vec![
+            (PathBuf::from(home).join("Library/Application Support/Adobe/UXP"), false),
+            (PathBuf::from("/Library/Application Support/Adobe/UXP"), true),
+        ]
+    }
+    #[cfg(not(any(target_os = "windows", target_os = "macos")))]
+    {
+        vec![(PathBuf::from("/tmp/uxp"), false)]
+    }
+}
+
+/// Localized manifest strings are objects like `{"default": "Name", "de": "Name"}`
+fn text(value: Option<&Value>) -> String {
+    match value {
+        Some(Value::String(s)) => s.clone(),
+        Some(Value::Object(map)) => map
+            .get("default")
+            .or_else(|| map.values().next())
+            .and_then(Value::as_str)
+            .unwrap_or_default()
+            .to_string(),
+        _ => String::new(),
+    }
+}
+
+/// Parse a UXP `manifest.json`
+pub fn parse_manifest_json(content: &str) -> Result<UxpPlugin, String> {
+    let manifest: Value = serde_json::from_str(content).map_err(|e| format!("Invalid manifest.json: {}", e))?;
+    let id = text(manifest.get("id"));
+    if id.is_empty() {
+        return Err("manifest.json has no plugin id".to_string());
+    }
+
+    // `host` is one object, or an array of them for multi-host plugins
+    let hosts = match manifest.get("host") {
+        Some(Value::Array(items)) => items.iter().collect(),
+        Some(host) => vec![host],
+        None => Vec::new(),
+    }
+    .into_iter()
+    .filter_map(|host| {
+        let app = host.get("app")?.as_str()?.to_string();
+        Some(UxpHost {
+            name: host_name(&app),
+            min_version: host.get("minVersion").and_then(Value::as_str).map(str::to_string),
+            app,
+        })
+    })
+    .collect();
+
+    let name = text(manifest.get("name"));
+    Ok(UxpPlugin {
+        name: if name.is_empty() { installer::name_from_id(&id) } else { name },
+        id,
+        version: manifest
+            .get("version")
+            .and_then(Value::as_str)
+            .unwrap_or("Unknown")
+            .to_string(),
+        hosts,
+        manifest_version: manifest.get("manifestVersion").and_then(Value::as_u64),
+        install_path: None,
+        enabled: None,
+        registered_in: Vec::new(),
+        system: false,
+    })
+}
+
+/// Read the manifest of a `.ccx` package (a zip with `manifest.json` at its
+/// root or below wrapper folders)
+pub fn get_plugin_info_from_ccx(path: &str) -> Result<UxpPlugin, String> {
+    let file = fs::File::open(path).map_err(|e| format!("Cannot open file: {}", e))?;
+    let mut archive = ZipArchive::new(file).map_err(|e| format!("Not a valid CCX: {}", e))?;
+    let (index, _) = find_plugin_root(&archive).ok_or("manifest.json not found in CCX")?;
+    let mut content = String::new();
+    archive
+        .by_index(index)
+        .map_err(|e| e.to_string())?
+        .read_to_string(&mut content)
+        .map_err(|e| e.to_string())?;
+    parse_manifest_json(&content)
+}
+
+fn read_plugin_dir(dir: &Path, system: bool) -> Option<UxpPlugin> {
+    let content = fs::read_to_string(dir.join("manifest.json")).ok()?;
+    match parse_manifest_json(&content) {
+        Ok(mut plugin) => {
+            plugin.install_path = Some(dir.to_string_lossy().to_string());
+            plugin.system = system;
+            Some(plugin)
+        }
+        Err(e) => {
+            log::warn!("Skipping UXP plugin in {}: {}", dir.display(), e);
+            None
+        }
+    }
+}
+
+/// One entry of a host's `PluginsInfo/v1/<HOST>.json`
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct RegisteredPlugin {
+    plugin_id: String,
+    #[serde(default)]
+    name: String,
+    #[serde(default)]
+    version_string: String,
+    #[serde(default)]
+    path: String,
+    #[serde(default)]
+    status: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct PluginsInfo {
+    #[serde(default)]
+    plugins: Vec<RegisteredPlugin>,
+}
+
+/// Registered plugins per host code, from every PluginsInfo file
+fn registered_plugins(root: &Path) -> Vec<(String, RegisteredPlugin)> {
+    let Ok(entries) = fs::read_dir(root.join("PluginsInfo").join("v1")) else {
+        return Vec::new();
+    };
+    let mut registered = Vec::new();
+    for entry in entries.flatten() {
+        let path = entry.path();
+        if path.extension().map(|e| e != "json").unwrap_or(true) {
+            continue;
+        }
+        let host = path.file_stem().unwrap_or_default().to_string_lossy().to_string();
+        let info = fs::read_to_string(&path)
+            .ok()
+            .and_then(|content| serde_json::from_str::<PluginsInfo>(&content).ok());
+        match info {
+            Some(info) => registered.extend(info.plugins.into_iter().map(|p| (host.clone(), p))),
+            None => log::warn!("Cannot read {}", path.display()),
+        }
+    }
+    registered
+}
+
+/// Resolve a PluginsInfo `path` such as `$localPlugins\External\com.x_1.0.0`
+fn resolve_registered_path(root: &Path, path: &str) -> Option<PathBuf> {
+    let normalized = path.replace('\\', "/");
+    let rel = normalized.strip_prefix("$localPlugins/")?;
+    Some(root.join("Plugins").join(rel))
+}
+
+/// Installed UXP plugins from the External plugin folders, merged with what the
+/// hosts' PluginsInfo files say is registered and enabled
+pub fn list_uxp_plugins() -> Vec<UxpPlugin> {
+    let mut plugins: Vec<UxpPlugin> = Vec::new();
+    // Install path → index in `plugins`
+    let mut by_path: HashMap<PathBuf, usize> = HashMap::new();
+
+    for (root, system) in uxp_roots() {
+        let Ok(entries) = fs::read_dir(root.join("Plugins").join("External")) else { continue };
+        for entry in entries.flatten() {
+            let dir = entry.path();
+            if let Some(plugin) = dir.is_dir().then(|| read_plugin_dir(&dir, system)).flatten() {
+                by_path.insert(dir, plugins.len());
+                plugins.push(plugin);
+            }
+        }
+    }
+
+    for (root, system) in uxp_roots() {
+        for (host, registered) in registered_plugins(&root) {
+            let dir = resolve_registered_path(&root, &registered.path);
+            let index = dir
+                .as_ref()
+                .and_then(|d| by_path.get(d).copied())
+                .or_else(|| plugins.iter().position(|p| p.id == registered.plugin_id && p.install_path.is_none()));
+            let index = match index {
+                Some(index) => index,
+                None => {
+                    // Registered but not in an External folder: read it where it lives if possible
+                    let plugin = dir
+                        .as_ref()
+                        .and_then(|d| read_plugin_dir(d, system))
+                        .unwrap_or_else(|| UxpPlugin {
+                            id: registered.plugin_id.clone(),
+                            name: registered.name.clone(),
+                            version: registered.version_string.clone(),
+                            hosts: vec![UxpHost {
+                                name: host_name(&host),
+                                app: host.clone(),
+                                min_version: None,
+                            }],
+                            manifest_version: None,
+                            install_path: None,
+                            enabled: None,
+                            registered_in: Vec::new(),
+                            system,
+                        });
+                    if let Some(d) = dir {
+                        by_path.insert(d, plugins.len());
+                    }
+                    plugins.push(plugin);
+                    plugins.len() - 1
+                }
+            };
+            let plugin = &mut plugins[index];
+            let enabled = registered.status.eq_ignore_ascii_case("enabled");
+            plugin.enabled = Some(plugin.enabled.unwrap_or(false) || enabled);
+            if !plugin.registered_in.contains(&host) {
+                plugin.registered_in.push(host);
+            }
+        }
+    }
+
+    plugins.sort_by_key(|p| p.name.to_lowercase());
+    plugins
+}
+
+/// The current user's UXP folder; development installs always go here
+fn user_root() -> PathBuf {
+    uxp_roots()
+        .into_iter()
+        .find(|(_, system)| !system)
+        .map(|(root, _)| root)
+        .unwrap_or_default()
+}
+
+fn external_folder() -> PathBuf {
+    user_root().join("Plugins").join("External")
+}
+
+/// Folder prefix of the shallowest `manifest.json` ("" or ending in '/')
+fn find_plugin_root<R: Read + Seek>(archive: &ZipArchive<R>) -> Option<(usize, String)> {
+    (0..archive.len())
+        .filter_map(|i| {
+            let name = archive.name_for_index(i)?;
+            let prefix = name.strip_suffix("manifest.json")?;
+            (prefix.is_empty() || prefix.ends_with('/')).then(|| (i, prefix.to_string()))
+        })
+        .filter(|(_, prefix)| !prefix.contains("__MACOSX"))
+        .min_by_key(|(_, prefix)| prefix.matches('/').count())
+}
+
+/// External folders holding `plugin_id`, whatever version they were named after
+fn installed_dirs(plugin_id: &str) -> Vec<PathBuf> {
+    let Ok(entries) = fs::read_dir(external_folder()) else {
+        return Vec::new();
+    };
+    entries
+        .flatten()
+        .map(|e| e.path())
+        .filter(|dir| !fs::symlink_metadata(dir).map(|m| m.file_type().is_symlink()).unwrap_or(true))
+        .filter(|dir| {
+            fs::read_to_string(dir.join("manifest.json"))
+                .ok()
+                .and_then(|content| parse_manifest_json(&content).ok())
+                .map(|plugin| plugin.id == plugin_id)
+                .unwrap_or(false)
+        })
+        .collect()
+}
+
+fn plugins_info_path(host: &str) -> PathBuf {
+    user_root().join("PluginsInfo").join("v1").join(format!("{}.json", host))
+}
+
+/// Rewrite a host's PluginsInfo, dropping `plugin_id` and optionally adding a new entry.
+/// Unknown fields are kept as they are.
+fn update_registry(host: &str, plugin_id: &str, entry: Option<Value>) -> Result<(), String> {
+    let path = plugins_info_path(host);
+    let mut info: Value = fs::read_to_string(&path)
+        .ok()
+        .and_then(|content| serde_json::from_str(&content).ok())
+        .unwrap_or_else(|| json!({ "plugins": [] }));
+    let Some(plugins) = info.get_mut("plugins").and_then(Value::as_array_mut) else {
+        return Err(format!("Unexpected format in {}", path.display()));
+    };
+    let before = plugins.len();
+    plugins.retain(|p| p.get("pluginId").and_then(Value::as_str) != Some(plugin_id));
+    if entry.is_none() && plugins.len() == before {
+        return Ok(());
+    }
+    plugins.extend(entry);
+
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent).map_err(|e| format!("Cannot create {}: {}", parent.display(), e))?;
+    }
+    let json = serde_json::to_string_pretty(&info).map_err(|e| e.to_string())?;
+    fs::write(&path, json).map_err(|e| format!("Cannot write {}: {}", path.display(), e))
+}
+
+/// Host codes with a PluginsInfo file in the user's UXP folder
+fn registry_hosts() -> Vec<String> {
+    fs::read_dir(user_root().join("PluginsInfo").join("v1"))
+        .map(|entries| {
+            entries
+                .flatten()
+                .map(|e| e.path())
+                .filter(|p| p.extension().map(|e| e == "json").unwrap_or(false))
+                .map(|p| p.file_stem().unwrap_or_default().to_string_lossy().to_string())
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+fn unregister(plugin_id: &str) -> Result<(), String> {
+    for host in registry_hosts() {
+        update_registry(&host, plugin_id, None)?;
+    }
+    Ok(())
+}
+
+fn register(plugin: &UxpPlugin, folder_name: &str) -> Result<(), String> {
+    let separator = if cfg!(target_os = "windows") { "\\" } else { "/" };
+    for host in &plugin.hosts {
+        let entry = json!({
+            "hostMinVersion": host.min_version.clone().unwrap_or_default(),
+            "name": plugin.name,
+            "path": format!("$localPlugins{sep}External{sep}{}", folder_name, sep = separator),
+            "pluginId": plugin.id,
+            "status": "enabled",
+            "type": "uxp",
+            "versionString": plugin.version,
+        });
+        update_registry(&host.app, &plugin.id, Some(entry))?;
+    }
+    Ok(())
+}
+
+/// Copy an unpacked plugin folder, or extract a `.ccx`, into `target_dir`
+fn unpack(path: &Path, target_dir: &Path) -> Result<(), String> {
+    if path.is_dir() {
+        return trash::copy_dir(path, target_dir).map_err(|e| format!("Cannot copy plugin: {}", e));
+    }
+    let file = fs::File::open(path).map_err(|e| format!("Cannot open CCX file: {}", e))?;
+    let mut archive = ZipArchive::new(file).map_err(|e| format!("Cannot read CCX archive: {}", e))?;
+    let (_, root) = find_plugin_root(&archive).ok_or("manifest.json not found in CCX")?;
+    let limits = settings::load_settings().extraction_limits;
+    installer::check_archive_limits(&mut archive, &root, &limits).map_err(|e| format!("Refusing to install: {}", e))?;
+    fs::create_dir_all(target_dir).map_err(|e| format!("Cannot create plugin directory: {}", e))?;
+    installer::extract_archive(&mut archive, &root, target_dir, &limits).map_err(|e| format!("Extraction aborted: {}", e))
+}
+
+fn read_source_manifest(path: &Path) -> Result<UxpPlugin, String> {
+    if path.is_dir() {
+        let content = fs::read_to_string(path.join("manifest.json"))
+            .map_err(|_| format!("No manifest.json in {}", path.display()))?;
+        return parse_manifest_json(&content);
+    }
+    get_plugin_info_from_ccx(&path.to_string_lossy())
+}
+
+/// Install an unsigned/development `.ccx` or unpacked UXP plugin folder for the
+/// current user and register it with its hosts, replacing any installed version
+pub fn install_uxp_plugin(path: &str) -> Result<UxpPlugin, String> {
+    let source = Path::new(path);
+    let plugin = read_source_manifest(source)?;
+    if plugin.hosts.is_empty() {
+        return Err("manifest.json does not name a host app".to_string());
+    }
+    let folder_name = installer::folder_name_for(&format!("{}_{}", plugin.id, plugin.version))?;
+    let target_dir = external_folder().join(&folder_name);
+    if source.is_dir() && fs::canonicalize(source).ok() == fs::canonicalize(&target_dir).ok() {
+        return Err("The plugin is already installed from this folder".to_string());
+    }
+
+    // Unpack before touching the installed version, so a failed install leaves it working
+    let existing = installed_dirs(&plugin.id);
+    if target_dir.exists() && !existing.contains(&target_dir) {
+        return Err(format!("{} already exists and holds a different plugin", target_dir.display()));
+    }
+    let staging = installer::StagingDir::new()?;
+    unpack(source, &staging.path)?;
+
+    for dir in existing {
+        log::info!("Replacing UXP plugin '{}' at {}", plugin.id, dir.display());
+        fs::remove_dir_all(&dir).map_err(|e| format!("Cannot remove existing installation: {}", e))?;
+    }
+    fs::create_dir_all(external_folder()).map_err(|e| format!("Cannot create plugin folder: {}", e))?;
+    staging.commit(&target_dir)?;
+
+    unregister(&plugin.id)?;
+    register(&plugin, &folder_name)?;
+
+    let mut installed = read_plugin_dir(&target_dir, false).unwrap_or(plugin);
+    installed.enabled = Some(true);
+    installed.registered_in = installed.hosts.iter().map(|h| h.app.clone()).collect();
+    Ok(installed)
+}
+
+/// Remove a UXP plugin installed for the current user and drop it from the hosts' registries
+pub fn uninstall_uxp_plugin(plugin_id: &str) -> Result<(), String> {
+    let dirs = installed_dirs(plugin_id);
+    let registered = registry_hosts().iter().any(|host| {
+        fs::read_to_string(plugins_info_path(host))
+            .map(|content| content.contains(&format!("\"{}\"", plugin_id)))
+            .unwrap_or(false)
+    });
+    if dirs.is_empty() && !registered {
+        let system = list_uxp_plugins().into_iter().any(|p| p.id == plugin_id && p.system);
+        return Err(if system {
+            format!("'{}' is installed for all users — remove it with Creative Cloud", plugin_id)
+        } else {
+            format!("UXP plugin '{}' is not installed", plugin_id)
+        });
+    }
+    for dir in dirs {
+        fs::remove_dir_all(&dir).map_err(|e| format!("Cannot remove {}: {}", dir.display(), e))?;
+    }
+    unregister(plugin_id)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::{write_zip, Sandbox};
+
+    #[test]
+    fn parse_manifest_json_reads_hosts_array() {
+        let plugin = parse_manifest_json(
+            r#"{"id":"com.vendor.tool","name":{"default":"Vendor Tool","de":"Werkzeug"},"version":"1.2.0",
+                "manifestVersion":5,"host":[{"app":"PS","minVersion":"23.3.0"},{"app":"ID"}]}"#,
+        )
+        .unwrap();
+        assert_eq!(plugin.id, "com.vendor.tool");
+        assert_eq!(plugin.name, "Vendor Tool");
+        assert_eq!(plugin.version, "1.2.0");
+        assert_eq!(plugin.manifest_version, Some(5));
+        assert_eq!(plugin.hosts.len(), 2);
+        assert_eq!(plugin.hosts[0].name, "Photoshop");
+        assert_eq!(plugin.hosts[0].min_version.as_deref(), Some("23.3.0"));
+        assert_eq!(plugin.hosts[1].min_version, None);
+    }
+
+    #[test]
+    fn parse_manifest_json_single_host_and_defaults() {
+        let plugin = parse_manifest_json(r#"{"id":"com.vendor.other","host":{"app":"AI"}}"#).unwrap();
+        assert_eq!(plugin.hosts.len(), 1);
+        assert_eq!(plugin.hosts[0].app, "AI");
+        assert_eq!(plugin.version, "Unknown");
+        assert!(!plugin.name.is_empty());
+    }
+
+    #[test]
+    fn parse_manifest_json_requires_an_id() {
+        assert!(parse_manifest_json(r#"{"name":"No id"}"#).is_err());
+        assert!(parse_manifest_json("not json").is_err());
+    }
+
+    fn plugin_manifest(version: &str) -> String {
+        format!(r#"{{"id":"com.test.uxp","name":"Test Tool","version":"{}","host":[{{"app":"PS","minVersion":"23.0.0"}}]}}"#, version)
+    }
+
+    fn archive(names: &[&str]) -> ZipArchive<std::io::Cursor<Vec<u8>>> {
+        let mut writer = zip::ZipWriter::new(std::io::Cursor::new(Vec::new()));
+        for name in names {
+            writer.start_file(*name, zip::write::SimpleFileOptions::default()).unwrap();
+        }
+        ZipArchive::new(writer.finish().unwrap()).unwrap()
+    }
+
+    #[test]
+    fn find_plugin_root_takes_the_shallowest_manifest() {
+        assert_eq!(find_plugin_root(&archive(&["index.js", "manifest.json"])), Some((1, String::new())));
+        let wrapped = archive(&["__MACOSX/Tool/manifest.json", "Tool/node_modules/x/manifest.json", "Tool/manifest.json"]);
+        assert_eq!(find_plugin_root(&wrapped), Some((2, "Tool/".to_string())));
+        assert_eq!(find_plugin_root(&archive(&["Tool/my-manifest.json"])), None);
+    }
+
+    #[test]
+    fn install_registers_and_replaces_and_uninstall_unregisters() {
+        let sandbox = Sandbox::new();
+        let ccx = |version: &str| {
+            let path = sandbox.path(&format!("in/tool-{}.ccx", version));
+            write_zip(&path, &[("Tool/", ""), ("Tool/manifest.json", &plugin_manifest(version)), ("Tool/index.js", "")]);
+            path.to_string_lossy().to_string()
+        };
+
+        let installed = install_uxp_plugin(&ccx("1.0.0")).unwrap();
+        assert_eq!(installed.registered_in, vec!["PS".to_string()]);
+        let old_dir = external_folder().join("com.test.uxp_1.0.0");
+        assert!(old_dir.join("index.js").is_file());
+
+        install_uxp_plugin(&ccx("1.1.0")).unwrap();
+        assert!(!old_dir.exists());
+        assert_eq!(installed_dirs("com.test.uxp"), vec![external_folder().join("com.test.uxp_1.1.0")]);
+        let registry = fs::read_to_string(plugins_info_path("PS")).unwrap();
+        assert_eq!(registry.matches("\"com.test.uxp\"").count(), 1);
+
+        let listed = list_uxp_plugins();
+        assert_eq!(listed.len(), 1);
+        assert_eq!(listed[0].version, "1.1.0");
+        assert_eq!(listed[0].enabled, Some(true));
+
+        uninstall_uxp_plugin("com.test.uxp").unwrap();
+        assert!(installed_dirs("com.test.uxp").is_empty());
+        assert!(!fs::read_to_string(plugins_info_path("PS")).unwrap().contains("com.test.uxp"));
+        assert!(uninstall_uxp_plugin("com.test.uxp").is_err());
+    }
+
+    #[test]
+    fn failed_install_keeps_the_installed_version() {
+        let sandbox = Sandbox::new();
+        let good = sandbox.path("in/good.ccx");
+        write_zip(&good, &[("manifest.json", &plugin_manifest("1.0.0"))]);
+        install_uxp_plugin(&good.to_string_lossy()).unwrap();
+
+        // "a" is a file, so "a/b.js" cannot be extracted
+        let broken = sandbox.path("in/broken.ccx");
+        write_zip(&broken, &[("manifest.json", &plugin_manifest("2.0.0")), ("a", ""), ("a/b.js", "")]);
+        assert!(install_uxp_plugin(&broken.to_string_lossy()).is_err());
+        assert_eq!(installed_dirs("com.test.uxp"), vec![external_folder().join("com.test.uxp_1.0.0")]);
+    }
+
+    #[test]
+    fn listing_merges_folders_with_the_registry() {
+        let sandbox = Sandbox::new();
+        let user = sandbox.path("uxp");
+        let external = user.join("Plugins/External/com.test.uxp_1.0.0");
+        fs::create_dir_all(&external).unwrap();
+        fs::write(external.join("manifest.json"), plugin_manifest("1.0.0")).unwrap();
+        let system = sandbox.path("uxp-system/Plugins/External/com.test.shared");
+        fs::create_dir_all(&system).unwrap();
+        fs::write(system.join("manifest.json"), r#"{"id":"com.test.shared","name":"Shared","host":{"app":"ID"}}"#).unwrap();
+        fs::create_dir_all(user.join("PluginsInfo/v1")).unwrap();
+        let registry = |plugins: serde_json::Value| json!({ "plugins": plugins }).to_string();
+        fs::write(
+            user.join("PluginsInfo/v1/PS.json"),
+            registry(json!([
+                { "pluginId": "com.test.uxp", "path": "$localPlugins\\External\\com.test.uxp_1.0.0", "status": "disabled" },
+                { "pluginId": "com.test.marketplace", "name": "Marketplace", "versionString": "4.0.0", "status": "enabled" },
+            ])),
+        )
+        .unwrap();
+        fs::write(
+            user.join("PluginsInfo/v1/PPRO.json"),
+            registry(json!([{ "pluginId": "com.test.uxp", "path": "$localPlugins/External/com.test.uxp_1.0.0", "status": "enabled" }])),
+        )
+        .unwrap();
+
+        let plugins = list_uxp_plugins();
+        let find = |id: &str| plugins.iter().find(|p| p.id == id).unwrap();
+        assert_eq!(plugins.len(), 3);
+        let local = find("com.test.uxp");
+        assert_eq!(local.enabled, Some(true));
+        let mut hosts = local.registered_in.clone();
+        hosts.sort();
+        assert_eq!(hosts, vec!["PPRO".to_string(), "PS".to_string()]);
+        let marketplace = find("com.test.marketplace");
+        assert_eq!((marketplace.version.as_str(), marketplace.install_path.is_none()), ("4.0.0", true));
+        assert_eq!(marketplace.hosts[0].name, "Photoshop");
+        let shared = find("com.test.shared");
+        assert!(shared.system && shared.enabled.is_none());
+    }
+}
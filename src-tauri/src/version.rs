@@ -0,0 +1,121 @@
+use std::cmp::Ordering;
+
+/// Compare two extension version strings ("1.2", "1.10.0", "2.0.1.45")
+///
+/// Segments are compared numerically and missing segments count as 0. A
+/// non-numeric suffix ("1.0.0-beta") sorts before the plain release.
+pub fn compare_versions(a: &str, b: &str) -> Ordering {
+    let pa: Vec<&str> = a.trim().split('.').collect();
+    let pb: Vec<&str> = b.trim().split('.').collect();
+
+    for i in 0..pa.len().max(pb.len()) {
+        let sa = pa.get(i).copied().unwrap_or("0");
+        let sb = pb.get(i).copied().unwrap_or("0");
+        let ord = match (split_segment(sa), split_segment(sb)) {
+            ((Some(na), xa), (Some(nb), xb)) => na.cmp(&nb).then_with(|| match (xa.is_empty(), xb.is_empty()) {
+                (true, false) => Ordering::Greater,
+                (false, true) => Ordering::Less,
+                _ => xa.cmp(xb),
+            }),
+            _ => sa.cmp(sb),
+        };
+        if ord != Ordering::Equal {
+            return ord;
+        }
+    }
+    Ordering::Equal
+}
+
+/// True if `candidate` is strictly newer than `installed`
+pub fn is_newer(candidate: &str, installed: &str) -> bool {
+    compare_versions(candidate, installed) == Ordering::Greater
+}
+
+/// Split "12-beta" into (Some(12), "-beta")
+fn split_segment(segment: &str) -> (Option<u64>, &str) {
+    let end = segment
+        .find(|c: char| !c.is_ascii_digit())
+        .unwrap_or(segment.len());
+    (segment[..end].parse().ok(), &segment[end..])
+}
+
+/// Check a version against a constraint such as `"*"`, `"1.2.0"`, `">=1.2, <2"`,
+/// `"^1.2"` (same major) or `"~1.2"` (same major.minor)
+pub fn satisfies(version: &str, constraint: &str) -> Result<bool, String> {
+    for part in constraint.split(',').map(str::trim).filter(|p| !p.is_empty()) {
+        if !satisfies_one(version, part)? {
+            return Ok(false);
+        }
+    }
+    Ok(true)
+}
+
+fn satisfies_one(version: &str, comparator: &str) -> Result<bool, String> {
+    if comparator == "*" {
+        return Ok(true);
+    }
+    let (op, target) = ["^", "~", ">=", "<=", ">", "<", "="]
+        .iter()
+        .find_map(|op| comparator.strip_prefix(op).map(|rest| (*op, rest.trim())))
+        .unwrap_or(("=", comparator));
+    if target.is_empty() {
+        return Err(format!("Invalid version constraint '{}'", comparator));
+    }
+
+    let ord = compare_versions(version, target);
+    Ok(match op {
+        ">=" => ord != Ordering::Less,
+        "<=" => ord != Ordering::Greater,
+        ">" => ord == Ordering::Greater,
+        "<" => ord == Ordering::Less,
+        "=" => ord == Ordering::Equal,
+        // ^1.2 → >=1.2, <2 and ~1.2 → >=1.2, <1.3
+        _ => {
+            let keep = if op == "^" { 1 } else { 2 };
+            let segments: Vec<&str> = target.split('.').collect();
+            let prefix = &segments[..keep.min(segments.len())];
+            let parts: Vec<&str> = version.trim().split('.').collect();
+            let same_prefix = prefix.iter().enumerate().all(|(i, p)| {
+                split_segment(parts.get(i).copied().unwrap_or("0")).0 == split_segment(p).0
+            });
+            ord != Ordering::Less && same_prefix
+        }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn satisfies_exact_and_wildcard() {
+        assert!(satisfies("1.2.0", "*").unwrap());
+        assert!(satisfies("1.2.0", "").unwrap());
+        assert!(satisfies("1.2.0", "1.2.0").unwrap());
+        assert!(satisfies("1.2", "=1.2.0").unwrap());
+        assert!(!satisfies("1.2.1", "1.2.0").unwrap());
+    }
+
+    #[test]
+    fn satisfies_ranges() {
+        assert!(satisfies("1.5.0", ">=1.2, <2").unwrap());
+        assert!(!satisfies("2.0.0", ">=1.2, <2").unwrap());
+        assert!(!satisfies("1.1.9", ">=1.2, <2").unwrap());
+        assert!(satisfies("1.2.0", "<=1.2").unwrap());
+        assert!(satisfies("1.2.1", ">1.2").unwrap());
+    }
+
+    #[test]
+    fn satisfies_caret_and_tilde() {
+        assert!(satisfies("1.9.3", "^1.2").unwrap());
+        assert!(!satisfies("2.0.0", "^1.2").unwrap());
+        assert!(!satisfies("1.1.0", "^1.2").unwrap());
+        assert!(satisfies("1.2.7", "~1.2").unwrap());
+        assert!(!satisfies("1.3.0", "~1.2").unwrap());
+    }
+
+    #[test]
+    fn satisfies_rejects_empty_comparator() {
+        assert!(satisfies("1.0.0", ">=").is_err());
+    }
+}
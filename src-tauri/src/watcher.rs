@@ -0,0 +1,174 @@
+use crate::installer::{self, ExtensionInfo};
+use crate::locale;
+use crate::scan_cache;
+use crate::settings::{self, FilterRules};
+use notify_debouncer_mini::notify::RecursiveMode;
+use notify_debouncer_mini::{new_debouncer, DebounceEventResult};
+use std::collections::{HashMap, HashSet};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::mpsc;
+use std::time::Duration;
+use tauri::{AppHandle, Emitter};
+
+/// Quiet period before a burst of filesystem changes is processed —
+/// installers write hundreds of files in quick succession
+const DEBOUNCE: Duration = Duration::from_millis(750);
+
+/// Visible extensions by directory, as last reported to the frontend
+type Snapshot = HashMap<PathBuf, ExtensionInfo>;
+
+/// Parse a directory and apply the filter rules; None if missing or hidden
+fn read_visible(root: &Path, dir: &Path, rules: &FilterRules) -> Option<ExtensionInfo> {
+    let mut info = installer::read_extension_dir(dir, &locale::preferred_language())?;
+    if installer::apply_filter_rules(root, &mut info, rules) {
+        return None;
+    }
+    Some(info)
+}
+
+/// Initial snapshot of a root — unchanged directories come from the scan index
+fn scan_root(root: &Path, rules: &FilterRules, snapshot: &mut Snapshot) {
+    let Ok(entries) = fs::read_dir(root) else { return };
+    let dirs: Vec<PathBuf> = entries.flatten().map(|e| e.path()).collect();
+    let infos = scan_cache::read_dirs(&dirs, &locale::preferred_language());
+    for (dir, info) in dirs.into_iter().zip(infos) {
+        let Some(mut info) = info else { continue };
+        if !installer::apply_filter_rules(root, &mut info, rules) {
+            snapshot.insert(dir, info);
+        }
+    }
+}
+
+/// Map a changed path to the extension directory (direct child of a root) it belongs to
+fn extension_dir_for(path: &Path, roots: &[PathBuf]) -> Option<(PathBuf, PathBuf)> {
+    roots.iter().find_map(|root| {
+        let rel = path.strip_prefix(root).ok()?;
+        let first = rel.components().next()?;
+        Some((root.clone(), root.join(first)))
+    })
+}
+
+/// Re-parse one extension directory and emit the matching event
+fn refresh_dir(app: &AppHandle, root: &Path, dir: &Path, rules: &FilterRules, snapshot: &mut Snapshot) {
+    let current = read_visible(root, dir, rules);
+    let previous = snapshot.get(dir);
+
+    let event = match (previous, &current) {
+        (None, Some(info)) => Some(("extension-added", info.clone())),
+        (Some(old), None) => Some(("extension-removed", old.clone())),
+        (Some(old), Some(info)) if old != info => Some(("extension-changed", info.clone())),
+        _ => None,
+    };
+
+    match current {
+        Some(info) => {
+            snapshot.insert(dir.to_path_buf(), info);
+        }
+        None => {
+            snapshot.remove(dir);
+        }
+    }
+
+    if let Some((name, info)) = event {
+        if let Err(e) = app.emit(name, info) {
+            log::warn!("Cannot emit {}: {}", name, e);
+        }
+    }
+}
+
+/// Watch every CEP extensions folder and push add/remove/change events to the frontend
+pub fn start(app: AppHandle) {
+    std::thread::spawn(move || {
+        // The user folder is the install target — create it so it can be watched from the start
+        let _ = fs::create_dir_all(installer::get_extensions_folder());
+        let roots: Vec<PathBuf> = installer::get_all_extension_folders()
+            .into_iter()
+            .filter(|r| r.exists())
+            .collect();
+
+        let (tx, rx) = mpsc::channel::<DebounceEventResult>();
+        let mut debouncer = match new_debouncer(DEBOUNCE, tx) {
+            Ok(d) => d,
+            Err(e) => {
+                log::error!("Cannot start extension folder watcher: {}", e);
+                return;
+            }
+        };
+        for root in &roots {
+            if let Err(e) = debouncer.watcher().watch(root, RecursiveMode::Recursive) {
+                log::warn!("Cannot watch {}: {}", root.display(), e);
+            }
+        }
+
+        let mut snapshot = Snapshot::new();
+        let rules = settings::load_settings().filter_rules;
+        for root in &roots {
+            scan_root(root, &rules, &mut snapshot);
+        }
+
+        for result in rx {
+            let events = match result {
+                Ok(events) => events,
+                Err(e) => {
+                    log::warn!("Extension folder watcher error: {}", e);
+                    continue;
+                }
+            };
+
+            let affected: HashSet<(PathBuf, PathBuf)> = events
+                .iter()
+                .filter_map(|event| extension_dir_for(&event.path, &roots))
+                .collect();
+            // Re-read each batch so rule edits in settings apply without a restart
+            let rules = settings::load_settings().filter_rules;
+            for (root, dir) in affected {
+                refresh_dir(&app, &root, &dir, &rules, &mut snapshot);
+            }
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::{manifest, Sandbox};
+
+    #[test]
+    fn maps_changes_to_their_extension_dir() {
+        let roots = vec![PathBuf::from("/ext"), PathBuf::from("/sys/ext")];
+        assert_eq!(
+            extension_dir_for(Path::new("/ext/com.a/js/main.js"), &roots),
+            Some((PathBuf::from("/ext"), PathBuf::from("/ext/com.a")))
+        );
+        assert_eq!(
+            extension_dir_for(Path::new("/sys/ext/com.b"), &roots),
+            Some((PathBuf::from("/sys/ext"), PathBuf::from("/sys/ext/com.b")))
+        );
+        assert_eq!(extension_dir_for(Path::new("/ext"), &roots), None);
+        assert_eq!(extension_dir_for(Path::new("/other/com.c"), &roots), None);
+    }
+
+    #[test]
+    fn initial_scan_skips_hidden_and_invalid_dirs() {
+        let _sandbox = Sandbox::new();
+        let root = installer::get_extensions_folder();
+        for id in ["com.test.shown", "com.test.hidden"] {
+            fs::create_dir_all(root.join(id).join("CSXS")).unwrap();
+            fs::write(root.join(id).join("CSXS/manifest.xml"), manifest(id, "1.0.0")).unwrap();
+        }
+        fs::create_dir_all(root.join("not-an-extension")).unwrap();
+        let rules = FilterRules {
+            hidden_vendors: Vec::new(),
+            always_show_ids: Vec::new(),
+            builtin_id_prefixes: vec!["com.test.hidden".to_string()],
+            builtin_system_only: false,
+            hidden_folders: Vec::new(),
+        };
+
+        let mut snapshot = Snapshot::new();
+        scan_root(&root, &rules, &mut snapshot);
+        assert_eq!(snapshot.len(), 1);
+        assert_eq!(snapshot[&root.join("com.test.shown")].id, "com.test.shown");
+    }
+}
@@ -9,7 +9,7 @@ import { LibraryView } from "./components/LibraryView";
 import { ExtensionDetail } from "./components/ExtensionDetail";
 import { SettingsPanel } from "./components/SettingsPanel";
 import { InstallToast } from "./components/InstallToast";
-import type { ExtensionInfo, InstallResult, ToastState } from "./types";
+import type { ExtensionInfo, InstallResult, ToastState, UxpPlugin } from "./types";
 
 type Tab = "install" | "library";
 
@@ -17,6 +17,7 @@ export default function App() {
   const [tab, setTab] = useState<Tab>("install");
   const [showSettings, setShowSettings] = useState(false);
   const [extensions, setExtensions] = useState<ExtensionInfo[]>([]);
+  const [uxpPlugins, setUxpPlugins] = useState<UxpPlugin[]>([]);
   const [loadingLibrary, setLoadingLibrary] = useState(true);
   const [installing, setInstalling] = useState(false);
   const [dragOver, setDragOver] = useState(false);
@@ -28,6 +29,7 @@ export default function App() {
     try {
       const list = await invoke<ExtensionInfo[]>("list_extensions");
       setExtensions(list);
+      setUxpPlugins(await invoke<UxpPlugin[]>("list_uxp_plugins"));
     } catch (e) {
       console.error(e);
     } finally {
@@ -35,7 +37,38 @@ export default function App() {
     }
   }, []);
 
-  useEffect(() => { loadExtensions(); }, [loadExtensions]);
+  // First load shows the cached listing instantly; the backend rescans and pushes changes
+  useEffect(() => {
+    invoke<ExtensionInfo[]>("list_extensions_cached")
+      .then(setExtensions)
+      .catch(console.error)
+      .finally(() => setLoadingLibrary(false));
+    invoke<UxpPlugin[]>("list_uxp_plugins").then(setUxpPlugins).catch(console.error);
+    const unlisten = listen<ExtensionInfo[]>("extensions-refreshed", (event) => setExtensions(event.payload));
+    return () => { unlisten.then(fn => fn()); };
+  }, []);
+
+  // Live updates when other tools (or the user) add/remove panels on disk
+  useEffect(() => {
+    const sameFolder = (a: ExtensionInfo, b: ExtensionInfo) =>
+      a.install_path && b.install_path ? a.install_path === b.install_path : a.id === b.id;
+    const upsert = (ext: ExtensionInfo) => {
+      setExtensions(list =>
+        [...list.filter(e => !sameFolder(e, ext) && e.id !== ext.id), ext].sort((a, b) => a.name.localeCompare(b.name))
+      );
+      setSelectedExt(selected => (selected && sameFolder(selected, ext) ? ext : selected));
+    };
+    const remove = (ext: ExtensionInfo) => {
+      setExtensions(list => list.filter(e => !sameFolder(e, ext)));
+      setSelectedExt(selected => (selected && sameFolder(selected, ext) ? null : selected));
+    };
+    const unlisteners = [
+      listen<ExtensionInfo>("extension-added", event => upsert(event.payload)),
+      listen<ExtensionInfo>("extension-changed", event => upsert(event.payload)),
+      listen<ExtensionInfo>("extension-removed", event => remove(event.payload)),
+    ];
+    return () => { unlisteners.forEach(p => p.then(fn => fn())); };
+  }, []);
 
   const showToast = (state: ToastState) => {
     setToast(state);
@@ -46,6 +79,12 @@ export default function App() {
     setInstalling(true);
     setLastInstalled(null);
     try {
+      if (filePath.toLowerCase().endsWith(".ccx")) {
+        const plugin = await invoke<UxpPlugin>("install_uxp_plugin", { path: filePath });
+        showToast({ type: "success", message: `${plugin.name} ${plugin.version} installed.` });
+        await loadExtensions();
+        return;
+      }
       const result = await invoke<InstallResult>("install_extension", { path: filePath });
       if (result.success && result.extension) {
         setLastInstalled(result.extension);
@@ -63,7 +102,7 @@ export default function App() {
   const handlePickFile = async () => {
     const selected = await open({
       multiple: false,
-      filters: [{ name: "Adobe Extension", extensions: ["zxp", "zxpinstall"] }],
+      filters: [{ name: "Adobe Extension", extensions: ["zxp", "zxpinstall", "ccx"] }],
     });
     if (selected && typeof selected === "string") await handleInstall(selected);
   };
@@ -80,6 +119,16 @@ export default function App() {
     }
   };
 
+  const handleUninstallUxp = async (plugin: UxpPlugin) => {
+    try {
+      await invoke("uninstall_uxp_plugin", { pluginId: plugin.id });
+      showToast({ type: "success", message: `${plugin.name} removed successfully.` });
+      await loadExtensions();
+    } catch (e) {
+      showToast({ type: "error", message: String(e) });
+    }
+  };
+
   useEffect(() => {
     let unlistenHover: (() => void) | undefined;
     let unlistenDrop: (() => void) | undefined;
@@ -93,10 +142,10 @@ export default function App() {
       const paths = event.payload.paths;
       if (!paths?.length) return;
       const path = paths[0];
-      if (path.endsWith(".zxp") || path.endsWith(".zxpinstall")) {
+      if ([".zxp", ".zxpinstall", ".ccx"].some(ext => path.toLowerCase().endsWith(ext))) {
         await handleInstall(path);
       } else {
-        showToast({ type: "error", message: "Please drop a .zxp or .zxpinstall file." });
+        showToast({ type: "error", message: "Please drop a .zxp, .zxpinstall or .ccx file." });
       }
     }).then(fn => { unlistenDrop = fn; });
 
@@ -299,9 +348,11 @@ export default function App() {
             >
               <LibraryView
                 extensions={extensions}
+                uxpPlugins={uxpPlugins}
                 loading={loadingLibrary}
                 onSelect={setSelectedExt}
                 onUninstall={handleUninstall}
+                onUninstallUxp={handleUninstallUxp}
                 onRefresh={loadExtensions}
               />
             </motion.div>
@@ -383,7 +434,7 @@ export default function App() {
                   Drop to install
                 </p>
                 <p className="text-[13px] mt-1.5 font-mono tracking-widest" style={{ color: "var(--text-3)" }}>
-                  .zxp · .zxpinstall
+                  .zxp · .zxpinstall · .ccx
                 </p>
               </div>
             </div>
@@ -43,7 +43,7 @@ export function DropZone({ installing, dragOver, onPickFile }: DropZoneProps) {
             {installing ? "Kuruluyor…" : "ZXP dosyası seç veya sürükle bırak"}
           </p>
           {!installing && (
-            <p className="text-xs text-white/30 mt-0.5">.zxp · .zxpinstall</p>
+            <p className="text-xs text-white/30 mt-0.5">.zxp · .zxpinstall · .ccx</p>
           )}
         </div>
       </motion.button>
@@ -1,7 +1,8 @@
 import { motion } from "framer-motion";
-import { X, Trash2, FolderOpen } from "lucide-react";
+import { X, Trash2, FolderOpen, Bug, Stethoscope } from "lucide-react";
+import { useEffect, useState } from "react";
 import { invoke, convertFileSrc } from "@tauri-apps/api/core";
-import type { ExtensionInfo } from "../types";
+import type { CheckStatus, DebugConfig, DiagnosticsReport, ExtensionInfo } from "../types";
 import { AppBadge, ExtIconFallback } from "../lib/appColors";
 
 interface ExtensionDetailProps {
@@ -34,6 +35,155 @@ function getPrimaryAccent(hostList: { name: string }[]): string {
   return "#4f8df7";
 }
 
+// `.debug` ports for CEP remote debugging; ports can be edited before saving
+function DebugSection({ installPath }: { installPath: string }) {
+  const [config, setConfig] = useState<DebugConfig | null>(null);
+  const [error, setError] = useState<string | null>(null);
+
+  useEffect(() => {
+    invoke<DebugConfig>("get_debug_config", { installPath }).then(setConfig).catch(e => setError(String(e)));
+  }, [installPath]);
+
+  const setPort = (t: number, h: number, port: number) => {
+    if (!config) return;
+    const extensions = config.extensions.map((target, ti) => ti !== t ? target : {
+      ...target,
+      hosts: target.hosts.map((host, hi) => hi !== h ? host : { ...host, port }),
+    });
+    setConfig({ ...config, extensions });
+  };
+
+  const save = async () => {
+    try {
+      setConfig(await invoke<DebugConfig>("write_debug_file", { installPath, extensions: config?.extensions ?? null }));
+      setError(null);
+    } catch (e) { setError(String(e)); }
+  };
+
+  const remove = async () => {
+    try {
+      await invoke("remove_debug_file", { installPath });
+      setConfig(await invoke<DebugConfig>("get_debug_config", { installPath }));
+      setError(null);
+    } catch (e) { setError(String(e)); }
+  };
+
+  if (!config && !error) return null;
+
+  return (
+    <div style={{ padding: "11px 20px", borderTop: "1px solid var(--border-sub)" }}>
+      <p style={{ fontSize: 11, color: "var(--text-3)", fontWeight: 500, display: "flex", alignItems: "center", gap: 6 }}>
+        <Bug size={11} />
+        Remote debugging {config?.exists ? "· .debug present" : ""}
+      </p>
+      {config?.extensions.map((target, t) => (
+        <div key={target.extension_id} style={{ marginTop: 8 }}>
+          <p style={{ fontSize: 10, color: "var(--text-3)", fontFamily: "monospace", wordBreak: "break-all" }}>
+            {target.extension_id}
+          </p>
+          {target.hosts.map((host, h) => (
+            <div key={host.host} style={{ display: "flex", alignItems: "center", gap: 8, marginTop: 4, fontSize: 11, color: "var(--text-2)" }}>
+              <span style={{ width: 44, fontFamily: "monospace" }}>{host.host}</span>
+              <input
+                type="number"
+                value={host.port}
+                onChange={e => setPort(t, h, Number(e.target.value))}
+                style={{ width: 64, fontSize: 11, padding: "2px 6px", borderRadius: 6, background: "var(--elevated)", border: "1px solid var(--border)", color: "var(--text)" }}
+              />
+              {config.exists && (
+                <span style={{ fontFamily: "monospace", fontSize: 10, color: "var(--text-3)", userSelect: "text" }}>{host.url}</span>
+              )}
+            </div>
+          ))}
+        </div>
+      ))}
+      {error && <p style={{ fontSize: 11, color: "#f87171", marginTop: 8 }}>{error}</p>}
+      <div style={{ display: "flex", gap: 6, marginTop: 10 }}>
+        <button
+          onClick={save}
+          style={{ fontSize: 11, padding: "5px 10px", borderRadius: 8, color: "var(--text-2)", background: "var(--elevated)", border: "1px solid var(--border)", cursor: "pointer" }}
+        >
+          {config?.exists ? "Save ports" : "Create .debug"}
+        </button>
+        {config?.exists && (
+          <button
+            onClick={remove}
+            style={{ fontSize: 11, padding: "5px 10px", borderRadius: 8, color: "#f87171", background: "rgba(239,68,68,0.07)", border: "1px solid rgba(239,68,68,0.15)", cursor: "pointer" }}
+          >
+            Remove .debug
+          </button>
+        )}
+      </div>
+    </div>
+  );
+}
+
+const CHECK_COLOR: Record<CheckStatus, string> = {
+  pass: "#34d399",
+  warning: "#fbbf24",
+  fail: "#f87171",
+  skipped: "var(--text-3)",
+};
+
+const CHECK_MARK: Record<CheckStatus, string> = {
+  pass: "✓",
+  warning: "!",
+  fail: "✕",
+  skipped: "–",
+};
+
+// "Why isn't my panel showing" checklist, run on demand
+function DiagnosticsSection({ installPath }: { installPath: string }) {
+  const [report, setReport] = useState<DiagnosticsReport | null>(null);
+  const [error, setError] = useState<string | null>(null);
+  const [running, setRunning] = useState(false);
+
+  const run = async () => {
+    setRunning(true);
+    try {
+      setReport(await invoke<DiagnosticsReport>("diagnose_extension", { installPath }));
+      setError(null);
+    } catch (e) { setError(String(e)); }
+    finally { setRunning(false); }
+  };
+
+  return (
+    <div style={{ padding: "11px 20px", borderTop: "1px solid var(--border-sub)" }}>
+      <div style={{ display: "flex", alignItems: "center", justifyContent: "space-between" }}>
+        <p style={{ fontSize: 11, color: "var(--text-3)", fontWeight: 500, display: "flex", alignItems: "center", gap: 6 }}>
+          <Stethoscope size={11} />
+          Not showing up?
+        </p>
+        <button
+          onClick={run}
+          disabled={running}
+          style={{ fontSize: 11, padding: "5px 10px", borderRadius: 8, color: "var(--text-2)", background: "var(--elevated)", border: "1px solid var(--border)", cursor: "pointer" }}
+        >
+          {running ? "Checking…" : report ? "Check again" : "Run diagnostics"}
+        </button>
+      </div>
+      {report?.likely_cause && (
+        <p style={{ fontSize: 11, color: "#f87171", marginTop: 8 }}>Likely cause: {report.likely_cause}</p>
+      )}
+      {report?.checks.map(c => (
+        <div key={c.id} style={{ display: "flex", gap: 8, marginTop: 8 }}>
+          <span style={{ width: 12, fontSize: 11, fontWeight: 700, color: CHECK_COLOR[c.status], flexShrink: 0 }}>
+            {CHECK_MARK[c.status]}
+          </span>
+          <div style={{ minWidth: 0 }}>
+            <p style={{ fontSize: 11, color: "var(--text-2)", fontWeight: 500 }}>{c.title}</p>
+            <p style={{ fontSize: 10, color: "var(--text-3)", marginTop: 2, wordBreak: "break-word", lineHeight: 1.5 }}>{c.detail}</p>
+            {c.fix && c.status !== "pass" && (
+              <p style={{ fontSize: 10, color: CHECK_COLOR[c.status], marginTop: 2 }}>{c.fix}</p>
+            )}
+          </div>
+        </div>
+      ))}
+      {error && <p style={{ fontSize: 11, color: "#f87171", marginTop: 8 }}>{error}</p>}
+    </div>
+  );
+}
+
 export function ExtensionDetail({ ext, onClose, onUninstall }: ExtensionDetailProps) {
   const handleOpenFolder = async () => {
     try { await invoke("open_extensions_folder"); } catch (e) { console.error(e); }
@@ -219,6 +369,8 @@ export function ExtensionDetail({ ext, onClose, onUninstall }: ExtensionDetailPr
               </span>
             </div>
           ))}
+          {ext.install_path && <DiagnosticsSection installPath={ext.install_path} />}
+          {ext.install_path && <DebugSection installPath={ext.install_path} />}
         </div>
 
         {/* ── Actions ── */}
@@ -311,7 +311,7 @@ export function InstallView({ installing, lastInstalled, onPickFile, onDismiss,
                   className="text-[11px] font-mono tracking-widest"
                   style={{ color: "var(--text-3)", opacity: 0.6 }}
                 >
-                  .zxp · .zxpinstall
+                  .zxp · .zxpinstall · .ccx
                 </p>
               </div>
             </motion.button>
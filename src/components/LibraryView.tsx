@@ -2,14 +2,16 @@ import { motion, AnimatePresence } from "framer-motion";
 import { Package, RefreshCw, Trash2, ChevronRight } from "lucide-react";
 import { useState } from "react";
 import { convertFileSrc } from "@tauri-apps/api/core";
-import type { ExtensionInfo } from "../types";
+import type { ExtensionInfo, UxpPlugin } from "../types";
 import { AppBadge, ExtIconFallback } from "../lib/appColors";
 
 interface LibraryViewProps {
   extensions: ExtensionInfo[];
+  uxpPlugins: UxpPlugin[];
   loading: boolean;
   onSelect: (ext: ExtensionInfo) => void;
   onUninstall: (ext: ExtensionInfo) => void;
+  onUninstallUxp: (plugin: UxpPlugin) => void;
   onRefresh: () => Promise<void>;
 }
 
@@ -61,7 +63,73 @@ function SkeletonCard() {
   );
 }
 
-export function LibraryView({ extensions, loading, onSelect, onUninstall, onRefresh }: LibraryViewProps) {
+// Plugins installed for all users are managed by Creative Cloud; only per-user
+// (development) installs can be removed from here
+function UxpSection({ plugins, onUninstall }: { plugins: UxpPlugin[]; onUninstall: (plugin: UxpPlugin) => void }) {
+  return (
+    <div className="mt-5">
+      <p className="text-[11px] font-semibold uppercase px-1 mb-2" style={{ color: "var(--text-3)", letterSpacing: "0.06em" }}>
+        UXP plugins · {plugins.length}
+      </p>
+      <div className="flex flex-col gap-2">
+        {plugins.map(plugin => (
+          <div
+            key={`${plugin.id}-${plugin.install_path ?? plugin.registered_in.join()}`}
+            className="flex items-center gap-4 rounded-xl px-4 py-3"
+            style={{ background: "var(--card)", border: "1px solid var(--border-sub)" }}
+          >
+            <div className="flex-1 min-w-0">
+              <p className="text-[13px] font-semibold truncate" style={{ color: "var(--text)", letterSpacing: "-0.015em" }}>
+                {plugin.name}
+              </p>
+              <p className="text-[11px] mt-0.5 truncate" style={{ color: "var(--text-3)" }}>
+                v{plugin.version} · {plugin.id}
+                {plugin.manifest_version !== null && <span> · manifest v{plugin.manifest_version}</span>}
+                {plugin.system && <span> · all users</span>}
+              </p>
+              {plugin.hosts.length > 0 && (
+                <div className="flex items-center gap-1.5 mt-2 flex-wrap">
+                  {plugin.hosts.map(h => (
+                    <AppBadge key={h.app} name={h.name} size={24} />
+                  ))}
+                </div>
+              )}
+            </div>
+            {plugin.enabled !== null && (
+              <span
+                className="text-[10px] px-1.5 py-[2px] rounded-md font-medium shrink-0"
+                style={{
+                  background: "var(--elevated)",
+                  color: plugin.enabled ? "var(--text-2)" : "var(--text-3)",
+                  border: "1px solid var(--border-sub)",
+                }}
+              >
+                {plugin.enabled ? "Enabled" : "Disabled"}
+              </span>
+            )}
+            {!plugin.system && plugin.install_path !== null && (
+              <button
+                onClick={() => onUninstall(plugin)}
+                className="flex items-center gap-1.5 rounded-lg px-2.5 py-1.5 shrink-0"
+                style={{
+                  fontSize: 11,
+                  color: "var(--text-2)",
+                  background: "var(--surface)",
+                  border: "1px solid var(--border)",
+                }}
+              >
+                <Trash2 size={10} />
+                Remove
+              </button>
+            )}
+          </div>
+        ))}
+      </div>
+    </div>
+  );
+}
+
+export function LibraryView({ extensions, uxpPlugins, loading, onSelect, onUninstall, onUninstallUxp, onRefresh }: LibraryViewProps) {
   const [refreshing, setRefreshing] = useState(false);
   const [hoveredId, setHoveredId] = useState<string | null>(null);
 
@@ -79,7 +147,7 @@ export function LibraryView({ extensions, loading, onSelect, onUninstall, onRefr
     );
   }
 
-  if (extensions.length === 0) {
+  if (extensions.length === 0 && uxpPlugins.length === 0) {
     return (
       <div className="h-full flex flex-col items-center justify-center gap-5 pb-8">
         <div
@@ -286,6 +354,7 @@ export function LibraryView({ extensions, loading, onSelect, onUninstall, onRefr
             })}
           </AnimatePresence>
         </div>
+        {uxpPlugins.length > 0 && <UxpSection plugins={uxpPlugins} onUninstall={onUninstallUxp} />}
       </div>
     </div>
   );
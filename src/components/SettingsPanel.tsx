@@ -1,8 +1,10 @@
 import { useState, useEffect } from "react";
 import { invoke } from "@tauri-apps/api/core";
+import { save } from "@tauri-apps/plugin-dialog";
 import { check } from "@tauri-apps/plugin-updater";
 import { relaunch } from "@tauri-apps/plugin-process";
-import { FolderOpen, Bug, Shield, Download, Check, RefreshCw, Loader } from "lucide-react";
+import { FolderOpen, Bug, Shield, Download, Check, RefreshCw, Loader, LifeBuoy } from "lucide-react";
+import type { SupportBundle } from "../types";
 
 type UpdateStatus = "idle" | "checking" | "available" | "downloading" | "installing" | "up-to-date" | "error";
 
@@ -14,6 +16,8 @@ export function SettingsPanel() {
   const [updateVersion, setUpdateVersion] = useState("");
   const [updateProgress, setUpdateProgress] = useState(0);
   const [, setUpdateError] = useState("");
+  const [redactBundle, setRedactBundle] = useState(true);
+  const [bundleStatus, setBundleStatus] = useState<string | null>(null);
 
   useEffect(() => {
     invoke<boolean>("get_debug_mode").then(setDebugMode).finally(() => setDebugLoading(false));
@@ -34,6 +38,21 @@ export function SettingsPanel() {
     try { await invoke("open_extensions_folder"); } catch (e) { console.error(e); }
   };
 
+  const createSupportBundle = async () => {
+    const path = await save({
+      defaultPath: `zxp-magic-support-${new Date().toISOString().slice(0, 10)}.zip`,
+      filters: [{ name: "ZIP", extensions: ["zip"] }],
+    });
+    if (!path) return;
+    setBundleStatus("Collecting…");
+    try {
+      const bundle = await invoke<SupportBundle>("create_support_bundle", { path, options: { redact: redactBundle } });
+      setBundleStatus(`Saved ${bundle.entries.length} files to ${bundle.path}`);
+    } catch (e) {
+      setBundleStatus(String(e));
+    }
+  };
+
   const checkForUpdate = async () => {
     setUpdateStatus("checking");
     setUpdateError("");
@@ -320,6 +339,49 @@ export function SettingsPanel() {
               </div>
             )}
           </div>
+
+          {/* Support bundle */}
+          <div style={{ padding: "14px 16px", borderTop: "1px solid var(--border-sub)" }}>
+            <div style={{ display: "flex", alignItems: "flex-start", justifyContent: "space-between", gap: 16 }}>
+              <div style={{ display: "flex", alignItems: "flex-start", gap: 10 }}>
+                <LifeBuoy size={14} style={{ color: "var(--text-3)", flexShrink: 0, marginTop: 2 }} />
+                <div>
+                  <p style={{ fontSize: 13, fontWeight: 500, color: "var(--text)" }}>
+                    Support Bundle
+                  </p>
+                  <p style={{ fontSize: 11, color: "var(--text-3)", marginTop: 2, lineHeight: 1.5 }}>
+                    Manifests, CEP settings and logs, host apps and system info in one ZIP
+                  </p>
+                  <label style={{ display: "flex", alignItems: "center", gap: 6, fontSize: 11, color: "var(--text-2)", marginTop: 6, cursor: "pointer" }}>
+                    <input type="checkbox" checked={redactBundle} onChange={e => setRedactBundle(e.target.checked)} />
+                    Hide user name and home folder
+                  </label>
+                </div>
+              </div>
+              <button
+                onClick={createSupportBundle}
+                style={{
+                  flexShrink: 0,
+                  fontSize: 11,
+                  fontWeight: 500,
+                  color: "var(--accent)",
+                  background: "var(--accent-dim)",
+                  border: "1px solid rgba(79,141,247,0.2)",
+                  borderRadius: 8,
+                  padding: "5px 12px",
+                  cursor: "pointer",
+                  whiteSpace: "nowrap",
+                }}
+              >
+                Create
+              </button>
+            </div>
+            {bundleStatus && (
+              <p style={{ fontSize: 11, color: "var(--text-3)", marginTop: 8, wordBreak: "break-all", lineHeight: 1.5 }}>
+                {bundleStatus}
+              </p>
+            )}
+          </div>
         </Section>
 
         {/* -- About section -- */}
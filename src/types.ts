@@ -13,15 +13,315 @@ export interface ExtensionInfo {
   cep_version: string;
   install_path: string | null;
   icon_path: string | null;
+  is_builtin: boolean;
+  hidden_reason: string | null;
+  bundle_name: string;
+  vendor: string;
+  copyright: string;
+  required_runtimes: RuntimeRequirement[];
+  cef_parameters: string[];
+  node_enabled: boolean;
+  mixed_context: boolean;
+  locales: string[];
+  installed_at: number | null;
+  size_bytes: number;
+  file_count: number;
+  package_sha256: string | null;
+  security: SecurityReport;
+  dependencies: ManifestDependency[];
+}
+
+export type RiskLevel = "low" | "medium" | "high";
+
+export interface SecurityFinding {
+  level: RiskLevel;
+  message: string;
+}
+
+export interface SecurityReport {
+  level: RiskLevel;
+  findings: SecurityFinding[];
+  script_paths: string[];
+  remote_urls: string[];
+  network_files: string[];
+  process_files: string[];
+}
+
+export interface RuntimeRequirement {
+  name: string;
+  version: string;
+}
+
+export interface ManifestDependency {
+  id: string;
+  version: string | null;
 }
 
 export interface InstallResult {
   success: boolean;
   message: string;
   extension: ExtensionInfo | null;
+  payload: PayloadFile[];
+}
+
+export interface PayloadFile {
+  source: string;
+  path: string;
+  product: string;
 }
 
 export interface ToastState {
   type: "success" | "error";
   message: string;
 }
+
+export interface IntegrityReport {
+  extension_id: string;
+  install_path: string;
+  intact: boolean;
+  missing: string[];
+  modified: string[];
+  extra: string[];
+  can_repair: boolean;
+}
+
+export interface Settings {
+  catalog_sources: string[];
+  update_folders: string[];
+  filter_rules: FilterRules;
+  language: string | null;
+  extraction_limits: ExtractionLimits;
+  trash_retention_days: number;
+}
+
+export interface ExtractionLimits {
+  max_total_bytes: number;
+  max_entries: number;
+  max_file_bytes: number;
+  max_compression_ratio: number;
+}
+
+export interface FilterRules {
+  builtin_id_prefixes: string[];
+  builtin_system_only: boolean;
+  hidden_folders: string[];
+  hidden_vendors: string[];
+  always_show_ids: string[];
+}
+
+export interface CatalogPackage {
+  id: string;
+  name: string;
+  version: string;
+  description: string;
+  url: string;
+  sha256: string;
+  hosts: HostApp[];
+  changelog: string;
+}
+
+export type CatalogStatus = "installable" | "updatable" | "installed" | "newer";
+
+export interface CatalogEntry {
+  package: CatalogPackage;
+  source: string;
+  status: CatalogStatus;
+  installed_version: string | null;
+}
+
+export type UpdateSourceKind = "catalog" | "vendor_feed" | "folder";
+
+export interface UpdateInfo {
+  extension_id: string;
+  name: string;
+  installed_version: string;
+  available_version: string;
+  changelog: string;
+  source_kind: UpdateSourceKind;
+  source: string;
+  package: CatalogPackage;
+  system: boolean;
+}
+
+export type PlanAction = "install" | "upgrade" | "downgrade" | "remove" | "unchanged";
+
+export type PackageSource =
+  | { kind: "file"; path: string }
+  | { kind: "remote"; package: CatalogPackage }
+  | { kind: "archive"; archive: string; entry: string };
+
+export interface PlanItem {
+  action: PlanAction;
+  extension_id: string;
+  name: string;
+  installed_version: string | null;
+  target_version: string | null;
+  install_path: string | null;
+  source: PackageSource | null;
+  system: boolean;
+}
+
+export interface ActionResult {
+  extension_id: string;
+  action: PlanAction;
+  success: boolean;
+  message: string;
+}
+
+export type InstallScope = "user" | "system";
+
+export interface ProfileExtension {
+  id: string;
+  name: string;
+  version: string;
+  scope: InstallScope;
+  bundled: boolean;
+  debug: DebugTarget[] | null;
+}
+
+export interface Profile {
+  format: number;
+  created_at: number;
+  debug_mode: boolean;
+  extensions: ProfileExtension[];
+}
+
+export interface ImportPlan {
+  profile: Profile;
+  items: PlanItem[];
+  debug_mode_change: boolean | null;
+  debug_file_changes: DebugFileChange[];
+}
+
+export interface DebugFileChange {
+  extension_id: string;
+  extensions: DebugTarget[];
+}
+
+export interface ImportReport {
+  results: ActionResult[];
+  debug_mode_changed: boolean;
+  debug_files_changed: string[];
+  debug_file_errors: string[];
+}
+
+export interface SyncPlan {
+  items: PlanItem[];
+  unresolved: string[];
+}
+
+export interface ArchiveEntry {
+  path: string;
+  is_dir: boolean;
+  compressed_size: number;
+  size: number;
+  skipped: string | null;
+}
+
+export interface InstallPreview {
+  extension: ExtensionInfo;
+  entries: ArchiveEntry[];
+  file_count: number;
+  install_size: number;
+  compressed_size: number;
+  wrapper_folder: string | null;
+  target_path: string;
+  replaces: string[];
+  existing_size: number | null;
+  free_space: number | null;
+  enough_space: boolean | null;
+  limit_error: string | null;
+}
+
+export interface TrashItem {
+  id: string;
+  extension_id: string;
+  name: string;
+  version: string;
+  original_path: string;
+  trashed_at: number;
+}
+
+export type CompanionKind = "host_plugin" | "startup_script" | "font" | "extension";
+
+export interface DependencyStatus {
+  kind: CompanionKind;
+  name: string;
+  optional: boolean;
+  satisfied: boolean;
+  found_at: string[];
+  installed_version: string | null;
+  installable: boolean;
+  message: string | null;
+}
+
+export interface DependencyReport {
+  extension_id: string;
+  install_path: string;
+  items: DependencyStatus[];
+  missing: string[];
+}
+
+export interface UxpHost {
+  app: string;
+  name: string;
+  min_version: string | null;
+}
+
+export interface UxpPlugin {
+  id: string;
+  name: string;
+  version: string;
+  hosts: UxpHost[];
+  manifest_version: number | null;
+  install_path: string | null;
+  enabled: boolean | null;
+  registered_in: string[];
+  system: boolean;
+}
+
+export interface DebugHost {
+  host: string;
+  port: number;
+  url: string;
+}
+
+export interface DebugTarget {
+  extension_id: string;
+  hosts: DebugHost[];
+}
+
+export interface DebugConfig {
+  install_path: string;
+  exists: boolean;
+  extensions: DebugTarget[];
+}
+
+export type CheckStatus = "pass" | "warning" | "fail" | "skipped";
+
+export interface DiagnosticCheck {
+  id: string;
+  title: string;
+  status: CheckStatus;
+  detail: string;
+  fix: string | null;
+}
+
+export interface DiagnosticsReport {
+  extension_id: string;
+  install_path: string;
+  checks: DiagnosticCheck[];
+  likely_cause: string | null;
+}
+
+export interface SupportBundleOptions {
+  redact: boolean;
+  cep_log_limit_mb: number;
+  app_log_limit_mb: number;
+}
+
+export interface SupportBundle {
+  path: string;
+  entries: string[];
+  redacted: boolean;
+}